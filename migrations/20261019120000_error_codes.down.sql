@@ -0,0 +1,16 @@
+create or replace function
+  raise_immutability_exception()
+  returns trigger as $$
+begin
+  raise exception 'This update was rejected because it attempted to UPDATE an immutable column. Old: %, New: %', OLD, NEW
+  using hint = 'Tip: check the triggers on the table to see what columns are immutable';
+end $$
+language plpgsql;
+
+create or replace function
+  raise_undeletable_table_exception()
+  returns trigger as $$
+begin
+  raise exception 'DELETE is not allowed on this table';
+end $$
+language plpgsql;
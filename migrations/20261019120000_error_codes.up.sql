@@ -0,0 +1,22 @@
+--
+-- Raise trigger exceptions with dedicated SQLSTATE codes so the application can classify them.
+--
+
+create or replace function
+  raise_immutability_exception()
+  returns trigger as $$
+begin
+  raise exception 'This update was rejected because it attempted to UPDATE an immutable column. Old: %, New: %', OLD, NEW
+  using errcode = 'GX001',
+        hint = 'Tip: check the triggers on the table to see what columns are immutable';
+end $$
+language plpgsql;
+
+create or replace function
+  raise_undeletable_table_exception()
+  returns trigger as $$
+begin
+  raise exception 'DELETE is not allowed on this table'
+  using errcode = 'GX002';
+end $$
+language plpgsql;
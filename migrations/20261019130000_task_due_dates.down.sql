@@ -0,0 +1,3 @@
+drop index if exists tasks_overdue_index;
+
+alter table tasks drop column if exists due_at;
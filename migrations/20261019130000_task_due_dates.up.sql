@@ -0,0 +1,5 @@
+alter table tasks add column due_at timestamptz;
+
+-- Supports overdue queries across all stories without scanning completed tasks.
+create index tasks_overdue_index on tasks using btree(due_at, id)
+  where status = 'incomplete' and due_at is not null;
@@ -0,0 +1,4 @@
+drop index if exists tasks_story_position_index;
+
+alter table tasks drop column if exists position;
+alter table tasks drop column if exists priority;
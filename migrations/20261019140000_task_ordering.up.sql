@@ -0,0 +1,16 @@
+alter table tasks add column priority text not null default 'medium'
+  check (priority in ('low', 'medium', 'high', 'urgent'));
+
+-- Fractional position keys: compared bytewise, so a task can be moved between two siblings by
+-- updating only its own row.
+alter table tasks add column position text collate "C";
+
+-- Order existing tasks by creation time. Keys must not end with the '0' digit.
+update tasks set position = t.position from (
+  select id, 'V' || lpad(row_number() over (partition by story_id order by created_at, id)::text, 10, '0') || 'V' as position
+  from tasks
+) t where tasks.id = t.id;
+
+alter table tasks alter column position set not null;
+
+create unique index tasks_story_position_index on tasks using btree(story_id, position);
@@ -0,0 +1,3 @@
+drop table if exists task_labels cascade;
+drop table if exists story_labels cascade;
+drop table if exists labels cascade;
@@ -0,0 +1,26 @@
+create table labels (
+  id uuid default gen_random_uuid() primary key,
+  name text not null
+);
+
+create unique index labels_name_index on labels using btree(lower(name));
+
+select add_timestamp_columns('labels');
+
+select set_immutable_columns('labels', 'id', 'created_at');
+
+create table story_labels (
+  story_id uuid references stories(id) on delete cascade not null,
+  label_id uuid references labels(id) on delete cascade not null,
+  primary key (story_id, label_id)
+);
+
+create index story_labels_label_id_index on story_labels using btree(label_id);
+
+create table task_labels (
+  task_id uuid references tasks(id) on delete cascade not null,
+  label_id uuid references labels(id) on delete cascade not null,
+  primary key (task_id, label_id)
+);
+
+create index task_labels_label_id_index on task_labels using btree(label_id);
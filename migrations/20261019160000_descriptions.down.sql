@@ -0,0 +1,3 @@
+alter table tasks drop column if exists description;
+
+alter table stories drop column if exists description;
@@ -0,0 +1,3 @@
+alter table stories add column description text not null default '';
+
+alter table tasks add column description text not null default '';
@@ -0,0 +1,9 @@
+drop index if exists tasks_parent_task_id_index;
+
+alter table tasks drop constraint if exists tasks_parent_task_check;
+
+alter table tasks drop constraint if exists tasks_parent_task_fkey;
+
+alter table tasks drop constraint if exists tasks_id_story_id_key;
+
+alter table tasks drop column if exists parent_task_id;
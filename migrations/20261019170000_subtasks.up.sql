@@ -0,0 +1,11 @@
+alter table tasks add column parent_task_id uuid;
+
+-- Lets subtasks reference their parent together with the story, so both must match.
+alter table tasks add constraint tasks_id_story_id_key unique (id, story_id);
+
+alter table tasks add constraint tasks_parent_task_fkey
+  foreign key (parent_task_id, story_id) references tasks(id, story_id) on delete cascade;
+
+alter table tasks add constraint tasks_parent_task_check check (parent_task_id <> id);
+
+create index tasks_parent_task_id_index on tasks using btree(parent_task_id);
@@ -0,0 +1,9 @@
+-- A task cannot be completed until all of its blockers are complete.
+create table task_dependencies (
+  task_id uuid references tasks(id) on delete cascade not null,
+  blocker_id uuid references tasks(id) on delete cascade not null,
+  primary key (task_id, blocker_id),
+  check (task_id <> blocker_id)
+);
+
+create index task_dependencies_blocker_id_index on task_dependencies using btree(blocker_id);
@@ -0,0 +1,3 @@
+drop table if exists task_comment_edits;
+
+drop table if exists task_comments;
@@ -0,0 +1,24 @@
+create table task_comments (
+  id uuid default gen_random_uuid() primary key,
+  task_id uuid references tasks(id) not null,
+  author text not null,
+  body text not null,
+  seqno bigint generated always as identity
+);
+
+create index task_comments_task_id_seqno_index on task_comments using btree(task_id, seqno);
+
+select add_timestamp_columns('task_comments');
+
+select set_immutable_columns('task_comments', 'id', 'task_id', 'author', 'created_at');
+
+-- Previous bodies of edited comments, kept for history.
+create table task_comment_edits (
+  id uuid default gen_random_uuid() primary key,
+  comment_id uuid references task_comments(id) on delete cascade not null,
+  body text not null,
+  replaced_at timestamptz not null default now()
+);
+
+create index task_comment_edits_comment_id_index on task_comment_edits
+  using btree(comment_id, replaced_at);
@@ -0,0 +1,3 @@
+drop index if exists tasks_assignee_index;
+
+alter table tasks drop column if exists assignee;
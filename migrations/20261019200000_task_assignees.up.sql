@@ -0,0 +1,4 @@
+alter table tasks add column assignee text;
+
+create index tasks_assignee_index on tasks using btree(assignee, created_at, id)
+  where assignee is not null;
@@ -0,0 +1,7 @@
+drop index if exists tasks_search_vector_index;
+
+alter table tasks drop column if exists search_vector;
+
+drop index if exists stories_search_vector_index;
+
+alter table stories drop column if exists search_vector;
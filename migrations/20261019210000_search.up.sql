@@ -0,0 +1,14 @@
+-- Weighted full-text search vectors: names rank above descriptions.
+alter table stories add column search_vector tsvector generated always as (
+  setweight(to_tsvector('english', name), 'A') ||
+  setweight(to_tsvector('english', coalesce(description, '')), 'B')
+) stored;
+
+create index stories_search_vector_index on stories using gin(search_vector);
+
+alter table tasks add column search_vector tsvector generated always as (
+  setweight(to_tsvector('english', name), 'A') ||
+  setweight(to_tsvector('english', coalesce(description, '')), 'B')
+) stored;
+
+create index tasks_search_vector_index on tasks using gin(search_vector);
@@ -0,0 +1 @@
+drop index if exists tasks_story_id_status_index;
@@ -0,0 +1 @@
+create index tasks_story_id_status_index on tasks using btree(story_id, status);
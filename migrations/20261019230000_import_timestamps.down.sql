@@ -0,0 +1,9 @@
+create or replace function
+  create_timestamps()
+  returns trigger as $$
+begin
+  new.created_at = now();
+  new.updated_at = now();
+  return new;
+end $$
+language plpgsql;
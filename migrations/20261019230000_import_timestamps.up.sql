@@ -0,0 +1,16 @@
+-- Bulk imports restore the original timestamps of stories and tasks. A transaction opts in with
+-- `SET LOCAL gsdx.keep_timestamps = 'on'`; any timestamps left unset still default to now.
+create or replace function
+  create_timestamps()
+  returns trigger as $$
+begin
+  if current_setting('gsdx.keep_timestamps', true) = 'on' then
+    new.created_at = coalesce(new.created_at, now());
+    new.updated_at = coalesce(new.updated_at, new.created_at);
+  else
+    new.created_at = now();
+    new.updated_at = now();
+  end if;
+  return new;
+end $$
+language plpgsql;
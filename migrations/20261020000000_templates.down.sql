@@ -0,0 +1 @@
+drop table if exists story_templates;
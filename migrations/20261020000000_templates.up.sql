@@ -0,0 +1,16 @@
+-- Standard checklists that stories are started from. Patterns may hold placeholders like {date}.
+create table story_templates (
+  id uuid default gen_random_uuid() primary key,
+  name text not null,
+  name_pattern text not null,
+  task_names text[] not null default '{}',
+  seqno bigint generated always as identity
+);
+
+create unique index story_templates_name_index on story_templates using btree(name);
+
+create index story_templates_seqno_index on story_templates using btree(seqno);
+
+select add_timestamp_columns('story_templates');
+
+select set_immutable_columns('story_templates', 'id', 'created_at');
@@ -0,0 +1,3 @@
+-- An RRULE subset like FREQ=WEEKLY;INTERVAL=2;COUNT=4. Completing a recurring task creates the
+-- next occurrence.
+alter table tasks add column recurrence text;
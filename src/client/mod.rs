@@ -0,0 +1,480 @@
+use crate::{
+    grpc::validate::AUTHOR_METADATA_KEY,
+    proto::{
+        CreateStoryRequest, CreateTaskRequest, DeleteStoryRequest, DeleteTaskRequest,
+        ListStoriesRequest, ListTasksRequest, TaskPriority, TaskStatus, UpdateStoryRequest,
+        UpdateTaskRequest, gsdx_service_client::GsdxServiceClient,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use prost_types::Timestamp;
+use std::{error::Error, fmt, fs, io::Write, path::PathBuf, time::Duration};
+use tonic::{
+    Code, Request, Status,
+    codec::CompressionEncoding::Gzip,
+    metadata::{AsciiMetadataValue, errors::InvalidMetadataValue},
+    service::{Interceptor, interceptor::InterceptedService},
+    transport::{Certificate, Channel, ClientTlsConfig, Endpoint},
+};
+use tonic_types::StatusExt;
+use uuid::Uuid;
+
+// Rendering of responses as tables or JSON.
+mod output;
+pub use output::Output;
+use output::Printer;
+
+/// How to reach and authenticate with a GSDX server.
+#[derive(Args, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClientArgs {
+    /// Server URL; https enables TLS
+    #[arg(long, env = "GSDX_ENDPOINT", default_value = "http://localhost:9090")]
+    pub endpoint: String,
+    /// PEM file with the CA certificate to trust, instead of the public web roots
+    #[arg(long, value_name = "FILE")]
+    pub tls_ca_cert: Option<PathBuf>,
+    /// Server name to verify the certificate against, when it differs from the endpoint host
+    #[arg(long, value_name = "NAME")]
+    pub tls_domain: Option<String>,
+    /// Bearer token sent in the authorization header, for servers behind an auth proxy
+    #[arg(long, env = "GSDX_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+    /// Name sent as the author of requests that record one
+    #[arg(long, env = "GSDX_AUTHOR")]
+    pub author: Option<String>,
+    /// Seconds to wait for each request
+    #[arg(long, value_name = "SECS", default_value_t = 10)]
+    pub timeout: u64,
+    /// How to print responses
+    #[arg(long, value_enum, default_value_t)]
+    pub output: Output,
+}
+
+/// Client subcommands, by resource.
+#[derive(Subcommand, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ClientCmd {
+    /// Manage stories
+    Stories {
+        #[command(subcommand)]
+        cmd: StoriesCmd,
+    },
+    /// Manage the tasks of a story
+    Tasks {
+        #[command(subcommand)]
+        cmd: TasksCmd,
+    },
+}
+
+/// Story subcommands.
+#[derive(Subcommand, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StoriesCmd {
+    /// List a page of stories
+    List {
+        /// An AIP-160 filter over name, created_at and updated_at
+        #[arg(long)]
+        filter: Option<String>,
+        /// One of name, created_at or updated_at, optionally followed by desc
+        #[arg(long)]
+        order_by: Option<String>,
+        /// The number of stories to fetch
+        #[arg(long)]
+        limit: Option<i64>,
+        /// The token of the page to fetch, from a previous list
+        #[arg(long)]
+        page_token: Option<String>,
+        /// Include task progress
+        #[arg(long)]
+        stats: bool,
+    },
+    /// Create a story
+    Create {
+        name: String,
+        /// The Markdown description
+        #[arg(long)]
+        description: Option<String>,
+    },
+    /// Rename a story, optionally changing its description
+    Update {
+        story_id: Uuid,
+        #[arg(long)]
+        name: String,
+        /// The Markdown description
+        #[arg(long)]
+        description: Option<String>,
+    },
+    /// Delete a story with all of its tasks
+    Delete { story_id: Uuid },
+}
+
+/// Task subcommands.
+#[derive(Subcommand, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TasksCmd {
+    /// List the tasks of a story in position order
+    List {
+        story_id: Uuid,
+        /// Only include tasks with any of these statuses
+        #[arg(long, value_enum)]
+        status: Vec<StatusArg>,
+    },
+    /// Create a task in a story
+    Create {
+        story_id: Uuid,
+        name: String,
+        #[arg(long, value_enum, default_value_t)]
+        status: StatusArg,
+        #[arg(long, value_enum)]
+        priority: Option<PriorityArg>,
+        /// When the task is due, as an RFC 3339 timestamp
+        #[arg(long)]
+        due_at: Option<DateTime<Utc>>,
+        /// The Markdown description
+        #[arg(long)]
+        description: Option<String>,
+        /// Create the task as a subtask of this task
+        #[arg(long)]
+        parent: Option<Uuid>,
+        /// Repeat the task by a rule like FREQ=WEEKLY;INTERVAL=2;COUNT=4
+        #[arg(long)]
+        recurrence: Option<String>,
+    },
+    /// Update a task, setting its status and any other fields given
+    Update {
+        task_id: Uuid,
+        #[arg(long, value_enum)]
+        status: StatusArg,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long, value_enum)]
+        priority: Option<PriorityArg>,
+        /// When the task is due, as an RFC 3339 timestamp
+        #[arg(long, conflicts_with = "clear_due_at")]
+        due_at: Option<DateTime<Utc>>,
+        /// Remove the due date
+        #[arg(long)]
+        clear_due_at: bool,
+        /// The Markdown description
+        #[arg(long)]
+        description: Option<String>,
+        /// Move the task under this parent task
+        #[arg(long, conflicts_with = "clear_parent")]
+        parent: Option<Uuid>,
+        /// Make the task top-level
+        #[arg(long)]
+        clear_parent: bool,
+        /// Repeat the task by a rule like FREQ=WEEKLY;INTERVAL=2;COUNT=4
+        #[arg(long, conflicts_with = "clear_recurrence")]
+        recurrence: Option<String>,
+        /// Stop the task repeating
+        #[arg(long)]
+        clear_recurrence: bool,
+        /// Complete the task even if it has incomplete subtasks, completing them too
+        #[arg(long)]
+        force: bool,
+    },
+    /// Delete a task with its subtasks
+    Delete { task_id: Uuid },
+}
+
+/// Task status flag values.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StatusArg {
+    #[default]
+    Incomplete,
+    Complete,
+}
+
+impl From<StatusArg> for TaskStatus {
+    fn from(status: StatusArg) -> Self {
+        match status {
+            StatusArg::Incomplete => TaskStatus::Incomplete,
+            StatusArg::Complete => TaskStatus::Complete,
+        }
+    }
+}
+
+/// Task priority flag values.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PriorityArg {
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl From<PriorityArg> for TaskPriority {
+    fn from(priority: PriorityArg) -> Self {
+        match priority {
+            PriorityArg::Low => TaskPriority::Low,
+            PriorityArg::Medium => TaskPriority::Medium,
+            PriorityArg::High => TaskPriority::High,
+            PriorityArg::Urgent => TaskPriority::Urgent,
+        }
+    }
+}
+
+/// A failed request, with any field violations the server reported.
+#[derive(Debug)]
+pub struct RequestFailed(Status);
+
+// Display the server message, or one line per field violation for invalid arguments.
+impl fmt::Display for RequestFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = &self.0;
+        if let Some(bad_request) = status.get_details_bad_request() {
+            write!(f, "invalid arguments:")?;
+            for violation in bad_request.field_violations {
+                write!(f, "\n  {}: {}", violation.field, violation.description)?;
+            }
+            return Ok(());
+        }
+        match status.code() {
+            Code::Unavailable => write!(f, "server unavailable: {}", status.message()),
+            _ => write!(f, "{}", status.message()),
+        }
+    }
+}
+
+impl Error for RequestFailed {}
+
+impl From<Status> for RequestFailed {
+    fn from(status: Status) -> Self {
+        Self(status)
+    }
+}
+
+/// Adds credentials from the command line to every request.
+#[derive(Clone)]
+struct Credentials {
+    authorization: Option<AsciiMetadataValue>,
+    author: Option<AsciiMetadataValue>,
+}
+
+impl Credentials {
+    fn new(args: &ClientArgs) -> Result<Self, InvalidMetadataValue> {
+        Ok(Self {
+            authorization: args
+                .token
+                .as_ref()
+                .map(|token| format!("Bearer {token}").parse())
+                .transpose()?,
+            author: args.author.as_deref().map(str::parse).transpose()?,
+        })
+    }
+}
+
+impl Interceptor for Credentials {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let metadata = request.metadata_mut();
+        if let Some(authorization) = &self.authorization {
+            metadata.insert("authorization", authorization.clone());
+        }
+        if let Some(author) = &self.author {
+            metadata.insert(AUTHOR_METADATA_KEY, author.clone());
+        }
+        Ok(request)
+    }
+}
+
+type Client = GsdxServiceClient<InterceptedService<Channel, Credentials>>;
+
+/// Connect to the server, using TLS for https endpoints or when a CA certificate is given.
+async fn connect(args: &ClientArgs) -> Result<Client, Box<dyn Error>> {
+    let mut endpoint =
+        Endpoint::from_shared(args.endpoint.clone())?.timeout(Duration::from_secs(args.timeout));
+    if args.endpoint.starts_with("https://") || args.tls_ca_cert.is_some() {
+        let mut tls = ClientTlsConfig::new().with_webpki_roots();
+        if let Some(path) = &args.tls_ca_cert {
+            let pem =
+                fs::read(path).map_err(|err| format!("cannot read {}: {err}", path.display()))?;
+            tls = tls.ca_certificate(Certificate::from_pem(pem));
+        }
+        if let Some(domain) = &args.tls_domain {
+            tls = tls.domain_name(domain);
+        }
+        endpoint = endpoint.tls_config(tls)?;
+    }
+    let channel = endpoint.connect().await.map_err(|err| {
+        format!(
+            "cannot connect to {}: {}",
+            args.endpoint,
+            source_message(&err)
+        )
+    })?;
+    let client = GsdxServiceClient::with_interceptor(channel, Credentials::new(args)?)
+        .send_compressed(Gzip)
+        .accept_compressed(Gzip);
+    Ok(client)
+}
+
+// The innermost cause of a transport error, which says what actually went wrong.
+fn source_message(err: &(dyn Error + 'static)) -> String {
+    let mut err = err;
+    while let Some(source) = err.source() {
+        err = source;
+    }
+    err.to_string()
+}
+
+/// Run a client subcommand against the server, printing responses to `out`.
+pub async fn run(args: &ClientArgs, cmd: ClientCmd, out: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut client = connect(args).await?;
+    let mut printer = Printer::new(args.output, out);
+    match cmd {
+        ClientCmd::Stories { cmd } => run_stories(&mut client, cmd, &mut printer).await,
+        ClientCmd::Tasks { cmd } => run_tasks(&mut client, cmd, &mut printer).await,
+    }
+}
+
+async fn run_stories(
+    client: &mut Client,
+    cmd: StoriesCmd,
+    printer: &mut Printer<impl Write>,
+) -> Result<(), Box<dyn Error>> {
+    match cmd {
+        StoriesCmd::List {
+            filter,
+            order_by,
+            limit,
+            page_token,
+            stats,
+        } => {
+            let request = ListStoriesRequest {
+                limit: limit.unwrap_or_default(),
+                filter: filter.unwrap_or_default(),
+                order_by: order_by.unwrap_or_default(),
+                page_token: page_token.unwrap_or_default(),
+                with_stats: stats,
+                ..ListStoriesRequest::default()
+            };
+            let response = client.list_stories(request).await.map_err(RequestFailed)?;
+            printer.stories(response.into_inner())?;
+        }
+        StoriesCmd::Create { name, description } => {
+            let request = CreateStoryRequest {
+                name,
+                description: description.unwrap_or_default(),
+            };
+            let response = client.create_story(request).await.map_err(RequestFailed)?;
+            printer.story(response.into_inner().story)?;
+        }
+        StoriesCmd::Update {
+            story_id,
+            name,
+            description,
+        } => {
+            let request = UpdateStoryRequest {
+                story_id: story_id.to_string(),
+                name,
+                description,
+            };
+            let response = client.update_story(request).await.map_err(RequestFailed)?;
+            printer.story(response.into_inner().story)?;
+        }
+        StoriesCmd::Delete { story_id } => {
+            let request = DeleteStoryRequest {
+                story_id: story_id.to_string(),
+            };
+            client.delete_story(request).await.map_err(RequestFailed)?;
+            printer.deleted("story", story_id)?;
+        }
+    }
+    Ok(())
+}
+
+async fn run_tasks(
+    client: &mut Client,
+    cmd: TasksCmd,
+    printer: &mut Printer<impl Write>,
+) -> Result<(), Box<dyn Error>> {
+    match cmd {
+        TasksCmd::List { story_id, status } => {
+            let request = ListTasksRequest {
+                story_id: story_id.to_string(),
+                statuses: status
+                    .into_iter()
+                    .map(|s| TaskStatus::from(s) as i32)
+                    .collect(),
+                ..ListTasksRequest::default()
+            };
+            let response = client.list_tasks(request).await.map_err(RequestFailed)?;
+            printer.tasks(response.into_inner().tasks)?;
+        }
+        TasksCmd::Create {
+            story_id,
+            name,
+            status,
+            priority,
+            due_at,
+            description,
+            parent,
+            recurrence,
+        } => {
+            let request = CreateTaskRequest {
+                story_id: story_id.to_string(),
+                name,
+                status: TaskStatus::from(status) as i32,
+                due_at: due_at.map(to_timestamp),
+                priority: priority_value(priority),
+                description: description.unwrap_or_default(),
+                parent_task_id: parent.map(|id| id.to_string()),
+                recurrence,
+            };
+            let response = client.create_task(request).await.map_err(RequestFailed)?;
+            printer.task(response.into_inner().task)?;
+        }
+        TasksCmd::Update {
+            task_id,
+            status,
+            name,
+            priority,
+            due_at,
+            clear_due_at,
+            description,
+            parent,
+            clear_parent,
+            recurrence,
+            clear_recurrence,
+            force,
+        } => {
+            let request = UpdateTaskRequest {
+                task_id: task_id.to_string(),
+                status: TaskStatus::from(status) as i32,
+                name,
+                due_at: due_at.map(to_timestamp),
+                clear_due_at,
+                priority: priority_value(priority),
+                description,
+                parent_task_id: parent.map(|id| id.to_string()),
+                clear_parent_task_id: clear_parent,
+                force,
+                recurrence,
+                clear_recurrence,
+            };
+            let response = client.update_task(request).await.map_err(RequestFailed)?;
+            let response = response.into_inner();
+            printer.updated_task(response.task, response.next_occurrence)?;
+        }
+        TasksCmd::Delete { task_id } => {
+            let request = DeleteTaskRequest {
+                task_id: task_id.to_string(),
+            };
+            client.delete_task(request).await.map_err(RequestFailed)?;
+            printer.deleted("task", task_id)?;
+        }
+    }
+    Ok(())
+}
+
+// A missing priority is unspecified, which the server treats as the default or unchanged.
+fn priority_value(priority: Option<PriorityArg>) -> i32 {
+    priority.map_or(TaskPriority::Unspecified, TaskPriority::from) as i32
+}
+
+fn to_timestamp(dt: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: dt.timestamp(),
+        nanos: dt.timestamp_subsec_nanos() as i32,
+    }
+}
@@ -0,0 +1,331 @@
+use crate::proto::{LabelData, ListStoriesResponse, StoryData, TaskData, TaskPriority, TaskStatus};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use prost_types::Timestamp;
+use serde_json::{Value, json};
+use std::{collections::HashMap, fmt::Display, io, io::Write};
+
+/// How responses are printed.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Output {
+    /// Aligned columns for reading
+    #[default]
+    Table,
+    /// One JSON document per response, for scripts
+    Json,
+}
+
+/// Prints responses in the chosen output format.
+pub(super) struct Printer<W: Write> {
+    output: Output,
+    out: W,
+}
+
+impl<W: Write> Printer<W> {
+    pub fn new(output: Output, out: W) -> Self {
+        Self { output, out }
+    }
+
+    /// Print a page of stories, with the token for the next page.
+    pub fn stories(&mut self, page: ListStoriesResponse) -> io::Result<()> {
+        match self.output {
+            Output::Json => {
+                let stories: Vec<Value> = page.stories.into_iter().map(story_json).collect();
+                self.json(json!({
+                    "stories": stories,
+                    "next_page_token": page.next_page_token,
+                }))
+            }
+            Output::Table => {
+                self.story_table(page.stories)?;
+                if !page.next_page_token.is_empty() {
+                    writeln!(self.out, "\nnext page token: {}", page.next_page_token)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Print a single story.
+    pub fn story(&mut self, story: Option<StoryData>) -> io::Result<()> {
+        match self.output {
+            Output::Json => self.json(story.map_or(Value::Null, story_json)),
+            Output::Table => self.story_table(story.into_iter().collect()),
+        }
+    }
+
+    /// Print tasks, indenting subtasks under parents listed before them.
+    pub fn tasks(&mut self, tasks: Vec<TaskData>) -> io::Result<()> {
+        match self.output {
+            Output::Json => self.json(Value::Array(tasks.into_iter().map(task_json).collect())),
+            Output::Table => self.task_table(tasks),
+        }
+    }
+
+    /// Print a single task.
+    pub fn task(&mut self, task: Option<TaskData>) -> io::Result<()> {
+        match self.output {
+            Output::Json => self.json(task.map_or(Value::Null, task_json)),
+            Output::Table => self.task_table(task.into_iter().collect()),
+        }
+    }
+
+    /// Print an updated task, followed by the next occurrence completing it created.
+    pub fn updated_task(
+        &mut self,
+        task: Option<TaskData>,
+        next: Option<TaskData>,
+    ) -> io::Result<()> {
+        match self.output {
+            Output::Json => {
+                let mut value = task.map_or(Value::Null, task_json);
+                if let Some(next) = next {
+                    value["next_occurrence"] = task_json(next);
+                }
+                self.json(value)
+            }
+            Output::Table => self.task_table(task.into_iter().chain(next).collect()),
+        }
+    }
+
+    /// Confirm a deletion.
+    pub fn deleted(&mut self, resource: &str, id: impl Display) -> io::Result<()> {
+        match self.output {
+            Output::Json => self.json(json!({ format!("{resource}_id"): id.to_string() })),
+            Output::Table => writeln!(self.out, "deleted {resource} {id}"),
+        }
+    }
+
+    fn json(&mut self, value: Value) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut self.out, &value)?;
+        writeln!(self.out)
+    }
+
+    fn story_table(&mut self, stories: Vec<StoryData>) -> io::Result<()> {
+        let with_stats = stories.iter().any(|story| story.stats.is_some());
+        let mut headers = vec!["ID", "NAME", "LABELS"];
+        if with_stats {
+            headers.push("DONE");
+        }
+        headers.push("UPDATED");
+        let rows = stories
+            .into_iter()
+            .map(|story| {
+                let mut row = vec![story.story_id, story.name, label_names(&story.labels)];
+                if with_stats {
+                    row.push(story.stats.map_or_else(String::new, |stats| {
+                        format!(
+                            "{}/{} ({}%)",
+                            stats.complete_tasks, stats.total_tasks, stats.percent_complete
+                        )
+                    }));
+                }
+                row.push(timestamp_text(story.updated_at));
+                row
+            })
+            .collect();
+        write_table(&mut self.out, &headers, rows)
+    }
+
+    fn task_table(&mut self, tasks: Vec<TaskData>) -> io::Result<()> {
+        let headers = ["ID", "STATUS", "PRIORITY", "DUE", "NAME"];
+        let mut depths: HashMap<String, usize> = HashMap::new();
+        let rows = tasks
+            .into_iter()
+            .map(|task| {
+                let depth = task
+                    .parent_task_id
+                    .as_ref()
+                    .and_then(|parent_id| depths.get(parent_id))
+                    .map_or(0, |depth| depth + 1);
+                depths.insert(task.task_id.clone(), depth);
+                let due = task
+                    .due_at
+                    .map(|ts| date_text(Some(ts)))
+                    .unwrap_or_default();
+                let overdue = if task.overdue { " !" } else { "" };
+                vec![
+                    task.task_id,
+                    status_name(task.status),
+                    priority_name(task.priority),
+                    format!("{due}{overdue}"),
+                    format!("{:indent$}{}", "", task.name, indent = depth * 2),
+                ]
+            })
+            .collect();
+        write_table(&mut self.out, &headers, rows)
+    }
+}
+
+/// Write rows under headers, padding every column but the last to its widest value.
+fn write_table(out: &mut impl Write, headers: &[&str], rows: Vec<Vec<String>>) -> io::Result<()> {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let headers = headers.iter().map(|h| h.to_string()).collect();
+    for row in std::iter::once(headers).chain(rows) {
+        let last = row.len().saturating_sub(1);
+        for (i, (cell, width)) in row.iter().zip(&widths).enumerate() {
+            if i == last {
+                writeln!(out, "{cell}")?;
+            } else {
+                let pad = width - cell.chars().count();
+                write!(out, "{cell}{:pad$}  ", "")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn story_json(story: StoryData) -> Value {
+    let mut value = json!({
+        "story_id": story.story_id,
+        "name": story.name,
+        "labels": story.labels.into_iter().map(label_json).collect::<Vec<_>>(),
+        "created_at": timestamp_json(story.created_at),
+        "updated_at": timestamp_json(story.updated_at),
+    });
+    if let Some(description) = story.description {
+        value["description"] = json!(description);
+    }
+    if let Some(stats) = story.stats {
+        value["stats"] = json!({
+            "incomplete_tasks": stats.incomplete_tasks,
+            "complete_tasks": stats.complete_tasks,
+            "total_tasks": stats.total_tasks,
+            "percent_complete": stats.percent_complete,
+            "last_activity_at": timestamp_json(stats.last_activity_at),
+        });
+    }
+    value
+}
+
+fn task_json(task: TaskData) -> Value {
+    let mut value = json!({
+        "task_id": task.task_id,
+        "story_id": task.story_id,
+        "parent_task_id": task.parent_task_id,
+        "name": task.name,
+        "status": status_name(task.status),
+        "priority": priority_name(task.priority),
+        "position": task.position,
+        "due_at": timestamp_json(task.due_at),
+        "overdue": task.overdue,
+        "labels": task.labels.into_iter().map(label_json).collect::<Vec<_>>(),
+        "blocker_task_ids": task.blocker_task_ids,
+        "created_at": timestamp_json(task.created_at),
+        "updated_at": timestamp_json(task.updated_at),
+    });
+    if let Some(description) = task.description {
+        value["description"] = json!(description);
+    }
+    if let Some(recurrence) = task.recurrence {
+        value["recurrence"] = json!(recurrence);
+    }
+    if !task.subtasks.is_empty() {
+        value["subtasks"] = Value::Array(task.subtasks.into_iter().map(task_json).collect());
+    }
+    value
+}
+
+fn label_json(label: LabelData) -> Value {
+    json!({ "label_id": label.label_id, "name": label.name })
+}
+
+fn label_names(labels: &[LabelData]) -> String {
+    let names: Vec<&str> = labels.iter().map(|label| label.name.as_str()).collect();
+    names.join(",")
+}
+
+fn status_name(value: i32) -> String {
+    match TaskStatus::try_from(value) {
+        Ok(TaskStatus::Complete) => "complete".to_string(),
+        Ok(TaskStatus::Incomplete) => "incomplete".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn priority_name(value: i32) -> String {
+    match TaskPriority::try_from(value) {
+        Ok(TaskPriority::Low) => "low".to_string(),
+        Ok(TaskPriority::Medium) => "medium".to_string(),
+        Ok(TaskPriority::High) => "high".to_string(),
+        Ok(TaskPriority::Urgent) => "urgent".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn to_datetime(ts: Timestamp) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(ts.seconds, u32::try_from(ts.nanos).ok()?)
+}
+
+fn timestamp_json(ts: Option<Timestamp>) -> Value {
+    ts.and_then(to_datetime).map_or(Value::Null, |dt| {
+        json!(dt.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    })
+}
+
+fn timestamp_text(ts: Option<Timestamp>) -> String {
+    ts.and_then(to_datetime)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn date_text(ts: Option<Timestamp>) -> String {
+    ts.and_then(to_datetime)
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, parent: Option<&str>, name: &str) -> TaskData {
+        TaskData {
+            task_id: id.to_string(),
+            parent_task_id: parent.map(str::to_string),
+            name: name.to_string(),
+            status: TaskStatus::Complete as i32,
+            priority: TaskPriority::High as i32,
+            ..TaskData::default()
+        }
+    }
+
+    #[test]
+    fn table_pads_columns() {
+        let mut out = Vec::new();
+        let rows = vec![vec![
+            "a".to_string(),
+            "long name".to_string(),
+            "x".to_string(),
+        ]];
+        write_table(&mut out, &["ID", "NAME", "LAST"], rows).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text, "ID  NAME       LAST\na   long name  x\n");
+    }
+
+    #[test]
+    fn task_table_indents_subtasks() {
+        let mut printer = Printer::new(Output::Table, Vec::new());
+        let tasks = vec![task("1", None, "parent"), task("2", Some("1"), "child")];
+        printer.tasks(tasks).unwrap();
+        let text = String::from_utf8(printer.out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[1].starts_with("1   complete  high"));
+        let name_at = lines[1].find("parent").unwrap();
+        assert_eq!(lines[2].find("child"), Some(name_at + 2));
+    }
+
+    #[test]
+    fn task_json_names_enums() {
+        let value = task_json(task("1", None, "parent"));
+        assert_eq!(value["status"], "complete");
+        assert_eq!(value["priority"], "high");
+        assert_eq!(value["due_at"], Value::Null);
+    }
+}
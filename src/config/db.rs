@@ -1,13 +1,18 @@
 use super::Config;
 use sqlx::{Executor, postgres::PgPoolOptions};
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 impl Config {
     /// Create a new database connection pool options for postgres.
     pub fn db_pool_opts(&self) -> PgPoolOptions {
-        let schema: Arc<str> = self.db_schema.as_str().into();
+        let db = &self.database;
+        let schema: Arc<str> = db.schema.as_str().into();
         PgPoolOptions::new()
-            .max_connections(self.db_max_connections)
+            .min_connections(db.min_connections)
+            .max_connections(db.max_connections)
+            .acquire_timeout(Duration::from_secs(db.acquire_timeout_secs))
+            .idle_timeout(Some(Duration::from_secs(db.idle_timeout_secs)).filter(|d| !d.is_zero()))
+            .max_lifetime(Some(Duration::from_secs(db.max_lifetime_secs)).filter(|d| !d.is_zero()))
             .after_connect(move |conn, _meta| {
                 let schema = Arc::clone(&schema);
                 Box::pin(async move {
@@ -0,0 +1,274 @@
+use super::Storage;
+use serde::Deserialize;
+use std::{fmt::Display, fs, net::IpAddr, path::Path, path::PathBuf, str::FromStr};
+
+/// Command line flags that override config file and env var settings.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct ConfigArgs {
+    /// Path to a TOML config file
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    /// gRPC server listen host
+    #[arg(long, global = true)]
+    pub host: Option<IpAddr>,
+    /// gRPC server listen port
+    #[arg(long, global = true)]
+    pub port: Option<u16>,
+    /// Seconds between health check queries
+    #[arg(long, global = true, value_name = "SECS")]
+    pub health_interval: Option<u64>,
+    /// Where the server keeps stories and tasks
+    #[arg(long, global = true, value_enum)]
+    pub storage: Option<Storage>,
+    /// Database connection string
+    #[arg(long, global = true, value_name = "URL")]
+    pub database_url: Option<String>,
+    /// Database schema
+    #[arg(long, global = true)]
+    pub database_schema: Option<String>,
+    /// Minimum number of pooled database connections
+    #[arg(long, global = true, value_name = "N")]
+    pub database_min_connections: Option<u32>,
+    /// Maximum number of pooled database connections
+    #[arg(long, global = true, value_name = "N")]
+    pub database_max_connections: Option<u32>,
+    /// Seconds to wait for a pooled database connection
+    #[arg(long, global = true, value_name = "SECS")]
+    pub database_acquire_timeout: Option<u64>,
+    /// Seconds a pooled database connection may sit idle
+    #[arg(long, global = true, value_name = "SECS")]
+    pub database_idle_timeout: Option<u64>,
+    /// Seconds a pooled database connection may live
+    #[arg(long, global = true, value_name = "SECS")]
+    pub database_max_lifetime: Option<u64>,
+    /// Minimum page size
+    #[arg(long, global = true, value_name = "N")]
+    pub page_limit_min: Option<i64>,
+    /// Maximum page size
+    #[arg(long, global = true, value_name = "N")]
+    pub page_limit_max: Option<i64>,
+    /// Maximum story name length, in graphemes
+    #[arg(long, global = true, value_name = "N")]
+    pub story_name_max_len: Option<usize>,
+    /// Maximum task name length, in graphemes
+    #[arg(long, global = true, value_name = "N")]
+    pub task_name_max_len: Option<usize>,
+    /// Maximum label name length, in graphemes
+    #[arg(long, global = true, value_name = "N")]
+    pub label_name_max_len: Option<usize>,
+    /// Maximum story and task description size, in bytes
+    #[arg(long, global = true, value_name = "N")]
+    pub description_max_bytes: Option<usize>,
+    /// Maximum task comment size, in bytes
+    #[arg(long, global = true, value_name = "N")]
+    pub comment_max_bytes: Option<usize>,
+    /// Maximum number of items in a batch request
+    #[arg(long, global = true, value_name = "N")]
+    pub batch_max_size: Option<usize>,
+}
+
+/// A partial set of settings from a single source.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct Layer {
+    pub server: ServerLayer,
+    pub database: DatabaseLayer,
+    pub limits: LimitsLayer,
+}
+
+/// Partial gRPC server settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct ServerLayer {
+    pub host: Option<IpAddr>,
+    pub port: Option<u16>,
+    pub health_interval_secs: Option<u64>,
+    pub storage: Option<Storage>,
+}
+
+/// Partial database settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct DatabaseLayer {
+    pub url: Option<String>,
+    pub schema: Option<String>,
+    pub min_connections: Option<u32>,
+    pub max_connections: Option<u32>,
+    pub acquire_timeout_secs: Option<u64>,
+    pub idle_timeout_secs: Option<u64>,
+    pub max_lifetime_secs: Option<u64>,
+}
+
+/// Partial validation limit settings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(super) struct LimitsLayer {
+    pub page_limit_min: Option<i64>,
+    pub page_limit_max: Option<i64>,
+    pub story_name_max_len: Option<usize>,
+    pub task_name_max_len: Option<usize>,
+    pub label_name_max_len: Option<usize>,
+    pub description_max_bytes: Option<usize>,
+    pub comment_max_bytes: Option<usize>,
+    pub batch_max_size: Option<usize>,
+}
+
+impl Layer {
+    /// Read settings from a TOML file.
+    pub fn from_file(path: &Path, errors: &mut Vec<String>) -> Self {
+        let parsed = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|s| toml::from_str(&s).map_err(|err| err.to_string()));
+        parsed.unwrap_or_else(|err| {
+            errors.push(format!("{}: {}", path.display(), err.trim()));
+            Self::default()
+        })
+    }
+
+    /// Read settings from env vars.
+    pub fn from_env(lookup: &impl Fn(&str) -> Option<String>, errors: &mut Vec<String>) -> Self {
+        Self {
+            server: ServerLayer {
+                host: parse_var(lookup, "GRPC_SERVER_HOST", errors),
+                port: parse_var(lookup, "GRPC_SERVER_PORT", errors),
+                health_interval_secs: parse_var(lookup, "HEALTH_CHECK_INTERVAL_SECS", errors),
+                storage: parse_var(lookup, "GSDX_STORAGE", errors),
+            },
+            database: DatabaseLayer {
+                url: lookup("DATABASE_URL"),
+                schema: lookup("DATABASE_SCHEMA"),
+                min_connections: parse_var(lookup, "DATABASE_MIN_CONNECTIONS", errors),
+                max_connections: parse_var(lookup, "DATABASE_MAX_CONNECTIONS", errors),
+                acquire_timeout_secs: parse_var(lookup, "DATABASE_ACQUIRE_TIMEOUT_SECS", errors),
+                idle_timeout_secs: parse_var(lookup, "DATABASE_IDLE_TIMEOUT_SECS", errors),
+                max_lifetime_secs: parse_var(lookup, "DATABASE_MAX_LIFETIME_SECS", errors),
+            },
+            limits: LimitsLayer {
+                page_limit_min: parse_var(lookup, "PAGE_LIMIT_MIN", errors),
+                page_limit_max: parse_var(lookup, "PAGE_LIMIT_MAX", errors),
+                story_name_max_len: parse_var(lookup, "STORY_NAME_MAX_LEN", errors),
+                task_name_max_len: parse_var(lookup, "TASK_NAME_MAX_LEN", errors),
+                label_name_max_len: parse_var(lookup, "LABEL_NAME_MAX_LEN", errors),
+                description_max_bytes: parse_var(lookup, "DESCRIPTION_MAX_BYTES", errors),
+                comment_max_bytes: parse_var(lookup, "COMMENT_MAX_BYTES", errors),
+                batch_max_size: parse_var(lookup, "BATCH_MAX_SIZE", errors),
+            },
+        }
+    }
+
+    /// Override settings in this layer with any set in another.
+    pub fn merge(self, other: Layer) -> Self {
+        Self {
+            server: ServerLayer {
+                host: other.server.host.or(self.server.host),
+                port: other.server.port.or(self.server.port),
+                health_interval_secs: other
+                    .server
+                    .health_interval_secs
+                    .or(self.server.health_interval_secs),
+                storage: other.server.storage.or(self.server.storage),
+            },
+            database: DatabaseLayer {
+                url: other.database.url.or(self.database.url),
+                schema: other.database.schema.or(self.database.schema),
+                min_connections: other
+                    .database
+                    .min_connections
+                    .or(self.database.min_connections),
+                max_connections: other
+                    .database
+                    .max_connections
+                    .or(self.database.max_connections),
+                acquire_timeout_secs: other
+                    .database
+                    .acquire_timeout_secs
+                    .or(self.database.acquire_timeout_secs),
+                idle_timeout_secs: other
+                    .database
+                    .idle_timeout_secs
+                    .or(self.database.idle_timeout_secs),
+                max_lifetime_secs: other
+                    .database
+                    .max_lifetime_secs
+                    .or(self.database.max_lifetime_secs),
+            },
+            limits: LimitsLayer {
+                page_limit_min: other.limits.page_limit_min.or(self.limits.page_limit_min),
+                page_limit_max: other.limits.page_limit_max.or(self.limits.page_limit_max),
+                story_name_max_len: other
+                    .limits
+                    .story_name_max_len
+                    .or(self.limits.story_name_max_len),
+                task_name_max_len: other
+                    .limits
+                    .task_name_max_len
+                    .or(self.limits.task_name_max_len),
+                label_name_max_len: other
+                    .limits
+                    .label_name_max_len
+                    .or(self.limits.label_name_max_len),
+                description_max_bytes: other
+                    .limits
+                    .description_max_bytes
+                    .or(self.limits.description_max_bytes),
+                comment_max_bytes: other
+                    .limits
+                    .comment_max_bytes
+                    .or(self.limits.comment_max_bytes),
+                batch_max_size: other.limits.batch_max_size.or(self.limits.batch_max_size),
+            },
+        }
+    }
+}
+
+/// Map command line flags into a settings layer.
+impl From<&ConfigArgs> for Layer {
+    fn from(args: &ConfigArgs) -> Self {
+        Self {
+            server: ServerLayer {
+                host: args.host,
+                port: args.port,
+                health_interval_secs: args.health_interval,
+                storage: args.storage,
+            },
+            database: DatabaseLayer {
+                url: args.database_url.clone(),
+                schema: args.database_schema.clone(),
+                min_connections: args.database_min_connections,
+                max_connections: args.database_max_connections,
+                acquire_timeout_secs: args.database_acquire_timeout,
+                idle_timeout_secs: args.database_idle_timeout,
+                max_lifetime_secs: args.database_max_lifetime,
+            },
+            limits: LimitsLayer {
+                page_limit_min: args.page_limit_min,
+                page_limit_max: args.page_limit_max,
+                story_name_max_len: args.story_name_max_len,
+                task_name_max_len: args.task_name_max_len,
+                label_name_max_len: args.label_name_max_len,
+                description_max_bytes: args.description_max_bytes,
+                comment_max_bytes: args.comment_max_bytes,
+                batch_max_size: args.batch_max_size,
+            },
+        }
+    }
+}
+
+/// Parse an env var value, recording an error if it is malformed.
+fn parse_var<T>(
+    lookup: &impl Fn(&str) -> Option<String>,
+    key: &str,
+    errors: &mut Vec<String>,
+) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    lookup(key).and_then(|s| match s.trim().parse() {
+        Ok(value) => Some(value),
+        Err(err) => {
+            errors.push(format!("{key}: {err}"));
+            None
+        }
+    })
+}
@@ -0,0 +1,39 @@
+use crate::domain::TaskId;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The newtype comment id.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CommentId(pub Uuid);
+
+// Display the inner uuid.
+impl std::fmt::Display for CommentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The comment domain object, for discussion on a task.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Comment {
+    pub id: CommentId,
+    pub task_id: TaskId,
+    /// Who wrote the comment; only they can edit or delete it.
+    pub author: String,
+    /// The Markdown comment body.
+    pub body: String,
+    /// Whether the body has been edited.
+    pub edited: bool,
+    /// Previous bodies, oldest first, when loaded with the full view.
+    pub history: Vec<CommentEdit>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A previous comment body, replaced by an edit.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CommentEdit {
+    pub body: String,
+    pub replaced_at: DateTime<Utc>,
+}
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+
+/// A boolean expression over story fields, parsed from an AIP-160 style filter string.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StoryExpr {
+    And(Box<StoryExpr>, Box<StoryExpr>),
+    Or(Box<StoryExpr>, Box<StoryExpr>),
+    Not(Box<StoryExpr>),
+    Name(TextMatch),
+    CreatedAt(Comparator, DateTime<Utc>),
+    UpdatedAt(Comparator, DateTime<Utc>),
+}
+
+/// A comparison against a text field.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TextMatch {
+    /// Exactly equal.
+    Equals(String),
+    /// Not exactly equal.
+    NotEquals(String),
+    /// Case-insensitive match, where `*` matches any run of characters. Without wildcards, the
+    /// value may appear anywhere in the field.
+    Has(String),
+}
+
+/// A comparison operator for ordered fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A half-open time window: from `after` inclusive, up to `before` exclusive. Missing bounds
+/// are unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TimeRange {
+    pub after: Option<DateTime<Utc>>,
+    pub before: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,31 @@
+use crate::domain::{StoryId, TaskId};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The newtype label id.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LabelId(pub Uuid);
+
+// Display the inner uuid.
+impl std::fmt::Display for LabelId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The label domain object, used to categorize stories and tasks.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Label {
+    pub id: LabelId,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Something labels can be attached to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LabelTarget {
+    Story(StoryId),
+    Task(TaskId),
+}
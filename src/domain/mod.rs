@@ -1,10 +1,35 @@
+mod comment;
+mod filter;
+mod label;
 mod page;
+mod position;
+mod priority;
+mod recurrence;
+mod search;
+mod sort;
 mod status;
 mod story;
 mod task;
+mod template;
+mod transfer;
+mod view;
 
-pub use page::{Cursor, Limit, Page, PageParams};
+pub use comment::{Comment, CommentEdit, CommentId};
+pub use filter::{Comparator, StoryExpr, TextMatch, TimeRange};
+pub use label::{Label, LabelId, LabelTarget};
+pub use page::{Cursor, Keyset, KeysetPage, Limit, Page, PageParams};
 pub use page::{PAGE_CURSOR_MAX, PAGE_CURSOR_MIN, PAGE_LIMIT_MAX, PAGE_LIMIT_MIN};
+pub use position::position_between;
+pub use priority::Priority;
+pub use recurrence::{Frequency, RECURRENCE_INTERVAL_MAX, Recurrence, RecurrenceEnd};
+pub use search::{SearchHit, SearchQuery};
+pub use sort::{StoryKeyset, StorySort, StorySortField, StorySortValue};
 pub use status::Status;
-pub use story::{Story, StoryId};
-pub use task::{Task, TaskId};
+pub use story::{NewStory, Story, StoryChanges, StoryFilter, StoryId, StoryPage, StoryStats};
+pub use task::{
+    NewTask, Placement, TASK_DEPTH_MAX, Task, TaskChanges, TaskFilter, TaskId, TaskLayout,
+    TaskNode, TaskOrder, TaskResult, TaskSelection,
+};
+pub use template::{NewTemplate, TEMPLATE_PLACEHOLDERS, Template, TemplateId, render_pattern};
+pub use transfer::{ImportSummary, StoryExport, StoryImport, TaskImport};
+pub use view::View;
@@ -1,3 +1,7 @@
+use chrono::{DateTime, Utc};
+use std::{fmt, str::FromStr};
+use uuid::Uuid;
+
 /// Minimum page cursor
 pub const PAGE_CURSOR_MIN: Cursor = 1;
 
@@ -28,3 +32,50 @@ impl Default for PageParams {
         Self(PAGE_CURSOR_MIN, PAGE_LIMIT_MIN)
     }
 }
+
+/// A keyset position: the timestamp sort key and id of the last row on the previous page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Keyset(pub DateTime<Utc>, pub Uuid);
+
+/// An optional keyset position for the next page, and data.
+pub struct KeysetPage<T>(pub Option<Keyset>, pub Vec<T>);
+
+// Display as an opaque "{micros}_{uuid}" page token.
+impl fmt::Display for Keyset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.0.timestamp_micros(), self.1)
+    }
+}
+
+// Parse an opaque page token.
+impl FromStr for Keyset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid page token: {s}");
+        let (micros, id) = s.split_once('_').ok_or_else(invalid)?;
+        let micros = micros.parse().map_err(|_| invalid())?;
+        let ts = DateTime::from_timestamp_micros(micros).ok_or_else(invalid)?;
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+        Ok(Self(ts, id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyset_round_trip() {
+        let keyset = Keyset(Utc::now(), Uuid::new_v4());
+        let parsed = Keyset::from_str(&keyset.to_string()).unwrap();
+        assert_eq!(parsed.0.timestamp_micros(), keyset.0.timestamp_micros());
+        assert_eq!(parsed.1, keyset.1);
+    }
+
+    #[test]
+    fn keyset_parse_error() {
+        assert!(Keyset::from_str("12345").is_err());
+        assert!(Keyset::from_str("abc_4ac0160a").is_err());
+    }
+}
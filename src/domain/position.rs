@@ -0,0 +1,124 @@
+/// Position digits, in ascending byte order so keys sort correctly under a "C" collation.
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// The position given to the first task in an empty story.
+const FIRST: &str = "V";
+
+/// Generate a fractional position key that sorts strictly between two existing keys.
+///
+/// Either bound may be missing, for placing at the start or end of a list. Appends grow keys
+/// by one digit per ~60 items, and inserts between neighbours grow keys by one digit per ~6
+/// repeated splits, so no other rows are ever rewritten.
+pub fn position_between(before: Option<&str>, after: Option<&str>) -> String {
+    match (before, after) {
+        (None, None) => FIRST.to_string(),
+        (Some(before), None) => increment(before.as_bytes()),
+        (before, Some(after)) => midpoint(
+            before.unwrap_or_default().as_bytes(),
+            Some(after.as_bytes()),
+        ),
+    }
+}
+
+/// The index of a position digit.
+fn index(digit: u8) -> usize {
+    DIGITS.iter().position(|&d| d == digit).unwrap_or_default()
+}
+
+/// The shortest key greater than `key`, found by bumping the first digit that is not the max.
+fn increment(key: &[u8]) -> String {
+    let max = DIGITS[DIGITS.len() - 1];
+    let mut out = Vec::with_capacity(key.len() + 1);
+    for &digit in key {
+        if digit != max {
+            out.push(DIGITS[index(digit) + 1]);
+            return String::from_utf8_lossy(&out).into_owned();
+        }
+        out.push(digit);
+    }
+    out.push(DIGITS[1]);
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A key strictly between `a` and `b` (or the end, if `b` is missing), where `a < b` and
+/// neither key ends with the zero digit.
+fn midpoint(a: &[u8], b: Option<&[u8]>) -> String {
+    let zero = DIGITS[0];
+    if let Some(b) = b {
+        // Keep any common prefix, padding `a` with zeros.
+        let n = b
+            .iter()
+            .enumerate()
+            .take_while(|&(i, &digit)| a.get(i).copied().unwrap_or(zero) == digit)
+            .count();
+        if n > 0 {
+            let prefix = String::from_utf8_lossy(&b[..n]);
+            let rest = midpoint(a.get(n..).unwrap_or_default(), Some(&b[n..]));
+            return format!("{prefix}{rest}");
+        }
+    }
+    let digit_a = a.first().map(|&d| index(d)).unwrap_or(0);
+    let digit_b = b
+        .and_then(|b| b.first())
+        .map(|&d| index(d))
+        .unwrap_or(DIGITS.len());
+    if digit_b - digit_a > 1 {
+        let mid = (digit_a + digit_b).div_ceil(2);
+        (DIGITS[mid] as char).to_string()
+    } else if let Some(b) = b.filter(|b| b.len() > 1) {
+        (b[0] as char).to_string()
+    } else {
+        let rest = midpoint(a.get(1..).unwrap_or_default(), None);
+        format!("{}{rest}", DIGITS[digit_a] as char)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_position() {
+        assert_eq!(position_between(None, None), "V");
+    }
+
+    #[test]
+    fn append_grows_slowly() {
+        let mut key = position_between(None, None);
+        for _ in 0..500 {
+            let next = position_between(Some(&key), None);
+            assert!(next > key);
+            key = next;
+        }
+        assert!(key.len() <= 10);
+    }
+
+    #[test]
+    fn prepend_stays_ordered() {
+        let mut key = position_between(None, None);
+        for _ in 0..100 {
+            let next = position_between(None, Some(&key));
+            assert!(next < key);
+            assert!(!next.ends_with('0'));
+            key = next;
+        }
+    }
+
+    #[test]
+    fn repeated_splits_stay_ordered() {
+        let low = position_between(None, None);
+        let mut high = position_between(Some(&low), None);
+        for _ in 0..200 {
+            let mid = position_between(Some(&low), Some(&high));
+            assert!(low < mid && mid < high, "{low} < {mid} < {high}");
+            high = mid;
+        }
+        let mut low = position_between(None, None);
+        let high = position_between(Some(&low), None);
+        for _ in 0..200 {
+            let mid = position_between(Some(&low), Some(&high));
+            assert!(low < mid && mid < high, "{low} < {mid} < {high}");
+            low = mid;
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use strum_macros::{Display, EnumString};
+
+/// The task priority domain object.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Display,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn priority_from_string() {
+        assert_eq!(Priority::from_str("urgent").unwrap(), Priority::Urgent);
+        assert!(Priority::from_str("whenever").is_err());
+    }
+
+    #[test]
+    fn priority_ordering() {
+        assert!(Priority::Urgent > Priority::High);
+        assert!(Priority::Low < Priority::Medium);
+    }
+}
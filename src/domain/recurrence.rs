@@ -0,0 +1,245 @@
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+use std::{fmt, str::FromStr};
+use strum_macros::{Display, EnumString};
+
+/// The largest interval between occurrences.
+pub const RECURRENCE_INTERVAL_MAX: u32 = 1000;
+
+/// How often a recurring task repeats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Display)]
+#[strum(serialize_all = "UPPERCASE", ascii_case_insensitive)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// When a recurring task stops repeating.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RecurrenceEnd {
+    /// The number of occurrences left, counting the current one.
+    Count(u32),
+    /// No occurrence is due after this time.
+    Until(DateTime<Utc>),
+}
+
+/// A recurrence rule for a task, written as a subset of an iCalendar RRULE like
+/// `FREQ=WEEKLY;INTERVAL=2;COUNT=4`. Only FREQ, INTERVAL, COUNT and UNTIL are supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// The number of days, weeks or months between occurrences.
+    pub interval: u32,
+    pub end: Option<RecurrenceEnd>,
+}
+
+impl Recurrence {
+    /// The due date of the occurrence after one due at the given time, with the rule it carries
+    /// on with, or `None` once the recurrence is over.
+    ///
+    /// Monthly occurrences keep the day of the month, skipping months that are too short.
+    pub fn next(&self, due_at: DateTime<Utc>) -> Option<(DateTime<Utc>, Recurrence)> {
+        let next_due_at = match self.frequency {
+            Frequency::Daily => due_at.checked_add_signed(TimeDelta::days(self.interval.into()))?,
+            Frequency::Weekly => {
+                due_at.checked_add_signed(TimeDelta::weeks(self.interval.into()))?
+            }
+            Frequency::Monthly => (1..=MONTHLY_SKIP_MAX).find_map(|n| {
+                let months = Months::new(self.interval.checked_mul(n)?);
+                let first = due_at.with_day(1)?.checked_add_months(months)?;
+                first.with_day(due_at.day())
+            })?,
+        };
+        let end = match self.end {
+            Some(RecurrenceEnd::Count(count)) if count <= 1 => return None,
+            Some(RecurrenceEnd::Count(count)) => Some(RecurrenceEnd::Count(count - 1)),
+            Some(RecurrenceEnd::Until(until)) if next_due_at > until => return None,
+            end => end,
+        };
+        let rule = Recurrence { end, ..*self };
+        Some((next_due_at, rule))
+    }
+}
+
+/// How many intervals to try for a day of the month that is missing from shorter months. Any
+/// day is found within a few months, except the 29th of February which can take eight years.
+const MONTHLY_SKIP_MAX: u32 = 96;
+
+// Write the rule in RRULE form, leaving out the default interval.
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        match self.end {
+            Some(RecurrenceEnd::Count(count)) => write!(f, ";COUNT={count}"),
+            Some(RecurrenceEnd::Until(until)) => {
+                write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+// Parse the RRULE subset, with or without the `RRULE:` prefix.
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let rule = match s.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &s[6..],
+            _ => s,
+        };
+        let mut frequency = None;
+        let mut interval = None;
+        let mut end = None;
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected NAME=VALUE, got {part}"))?;
+            let name = name.trim().to_ascii_uppercase();
+            let value = value.trim();
+            let duplicate = match name.as_str() {
+                "FREQ" => frequency
+                    .replace(Frequency::from_str(value).map_err(|_| {
+                        format!("unsupported FREQ {value}; expected DAILY, WEEKLY or MONTHLY")
+                    })?)
+                    .is_some(),
+                "INTERVAL" => interval.replace(parse_interval(value)?).is_some(),
+                "COUNT" => end.replace(parse_count(value)?).is_some(),
+                "UNTIL" => end.replace(parse_until(value)?).is_some(),
+                _ => return Err(format!("unsupported rule part {name}")),
+            };
+            if duplicate {
+                return Err(match name.as_str() {
+                    "COUNT" | "UNTIL" => "only one of COUNT or UNTIL can be given".to_string(),
+                    _ => format!("{name} is given more than once"),
+                });
+            }
+        }
+        Ok(Recurrence {
+            frequency: frequency.ok_or("FREQ is required")?,
+            interval: interval.unwrap_or(1),
+            end,
+        })
+    }
+}
+
+fn parse_interval(value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(interval) if (1..=RECURRENCE_INTERVAL_MAX).contains(&interval) => Ok(interval),
+        _ => Err(format!(
+            "INTERVAL must be a number from 1 to {RECURRENCE_INTERVAL_MAX}"
+        )),
+    }
+}
+
+fn parse_count(value: &str) -> Result<RecurrenceEnd, String> {
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(RecurrenceEnd::Count(count)),
+        _ => Err("COUNT must be a positive number".to_string()),
+    }
+}
+
+// A date without a time runs through the end of that day.
+fn parse_until(value: &str) -> Result<RecurrenceEnd, String> {
+    let invalid = || format!("UNTIL must look like 20261231 or 20261231T120000Z, got {value}");
+    let until = match value.split_once('T') {
+        Some(_) => {
+            let time = value.strip_suffix('Z').ok_or_else(invalid)?;
+            NaiveDateTime::parse_from_str(time, "%Y%m%dT%H%M%S")
+                .map_err(|_| invalid())?
+                .and_utc()
+        }
+        None => NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map_err(|_| invalid())?
+            .and_time(NaiveTime::from_hms_opt(23, 59, 59).ok_or_else(invalid)?)
+            .and_utc(),
+    };
+    Ok(RecurrenceEnd::Until(until))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    fn rule(s: &str) -> Recurrence {
+        Recurrence::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let recurrence = rule("RRULE:freq=weekly;interval=2;count=3");
+        assert_eq!(recurrence.frequency, Frequency::Weekly);
+        assert_eq!(recurrence.interval, 2);
+        assert_eq!(recurrence.end, Some(RecurrenceEnd::Count(3)));
+        assert_eq!(recurrence.to_string(), "FREQ=WEEKLY;INTERVAL=2;COUNT=3");
+        assert_eq!(rule("FREQ=DAILY").to_string(), "FREQ=DAILY");
+        let recurrence = rule("FREQ=MONTHLY;UNTIL=20261231");
+        assert_eq!(
+            recurrence.to_string(),
+            "FREQ=MONTHLY;UNTIL=20261231T235959Z"
+        );
+        assert_eq!(rule(&recurrence.to_string()), recurrence);
+    }
+
+    #[test]
+    fn parse_rejects_unsupported_rules() {
+        let err = |s| Recurrence::from_str(s).unwrap_err();
+        assert!(err("INTERVAL=2").contains("FREQ is required"));
+        assert!(err("FREQ=YEARLY").contains("unsupported FREQ"));
+        assert!(err("FREQ=WEEKLY;BYDAY=MO").contains("BYDAY"));
+        assert!(err("FREQ=DAILY;INTERVAL=0").contains("INTERVAL"));
+        assert!(err("FREQ=DAILY;COUNT=0").contains("COUNT"));
+        assert!(err("FREQ=DAILY;COUNT=2;UNTIL=20261231").contains("only one"));
+        assert!(err("FREQ=DAILY;FREQ=WEEKLY").contains("more than once"));
+        assert!(err("FREQ=DAILY;UNTIL=tomorrow").contains("UNTIL"));
+    }
+
+    #[test]
+    fn next_daily_and_weekly() {
+        let (due_at, _) = rule("FREQ=DAILY;INTERVAL=3")
+            .next(at(2026, 10, 30))
+            .unwrap();
+        assert_eq!(due_at, at(2026, 11, 2));
+        let (due_at, _) = rule("FREQ=WEEKLY").next(at(2026, 10, 19)).unwrap();
+        assert_eq!(due_at, at(2026, 10, 26));
+    }
+
+    #[test]
+    fn next_monthly_skips_short_months() {
+        let monthly = rule("FREQ=MONTHLY");
+        let (due_at, _) = monthly.next(at(2026, 1, 31)).unwrap();
+        assert_eq!(due_at, at(2026, 3, 31));
+        let (due_at, _) = monthly.next(at(2026, 1, 15)).unwrap();
+        assert_eq!(due_at, at(2026, 2, 15));
+        let (due_at, _) = rule("FREQ=MONTHLY;INTERVAL=12")
+            .next(at(2028, 2, 29))
+            .unwrap();
+        assert_eq!(due_at, at(2032, 2, 29));
+    }
+
+    #[test]
+    fn next_counts_down_to_the_end() {
+        let (_, next) = rule("FREQ=DAILY;COUNT=2").next(at(2026, 10, 19)).unwrap();
+        assert_eq!(next.end, Some(RecurrenceEnd::Count(1)));
+        assert_eq!(next.next(at(2026, 10, 20)), None);
+    }
+
+    #[test]
+    fn next_stops_after_until() {
+        let until = rule("FREQ=WEEKLY;UNTIL=20261026");
+        assert!(until.next(at(2026, 10, 19)).is_some());
+        assert_eq!(until.next(at(2026, 10, 20)), None);
+    }
+}
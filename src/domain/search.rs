@@ -0,0 +1,24 @@
+use crate::domain::{StoryId, TaskId};
+
+/// A story or task matching a search query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHit {
+    /// The matching story, or the story of the matching task.
+    pub story_id: StoryId,
+    /// The matching task, for task hits.
+    pub task_id: Option<TaskId>,
+    pub name: String,
+    /// Plain matching text, not HTML, with the search terms wrapped in `**`.
+    pub snippet: String,
+    /// The relevance of the hit; higher is better.
+    pub rank: f32,
+}
+
+/// Criteria for a full-text search.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SearchQuery {
+    /// The search terms, in web search syntax: quoted phrases, `or` and `-` exclusions.
+    pub terms: String,
+    /// Only search this story and its tasks.
+    pub story_id: Option<StoryId>,
+}
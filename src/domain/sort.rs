@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use std::{fmt, str::FromStr};
+use strum_macros::{Display, EnumString};
+
+/// The story fields that can be sorted on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum StorySortField {
+    /// Creation order, the default.
+    #[default]
+    Seqno,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
+
+/// The order to list stories in. Ties are broken by creation order, in the same direction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StorySort {
+    pub field: StorySortField,
+    pub descending: bool,
+}
+
+impl StorySort {
+    /// Whether this is the default creation order, which supports numeric cursors.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// Display as "{field}" or "{field} desc", like an order_by clause.
+impl fmt::Display for StorySort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.descending {
+            true => write!(f, "{} desc", self.field),
+            false => write!(f, "{}", self.field),
+        }
+    }
+}
+
+/// The sort key of the last story on a page.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StorySortValue {
+    Seqno,
+    Name(String),
+    Time(DateTime<Utc>),
+}
+
+/// A keyset position for stories: the sort the page was made for, the sort key and seqno of
+/// the last story on the previous page.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StoryKeyset {
+    pub sort: StorySort,
+    pub value: StorySortValue,
+    pub seqno: i64,
+}
+
+// Display as an opaque "{field}_{asc|desc}_{seqno}_{value}" page token.
+impl fmt::Display for StoryKeyset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = if self.sort.descending { "desc" } else { "asc" };
+        write!(f, "{}_{}_{}_", self.sort.field, direction, self.seqno)?;
+        match &self.value {
+            StorySortValue::Seqno => Ok(()),
+            StorySortValue::Name(name) => write!(f, "{name}"),
+            StorySortValue::Time(ts) => write!(f, "{}", ts.timestamp_micros()),
+        }
+    }
+}
+
+// Parse an opaque page token. Field names contain underscores, so split from known fields.
+impl FromStr for StoryKeyset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid page token: {s}");
+        let (field, rest) = [
+            StorySortField::Seqno,
+            StorySortField::Name,
+            StorySortField::CreatedAt,
+            StorySortField::UpdatedAt,
+        ]
+        .into_iter()
+        .find_map(|field| {
+            let rest = s.strip_prefix(&field.to_string())?.strip_prefix('_')?;
+            Some((field, rest))
+        })
+        .ok_or_else(invalid)?;
+        let (direction, rest) = rest.split_once('_').ok_or_else(invalid)?;
+        let descending = match direction {
+            "asc" => false,
+            "desc" => true,
+            _ => return Err(invalid()),
+        };
+        let (seqno, value) = rest.split_once('_').ok_or_else(invalid)?;
+        let seqno = seqno.parse().map_err(|_| invalid())?;
+        let value = match field {
+            StorySortField::Seqno if value.is_empty() => StorySortValue::Seqno,
+            StorySortField::Seqno => return Err(invalid()),
+            StorySortField::Name => StorySortValue::Name(value.to_string()),
+            StorySortField::CreatedAt | StorySortField::UpdatedAt => {
+                let micros = value.parse().map_err(|_| invalid())?;
+                let ts = DateTime::from_timestamp_micros(micros).ok_or_else(invalid)?;
+                StorySortValue::Time(ts)
+            }
+        };
+        let sort = StorySort { field, descending };
+        Ok(Self { sort, value, seqno })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn story_keyset_round_trip() {
+        let keyset = StoryKeyset {
+            sort: StorySort {
+                field: StorySortField::Name,
+                descending: true,
+            },
+            value: StorySortValue::Name("a_b c".into()),
+            seqno: 42,
+        };
+        let parsed = StoryKeyset::from_str(&keyset.to_string()).unwrap();
+        assert_eq!(parsed, keyset);
+
+        let ts = DateTime::from_timestamp_micros(1_760_000_000_123_456).unwrap();
+        let keyset = StoryKeyset {
+            sort: StorySort {
+                field: StorySortField::UpdatedAt,
+                descending: false,
+            },
+            value: StorySortValue::Time(ts),
+            seqno: 7,
+        };
+        assert_eq!(keyset.to_string(), "updated_at_asc_7_1760000000123456");
+        assert_eq!(StoryKeyset::from_str(&keyset.to_string()).unwrap(), keyset);
+    }
+
+    #[test]
+    fn story_keyset_parse_error() {
+        assert!(StoryKeyset::from_str("name_up_1_x").is_err());
+        assert!(StoryKeyset::from_str("seqno_asc_1_x").is_err());
+        assert!(StoryKeyset::from_str("created_at_asc_1_soon").is_err());
+    }
+}
@@ -1,7 +1,9 @@
 use strum_macros::{Display, EnumString};
 
 /// The task status domain object.
-#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Display)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Display,
+)]
 #[strum(serialize_all = "snake_case")]
 pub enum Status {
     #[default]
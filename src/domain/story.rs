@@ -1,3 +1,5 @@
+use crate::domain::{Cursor, Label, LabelId, StoryExpr, StoryKeyset};
+
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
@@ -17,6 +19,115 @@ impl std::fmt::Display for StoryId {
 pub struct Story {
     pub id: StoryId,
     pub name: String,
+    /// The Markdown description, when loaded with the full view.
+    pub description: Option<String>,
+    pub labels: Vec<Label>,
+    /// Task progress, when requested.
+    pub stats: Option<StoryStats>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+/// Task progress for a story.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StoryStats {
+    pub incomplete_tasks: i64,
+    pub complete_tasks: i64,
+    /// The latest change to the story, its tasks or their comments.
+    pub last_activity_at: DateTime<Utc>,
+}
+
+impl StoryStats {
+    /// The total number of tasks in the story.
+    pub fn total_tasks(&self) -> i64 {
+        self.incomplete_tasks + self.complete_tasks
+    }
+
+    /// The whole percentage of tasks that are complete, rounded down; zero without tasks.
+    pub fn percent_complete(&self) -> u32 {
+        match self.total_tasks() {
+            0 => 0,
+            total => (self.complete_tasks * 100 / total) as u32,
+        }
+    }
+}
+
+/// The fields for creating a new story.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct NewStory {
+    pub name: String,
+    pub description: String,
+}
+
+impl NewStory {
+    /// Create new story fields without a description.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+        }
+    }
+}
+
+/// Changes to apply to an existing story; a description left as `None` is unchanged.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct StoryChanges {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl StoryChanges {
+    /// Whether applying these changes would leave a fully loaded story as it is.
+    pub fn is_noop(&self, story: &Story) -> bool {
+        self.name == story.name
+            && self
+                .description
+                .as_ref()
+                .is_none_or(|d| Some(d) == story.description.as_ref())
+    }
+
+    /// Apply these changes to a story.
+    pub fn apply(self, story: Story) -> Story {
+        Story {
+            name: self.name,
+            description: self.description.or(story.description),
+            ..story
+        }
+    }
+}
+
+/// Criteria for selecting stories.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StoryFilter {
+    /// Only stories with all of these labels.
+    pub label_ids: Vec<LabelId>,
+    /// Only stories matching this expression.
+    pub expr: Option<StoryExpr>,
+}
+
+/// A page of stories, with positions to continue from.
+#[derive(Debug, Default)]
+pub struct StoryPage {
+    /// The next numeric cursor, for the default sort only; zero otherwise.
+    pub next_cursor: Cursor,
+    /// The next keyset position, when the page was full.
+    pub next_keyset: Option<StoryKeyset>,
+    pub stories: Vec<Story>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_complete_rounds_down() {
+        let stats = |incomplete_tasks, complete_tasks| StoryStats {
+            incomplete_tasks,
+            complete_tasks,
+            last_activity_at: Utc::now(),
+        };
+        assert_eq!(stats(0, 0).percent_complete(), 0);
+        assert_eq!(stats(5, 7).percent_complete(), 58);
+        assert_eq!(stats(0, 3).percent_complete(), 100);
+    }
+}
@@ -1,8 +1,13 @@
-use crate::domain::{Status, StoryId};
+use crate::domain::{Label, LabelId, Priority, Recurrence, Status, StoryId, TimeRange};
 
 use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use strum_macros::Display;
 use uuid::Uuid;
 
+/// The deepest a task can be nested, counting top-level tasks as depth one.
+pub const TASK_DEPTH_MAX: i32 = 5;
+
 /// The newtype task id.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TaskId(pub Uuid);
@@ -19,8 +24,230 @@ impl std::fmt::Display for TaskId {
 pub struct Task {
     pub id: TaskId,
     pub story_id: StoryId,
+    /// The parent task, for subtasks.
+    pub parent_id: Option<TaskId>,
     pub name: String,
+    /// The Markdown description, when loaded with the full view.
+    pub description: Option<String>,
     pub status: Status,
+    pub priority: Priority,
+    pub position: String,
+    pub due_at: Option<DateTime<Utc>>,
+    /// The opaque id of the user working on the task.
+    pub assignee: Option<String>,
+    /// How the task repeats; completing it creates the next occurrence.
+    pub recurrence: Option<Recurrence>,
+    pub labels: Vec<Label>,
+    /// The tasks that must be complete before this one can be.
+    pub blocker_ids: Vec<TaskId>,
+    /// Whether any blocker is still incomplete.
+    pub blocked: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+impl Task {
+    /// Whether the task is incomplete and past its due date.
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.status == Status::Incomplete && self.due_at.is_some_and(|due_at| due_at < now)
+    }
+}
+
+/// The fields for creating a new task.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct NewTask {
+    pub parent_id: Option<TaskId>,
+    pub name: String,
+    pub description: String,
+    pub status: Status,
+    pub priority: Priority,
+    pub due_at: Option<DateTime<Utc>>,
+    pub recurrence: Option<Recurrence>,
+}
+
+impl NewTask {
+    /// Create new top-level task fields with default priority, and no description, due date or
+    /// recurrence.
+    pub fn new(name: impl Into<String>, status: Status) -> Self {
+        Self {
+            parent_id: None,
+            name: name.into(),
+            description: String::new(),
+            status,
+            priority: Priority::default(),
+            due_at: None,
+            recurrence: None,
+        }
+    }
+}
+
+/// Changes to apply to an existing task; fields left as `None` are unchanged.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct TaskChanges {
+    pub parent_id: Option<Option<TaskId>>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub status: Status,
+    pub priority: Option<Priority>,
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    pub recurrence: Option<Option<Recurrence>>,
+    /// Complete the task even if it has incomplete subtasks, completing them too.
+    pub force: bool,
+}
+
+impl TaskChanges {
+    /// Apply these changes to a task.
+    pub fn apply(self, task: Task) -> Task {
+        Task {
+            parent_id: self.parent_id.unwrap_or(task.parent_id),
+            name: self.name.unwrap_or(task.name),
+            description: self.description.or(task.description),
+            status: self.status,
+            priority: self.priority.unwrap_or(task.priority),
+            due_at: self.due_at.unwrap_or(task.due_at),
+            recurrence: self.recurrence.unwrap_or(task.recurrence),
+            ..task
+        }
+    }
+}
+
+/// A task together with its subtasks.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct TaskNode {
+    pub task: Task,
+    pub subtasks: Vec<TaskNode>,
+}
+
+impl TaskNode {
+    /// Arrange a list of tasks into trees, keeping list order among siblings. Tasks whose parent
+    /// is not in the list become roots.
+    pub fn build(tasks: Vec<Task>) -> Vec<TaskNode> {
+        let ids: HashSet<TaskId> = tasks.iter().map(|t| t.id.clone()).collect();
+        let mut roots = Vec::new();
+        let mut children: HashMap<TaskId, Vec<Task>> = HashMap::new();
+        for task in tasks {
+            match task.parent_id.clone().filter(|id| ids.contains(id)) {
+                Some(parent_id) => children.entry(parent_id).or_default().push(task),
+                None => roots.push(task),
+            }
+        }
+        roots
+            .into_iter()
+            .map(|task| Self::attach(task, &mut children))
+            .collect()
+    }
+
+    /// Build a node, taking its subtasks from the children map.
+    fn attach(task: Task, children: &mut HashMap<TaskId, Vec<Task>>) -> TaskNode {
+        let subtasks = children
+            .remove(&task.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::attach(child, children))
+            .collect();
+        TaskNode { task, subtasks }
+    }
+}
+
+/// Where to move a task, relative to a sibling task in the same story.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Placement {
+    Before(TaskId),
+    After(TaskId),
+}
+
+/// Criteria for selecting tasks within a story.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TaskFilter {
+    /// Only tasks with all of these labels.
+    pub label_ids: Vec<LabelId>,
+    /// Only tasks with any of these statuses; empty means any status.
+    pub statuses: Vec<Status>,
+    /// Only tasks created in this window.
+    pub created: TimeRange,
+    /// Only tasks last updated in this window.
+    pub updated: TimeRange,
+}
+
+/// The tasks a batch update applies to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TaskSelection {
+    /// These tasks, by id, in order.
+    Ids(Vec<TaskId>),
+    /// All tasks in a story matching a filter, in position order.
+    Story(StoryId, TaskFilter),
+}
+
+/// The outcome of a batch update for one task: the updated task, or `None` if it was not found.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct TaskResult {
+    pub task_id: TaskId,
+    pub task: Option<Task>,
+}
+
+/// Whether to list tasks flat, or nested under their parents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TaskLayout {
+    #[default]
+    Flat,
+    Tree,
+}
+
+/// The order to list tasks in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum TaskOrder {
+    #[default]
+    Position,
+    Priority,
+    CreatedAt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str, id: u128, parent: Option<u128>) -> Task {
+        let now = Utc::now();
+        Task {
+            id: TaskId(Uuid::from_u128(id)),
+            story_id: StoryId(Uuid::nil()),
+            parent_id: parent.map(|p| TaskId(Uuid::from_u128(p))),
+            name: name.into(),
+            description: None,
+            status: Status::Incomplete,
+            priority: Priority::default(),
+            position: name.into(),
+            due_at: None,
+            assignee: None,
+            recurrence: None,
+            labels: Vec::new(),
+            blocker_ids: Vec::new(),
+            blocked: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn build_tree_keeps_order() {
+        let tasks = vec![
+            task("a", 1, None),
+            task("a2", 3, Some(1)),
+            task("b", 2, None),
+            task("a1", 4, Some(1)),
+            task("a2x", 5, Some(3)),
+            task("orphan", 6, Some(99)),
+        ];
+        let tree = TaskNode::build(tasks);
+        let names: Vec<_> = tree.iter().map(|n| n.task.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "orphan"]);
+        let names: Vec<_> = tree[0]
+            .subtasks
+            .iter()
+            .map(|n| n.task.name.as_str())
+            .collect();
+        assert_eq!(names, ["a2", "a1"]);
+        assert_eq!(tree[0].subtasks[0].subtasks[0].task.name, "a2x");
+    }
+}
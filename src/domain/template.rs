@@ -0,0 +1,144 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use uuid::Uuid;
+
+/// The newtype template id.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TemplateId(pub Uuid);
+
+// Display the inner uuid.
+impl std::fmt::Display for TemplateId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The placeholders that can appear in template patterns, between braces.
+pub const TEMPLATE_PLACEHOLDERS: [&str; 4] = ["date", "week", "year", "month"];
+
+/// The template domain object: a standard checklist that stories can be started from.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Template {
+    pub id: TemplateId,
+    pub name: String,
+    /// The name of instantiated stories, with placeholders.
+    pub name_pattern: String,
+    /// The names of instantiated tasks in order, with placeholders.
+    pub task_names: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Template {
+    /// Render the story name and task names for the given date.
+    pub fn render(&self, date: NaiveDate) -> Result<(String, Vec<String>), String> {
+        let name = render_pattern(&self.name_pattern, date)?;
+        let task_names = self
+            .task_names
+            .iter()
+            .map(|pattern| render_pattern(pattern, date))
+            .collect::<Result<_, _>>()?;
+        Ok((name, task_names))
+    }
+}
+
+/// Fields for creating a template.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NewTemplate {
+    pub name: String,
+    pub name_pattern: String,
+    pub task_names: Vec<String>,
+}
+
+/// Substitute the placeholders in a pattern for a date: `{date}` is `2026-10-19`, `{week}` is the
+/// ISO week `2026-W43`, `{year}` is `2026` and `{month}` is `10`. Literal braces are doubled.
+pub fn render_pattern(pattern: &str, date: NaiveDate) -> Result<String, String> {
+    let mut out = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let rest = chars.as_str();
+                let Some(end) = rest.find('}') else {
+                    return Err("unclosed placeholder; use {{ for a literal brace".to_string());
+                };
+                let placeholder = &rest[..end];
+                let value = match placeholder {
+                    "date" => date.format("%Y-%m-%d").to_string(),
+                    "week" => {
+                        let week = date.iso_week();
+                        format!("{}-W{:02}", week.year(), week.week())
+                    }
+                    "year" => date.format("%Y").to_string(),
+                    "month" => date.format("%m").to_string(),
+                    _ => {
+                        return Err(format!(
+                            "unknown placeholder {{{placeholder}}}; expected one of {}",
+                            TEMPLATE_PLACEHOLDERS.map(|p| format!("{{{p}}}")).join(", ")
+                        ));
+                    }
+                };
+                out.push_str(&value);
+                chars = rest[end + 1..].chars();
+            }
+            '}' => return Err("unmatched }; use }} for a literal brace".to_string()),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn render_placeholders() {
+        let day = date(2026, 10, 19);
+        let name = render_pattern("Review {week} ({date})", day).unwrap();
+        assert_eq!(name, "Review 2026-W43 (2026-10-19)");
+        let name = render_pattern("Close {year}-{month}", day).unwrap();
+        assert_eq!(name, "Close 2026-10");
+        assert_eq!(render_pattern("Plain", day).unwrap(), "Plain");
+    }
+
+    #[test]
+    fn render_iso_week_year() {
+        // The first days of January can belong to the last week of the previous year.
+        let name = render_pattern("{week}", date(2027, 1, 1)).unwrap();
+        assert_eq!(name, "2026-W53");
+    }
+
+    #[test]
+    fn render_escaped_braces() {
+        let name = render_pattern("{{date}} is {date}", date(2026, 1, 2)).unwrap();
+        assert_eq!(name, "{date} is 2026-01-02");
+    }
+
+    #[test]
+    fn render_rejects_bad_placeholders() {
+        let day = date(2026, 10, 19);
+        assert!(render_pattern("{day}", day).unwrap_err().contains("{day}"));
+        assert!(
+            render_pattern("{date", day)
+                .unwrap_err()
+                .contains("unclosed")
+        );
+        assert!(
+            render_pattern("date}", day)
+                .unwrap_err()
+                .contains("unmatched")
+        );
+    }
+}
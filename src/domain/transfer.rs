@@ -0,0 +1,53 @@
+use crate::domain::{NewTask, Story, StoryId, Task, TaskId};
+use chrono::{DateTime, Utc};
+
+/// A story with all of its tasks, in position order.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct StoryExport {
+    pub story: Story,
+    pub tasks: Vec<Task>,
+}
+
+/// A story to import with its tasks. Ids and timestamps are kept when given, so an export can be
+/// restored as it was; missing ones are generated.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct StoryImport {
+    pub id: Option<StoryId>,
+    pub name: String,
+    pub description: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Tasks in position order, with parents before their subtasks.
+    pub tasks: Vec<TaskImport>,
+}
+
+/// What one committed import batch created.
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
+pub struct ImportSummary {
+    /// The new story ids, in import order.
+    pub story_ids: Vec<StoryId>,
+    pub tasks_imported: u64,
+}
+
+/// A task to import. A parent id refers to an earlier task in the same story.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct TaskImport {
+    pub id: Option<TaskId>,
+    pub task: NewTask,
+    pub assignee: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl TaskImport {
+    /// Import new task fields, generating an id and timestamps.
+    pub fn new(task: NewTask) -> Self {
+        Self {
+            id: None,
+            task,
+            assignee: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+}
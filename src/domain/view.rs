@@ -0,0 +1,16 @@
+/// How much of a story or task to load.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum View {
+    /// Everything except Markdown descriptions, for lightweight listings.
+    #[default]
+    Basic,
+    /// All fields, including descriptions.
+    Full,
+}
+
+impl View {
+    /// Whether descriptions should be loaded.
+    pub fn is_full(self) -> bool {
+        self == View::Full
+    }
+}
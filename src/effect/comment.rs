@@ -0,0 +1,26 @@
+use crate::{
+    Result,
+    domain::{Comment, CommentId, Page, PageParams, TaskId, View},
+};
+use async_trait::async_trait;
+
+/// Abstract type for stateful I/O effects that can be performed on task comments.
+#[async_trait]
+pub trait CommentEffects: Send + Sync {
+    /// Add a comment to a task
+    async fn add(&self, task_id: TaskId, author: String, body: String) -> Result<Comment>;
+
+    /// Fetch a page of comments for a task
+    async fn list(
+        &self,
+        task_id: TaskId,
+        page_params: PageParams,
+        view: View,
+    ) -> Result<Page<Comment>>;
+
+    /// Replace the body of a comment, if written by the author
+    async fn edit(&self, comment_id: CommentId, author: String, body: String) -> Result<Comment>;
+
+    /// Delete a comment, if written by the author
+    async fn delete(&self, comment_id: CommentId, author: String) -> Result<()>;
+}
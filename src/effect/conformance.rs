@@ -0,0 +1,480 @@
+use crate::{
+    Error,
+    domain::{
+        KeysetPage, NewStory, NewTask, PageParams, Placement, Status, StoryChanges, StoryExpr,
+        StoryFilter, StoryId, StoryImport, StorySort, StorySortField, TaskChanges, TaskFilter,
+        TaskId, TaskImport, TaskOrder, TaskSelection, TextMatch, View,
+    },
+    effect::{CommentEffects, StoryEffects, TaskEffects},
+};
+use chrono::{TimeDelta, Utc};
+use futures_util::TryStreamExt;
+use uuid::Uuid;
+
+// A suite of checks that every effects backend must pass, so in-memory and database backed
+// effects can be used interchangeably. Each suite works in its own stories, so both can run
+// against the same database.
+
+/// Check story effects: paging, not found errors, duplication, transfer and cascade delete.
+pub async fn stories(
+    stories: &impl StoryEffects,
+    tasks: &impl TaskEffects,
+    comments: &impl CommentEffects,
+) {
+    // Create stories, loading descriptions and stats only when asked
+    let read = NewStory {
+        description: "Fiction first".into(),
+        ..NewStory::new("Books to read")
+    };
+    let read = stories.create(read).await.unwrap();
+    assert_eq!(read.description.as_deref(), Some("Fiction first"));
+    let basic = stories
+        .get(read.id.clone(), View::Basic, false)
+        .await
+        .unwrap();
+    assert_eq!(basic.description, None);
+    assert_eq!(basic.stats, None);
+    let full = stories
+        .get(read.id.clone(), View::Full, true)
+        .await
+        .unwrap();
+    assert_eq!(full.description.as_deref(), Some("Fiction first"));
+    assert_eq!(full.stats.map(|s| s.total_tasks()), Some(0));
+    let missing = StoryId(Uuid::new_v4());
+    let result = stories.get(missing.clone(), View::Full, false).await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+
+    let buy = stories.create(NewStory::new("Books to buy")).await.unwrap();
+    let watch = stories
+        .create(NewStory::new("Movies to watch"))
+        .await
+        .unwrap();
+
+    // Page through stories in creation order with numeric cursors
+    let filter = StoryFilter {
+        expr: Some(StoryExpr::Name(TextMatch::Has("to".into()))),
+        ..StoryFilter::default()
+    };
+    let list = |cursor, filter: &StoryFilter, sort, after| {
+        let params = PageParams(cursor, 2);
+        stories.list(params, filter.clone(), sort, after, View::Basic, false)
+    };
+    let page = list(1, &filter, StorySort::default(), None).await.unwrap();
+    let ids: Vec<StoryId> = page.stories.into_iter().map(|s| s.id).collect();
+    assert_eq!(ids, vec![read.id.clone(), buy.id.clone()]);
+    assert!(page.next_cursor > 0);
+    let page = list(page.next_cursor, &filter, StorySort::default(), None)
+        .await
+        .unwrap();
+    let ids: Vec<StoryId> = page.stories.into_iter().map(|s| s.id).collect();
+    assert_eq!(ids, vec![watch.id.clone()]);
+    assert_eq!(page.next_keyset, None);
+
+    // Page through filtered stories by name with keysets
+    let filter = StoryFilter {
+        expr: Some(StoryExpr::Name(TextMatch::Has("books*".into()))),
+        ..StoryFilter::default()
+    };
+    let sort = StorySort {
+        field: StorySortField::Name,
+        descending: true,
+    };
+    let page = list(1, &filter, sort, None).await.unwrap();
+    let ids: Vec<StoryId> = page.stories.into_iter().map(|s| s.id).collect();
+    assert_eq!(ids, vec![read.id.clone(), buy.id.clone()]);
+    assert_eq!(page.next_cursor, 0);
+    let page = list(1, &filter, sort, page.next_keyset).await.unwrap();
+    assert!(page.stories.is_empty());
+    assert_eq!(page.next_keyset, None);
+
+    // Updates keep a description left out, and missing stories are not found
+    let changes = StoryChanges {
+        name: "Books to reread".into(),
+        description: None,
+    };
+    let updated = stories.update(read.id.clone(), changes).await.unwrap();
+    assert_eq!(updated.name, "Books to reread");
+    assert_eq!(updated.description.as_deref(), Some("Fiction first"));
+    let changes = StoryChanges {
+        name: "Gone".into(),
+        description: None,
+    };
+    let result = stories.update(missing.clone(), changes).await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+    let result = stories.delete(missing.clone()).await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+
+    // Stats count task progress
+    let dune = NewTask::new("Dune", Status::Incomplete);
+    let dune = tasks.create(read.id.clone(), dune).await.unwrap();
+    let emma = NewTask::new("Emma", Status::Complete);
+    tasks.create(read.id.clone(), emma).await.unwrap();
+    let story = stories
+        .get(read.id.clone(), View::Basic, true)
+        .await
+        .unwrap();
+    let stats = story.stats.unwrap();
+    assert_eq!((stats.incomplete_tasks, stats.complete_tasks), (1, 1));
+
+    // Duplicates copy the description and tasks in order, optionally reopened
+    let copy = stories
+        .duplicate(read.id.clone(), "Books to read again".into(), true)
+        .await
+        .unwrap();
+    assert_ne!(copy.id, read.id);
+    assert_eq!(copy.description.as_deref(), Some("Fiction first"));
+    let copied = tasks
+        .list(
+            copy.id.clone(),
+            TaskOrder::Position,
+            TaskFilter::default(),
+            View::Basic,
+        )
+        .await
+        .unwrap();
+    let copied: Vec<(String, Status)> = copied.into_iter().map(|t| (t.name, t.status)).collect();
+    assert_eq!(
+        copied,
+        vec![
+            ("Dune".into(), Status::Incomplete),
+            ("Emma".into(), Status::Incomplete)
+        ]
+    );
+    let result = stories
+        .duplicate(missing.clone(), "Gone".into(), false)
+        .await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+
+    // Export a story with its tasks
+    let exports: Vec<_> = stories
+        .export(Some(read.id.clone()))
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(exports.len(), 1);
+    assert_eq!(
+        exports[0].story.description.as_deref(),
+        Some("Fiction first")
+    );
+    let names: Vec<&str> = exports[0].tasks.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["Dune", "Emma"]);
+    let result = stories.export(Some(missing.clone())).await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+
+    // Import stories all or nothing, keeping task recurrence and reopening parents of
+    // incomplete subtasks
+    let weekly = Some("FREQ=WEEKLY;COUNT=3".parse().unwrap());
+    let ulysses = NewTask {
+        recurrence: weekly,
+        ..NewTask::new("Ulysses", Status::Incomplete)
+    };
+    let odyssey = TaskImport {
+        id: Some(TaskId(Uuid::new_v4())),
+        ..TaskImport::new(NewTask::new("Odyssey", Status::Complete))
+    };
+    let chapter = NewTask {
+        parent_id: odyssey.id.clone(),
+        ..NewTask::new("Chapter one", Status::Incomplete)
+    };
+    let import = StoryImport {
+        name: "Imported to read".into(),
+        tasks: vec![TaskImport::new(ulysses), odyssey, TaskImport::new(chapter)],
+        ..StoryImport::default()
+    };
+    let summary = stories.import(vec![import]).await.unwrap();
+    assert_eq!(summary.story_ids.len(), 1);
+    assert_eq!(summary.tasks_imported, 3);
+    let imported = tasks
+        .list(
+            summary.story_ids[0].clone(),
+            TaskOrder::Position,
+            TaskFilter::default(),
+            View::Basic,
+        )
+        .await
+        .unwrap();
+    assert_eq!(imported[0].recurrence, weekly);
+    assert_eq!(imported[1].status, Status::Incomplete);
+    let partial = StoryImport {
+        name: "Partially imported".into(),
+        ..StoryImport::default()
+    };
+    let again = StoryImport {
+        id: Some(summary.story_ids[0].clone()),
+        name: "Imported again".into(),
+        ..StoryImport::default()
+    };
+    let result = stories.import(vec![partial, again]).await;
+    assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+    let filter = StoryFilter {
+        expr: Some(StoryExpr::Name(TextMatch::Has("partially".into()))),
+        ..StoryFilter::default()
+    };
+    let page = list(1, &filter, StorySort::default(), None).await.unwrap();
+    assert!(page.stories.is_empty());
+
+    // Deleting a story deletes its tasks and their comments
+    let comment = comments.add(dune.id.clone(), "ann".into(), "Spice".into());
+    comment.await.unwrap();
+    stories.delete(read.id.clone()).await.unwrap();
+    let result = stories.get(read.id.clone(), View::Basic, false).await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+    let result = tasks
+        .list(
+            read.id.clone(),
+            TaskOrder::Position,
+            TaskFilter::default(),
+            View::Basic,
+        )
+        .await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+    let result = comments
+        .list(dune.id.clone(), PageParams::default(), View::Basic)
+        .await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+    let result = tasks.delete(dune.id).await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+}
+
+/// Check task effects: ordering, subtasks, dependencies, batches, recurrence and delete.
+pub async fn tasks(stories: &impl StoryEffects, tasks: &impl TaskEffects) {
+    let story = stories.create(NewStory::new("Chores")).await.unwrap();
+    let other = stories.create(NewStory::new("Errands")).await.unwrap();
+    let new = |name: &str| NewTask::new(name, Status::Incomplete);
+    let names = async |filter: TaskFilter| -> Vec<String> {
+        let list = tasks.list(story.id.clone(), TaskOrder::Position, filter, View::Basic);
+        list.await.unwrap().into_iter().map(|t| t.name).collect()
+    };
+
+    // Tasks need a story
+    let result = tasks.create(StoryId(Uuid::new_v4()), new("Lost")).await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+
+    // Reorder tasks within a story
+    let laundry = tasks
+        .create(story.id.clone(), new("Laundry"))
+        .await
+        .unwrap();
+    let dishes = tasks.create(story.id.clone(), new("Dishes")).await.unwrap();
+    let vacuum = tasks.create(story.id.clone(), new("Vacuum")).await.unwrap();
+    let groceries = tasks
+        .create(other.id.clone(), new("Groceries"))
+        .await
+        .unwrap();
+    let placement = Placement::Before(laundry.id.clone());
+    tasks.reorder(vacuum.id.clone(), placement).await.unwrap();
+    assert_eq!(
+        names(TaskFilter::default()).await,
+        vec!["Vacuum", "Laundry", "Dishes"]
+    );
+    let placement = Placement::After(laundry.id.clone());
+    let result = tasks.reorder(laundry.id.clone(), placement).await;
+    assert!(matches!(result, Err(Error::InvalidArgs { .. })));
+    let placement = Placement::After(groceries.id.clone());
+    let result = tasks.reorder(laundry.id.clone(), placement).await;
+    assert!(matches!(result, Err(Error::InvalidArgs { .. })));
+
+    // Completion rolls down to subtasks only when forced; reopening rolls up
+    let fold = NewTask {
+        parent_id: Some(laundry.id.clone()),
+        ..new("Fold")
+    };
+    let fold = tasks.create(story.id.clone(), fold).await.unwrap();
+    let complete = || TaskChanges {
+        status: Status::Complete,
+        ..TaskChanges::default()
+    };
+    let result = tasks.update(laundry.id.clone(), complete()).await;
+    assert!(matches!(result, Err(Error::FailedPrecondition { .. })));
+    let forced = TaskChanges {
+        force: true,
+        ..complete()
+    };
+    tasks.update(laundry.id.clone(), forced).await.unwrap();
+    let complete_only = TaskFilter {
+        statuses: vec![Status::Complete],
+        ..TaskFilter::default()
+    };
+    assert_eq!(names(complete_only.clone()).await, vec!["Laundry", "Fold"]);
+    let (fold, _) = tasks
+        .update(fold.id.clone(), TaskChanges::default())
+        .await
+        .unwrap();
+    assert_eq!(fold.status, Status::Incomplete);
+    assert!(names(complete_only.clone()).await.is_empty());
+
+    // Dependencies block completion and cannot form cycles
+    let blocked = tasks
+        .add_dependency(dishes.id.clone(), vacuum.id.clone())
+        .await
+        .unwrap();
+    assert!(blocked.blocked);
+    assert_eq!(blocked.blocker_ids, vec![vacuum.id.clone()]);
+    let result = tasks
+        .add_dependency(vacuum.id.clone(), dishes.id.clone())
+        .await;
+    assert!(matches!(result, Err(Error::InvalidArgs { .. })));
+    let result = tasks
+        .add_dependency(dishes.id.clone(), dishes.id.clone())
+        .await;
+    assert!(matches!(result, Err(Error::InvalidArgs { .. })));
+    let result = tasks.update(dishes.id.clone(), complete()).await;
+    assert!(matches!(result, Err(Error::FailedPrecondition { .. })));
+    let unblocked = tasks
+        .remove_dependency(dishes.id.clone(), vacuum.id.clone())
+        .await
+        .unwrap();
+    assert!(!unblocked.blocked);
+
+    // Forced completion fails when a subtask it would complete is blocked
+    let bathroom = tasks
+        .create(story.id.clone(), new("Bathroom"))
+        .await
+        .unwrap();
+    let scrub = NewTask {
+        parent_id: Some(bathroom.id.clone()),
+        ..new("Scrub")
+    };
+    let scrub = tasks.create(story.id.clone(), scrub).await.unwrap();
+    tasks
+        .add_dependency(scrub.id.clone(), vacuum.id.clone())
+        .await
+        .unwrap();
+    let forced = TaskChanges {
+        force: true,
+        ..complete()
+    };
+    let result = tasks.update(bathroom.id.clone(), forced).await;
+    assert!(matches!(result, Err(Error::FailedPrecondition { .. })));
+    let selection = TaskSelection::Ids(vec![bathroom.id.clone()]);
+    let result = tasks
+        .batch_update_status(selection, Status::Complete, true)
+        .await;
+    assert!(matches!(result, Err(Error::FailedPrecondition { .. })));
+    let selection = TaskSelection::Ids(vec![bathroom.id.clone(), vacuum.id.clone()]);
+    tasks
+        .batch_update_status(selection, Status::Complete, true)
+        .await
+        .unwrap();
+    tasks.delete(bathroom.id).await.unwrap();
+    tasks
+        .update(vacuum.id.clone(), TaskChanges::default())
+        .await
+        .unwrap();
+
+    // Find overdue tasks across stories
+    let now = Utc::now();
+    let due = TaskChanges {
+        due_at: Some(Some(now - TimeDelta::days(1))),
+        ..TaskChanges::default()
+    };
+    tasks.update(dishes.id.clone(), due).await.unwrap();
+    let KeysetPage(_, overdue) = tasks.list_overdue(now, None, 10).await.unwrap();
+    let ids: Vec<TaskId> = overdue.into_iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![dishes.id.clone()]);
+
+    // Page through assigned tasks
+    tasks
+        .assign(laundry.id.clone(), Some("ann".into()))
+        .await
+        .unwrap();
+    tasks
+        .assign(groceries.id.clone(), Some("ann".into()))
+        .await
+        .unwrap();
+    let assigned = tasks.list_by_assignee("ann".into(), None, None, 1);
+    let KeysetPage(next, first) = assigned.await.unwrap();
+    assert!(next.is_some());
+    let assigned = tasks.list_by_assignee("ann".into(), None, next, 1);
+    let KeysetPage(_, second) = assigned.await.unwrap();
+    let mut ids: Vec<TaskId> = first.into_iter().chain(second).map(|t| t.id).collect();
+    ids.sort();
+    let mut expected = vec![laundry.id.clone(), groceries.id.clone()];
+    expected.sort();
+    assert_eq!(ids, expected);
+    let assigned = tasks.list_by_assignee("ann".into(), Some(Status::Complete), None, 10);
+    let KeysetPage(_, complete_assigned) = assigned.await.unwrap();
+    assert!(complete_assigned.is_empty());
+
+    // Batches create in order, and update the tasks found
+    let created = tasks.batch_create(story.id.clone(), Vec::new()).await;
+    assert!(created.unwrap().is_empty());
+    let created = tasks
+        .batch_create(story.id.clone(), vec![new("Mop"), new("Dust")])
+        .await
+        .unwrap();
+    let created: Vec<String> = created.into_iter().map(|t| t.name).collect();
+    assert_eq!(created, vec!["Mop", "Dust"]);
+    let missing = TaskId(Uuid::new_v4());
+    let selection = TaskSelection::Ids(vec![dishes.id.clone(), missing.clone()]);
+    let results = tasks
+        .batch_update_status(selection, Status::Complete, false)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[0].task.as_ref().map(|t| t.status),
+        Some(Status::Complete)
+    );
+    assert_eq!(results[1].task_id, missing);
+    assert_eq!(results[1].task, None);
+    assert_eq!(names(complete_only).await, vec!["Dishes"]);
+
+    // Completing a recurring task creates the next occurrence until the rule runs out, so
+    // recurring tasks cannot be completed in a batch or by forcing their parent
+    let water = NewTask {
+        due_at: Some(now),
+        recurrence: Some("FREQ=DAILY;COUNT=2".parse().unwrap()),
+        ..new("Water plants")
+    };
+    let water = tasks.create(story.id.clone(), water).await.unwrap();
+    let selection = TaskSelection::Ids(vec![water.id.clone()]);
+    let result = tasks
+        .batch_update_status(selection, Status::Complete, false)
+        .await;
+    assert!(matches!(result, Err(Error::FailedPrecondition { .. })));
+    let garden = tasks.create(story.id.clone(), new("Garden")).await.unwrap();
+    let weed = NewTask {
+        parent_id: Some(garden.id.clone()),
+        recurrence: Some("FREQ=WEEKLY".parse().unwrap()),
+        ..new("Weed")
+    };
+    tasks.create(story.id.clone(), weed).await.unwrap();
+    let forced = TaskChanges {
+        force: true,
+        ..complete()
+    };
+    let result = tasks.update(garden.id.clone(), forced).await;
+    assert!(matches!(result, Err(Error::FailedPrecondition { .. })));
+    tasks.delete(garden.id).await.unwrap();
+    let (done, next) = tasks.update(water.id, complete()).await.unwrap();
+    assert_eq!(done.recurrence, None);
+    let next = next.unwrap();
+    assert_eq!(next.status, Status::Incomplete);
+    assert_eq!(
+        next.due_at,
+        done.due_at.map(|due_at| due_at + TimeDelta::days(1))
+    );
+    let (_, last) = tasks.update(next.id, complete()).await.unwrap();
+    assert_eq!(last, None);
+
+    // Deleting a task deletes its subtasks
+    tasks.delete(laundry.id.clone()).await.unwrap();
+    let remaining = names(TaskFilter::default()).await;
+    assert!(
+        !remaining
+            .iter()
+            .any(|name| name == "Laundry" || name == "Fold")
+    );
+    let result = tasks.delete(fold.id).await;
+    assert!(matches!(result, Err(Error::NotFound { .. })));
+    let result = tasks.list(
+        StoryId(Uuid::new_v4()),
+        TaskOrder::Position,
+        TaskFilter::default(),
+        View::Basic,
+    );
+    assert!(matches!(result.await, Err(Error::NotFound { .. })));
+}
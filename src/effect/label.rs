@@ -0,0 +1,24 @@
+use crate::{
+    Result,
+    domain::{Label, LabelId, LabelTarget},
+};
+use async_trait::async_trait;
+
+/// Abstract type for stateful I/O effects that can be performed on labels.
+#[async_trait]
+pub trait LabelEffects: Send + Sync {
+    /// Create a new label
+    async fn create(&self, name: String) -> Result<Label>;
+
+    /// Fetch all labels
+    async fn list(&self) -> Result<Vec<Label>>;
+
+    /// Delete an existing label
+    async fn delete(&self, label_id: LabelId) -> Result<()>;
+
+    /// Attach labels to a story or task, returning all of its labels
+    async fn add(&self, target: LabelTarget, label_ids: Vec<LabelId>) -> Result<Vec<Label>>;
+
+    /// Detach labels from a story or task, returning its remaining labels
+    async fn remove(&self, target: LabelTarget, label_ids: Vec<LabelId>) -> Result<Vec<Label>>;
+}
@@ -1,8 +1,28 @@
+mod comment;
+mod label;
+mod search;
 mod story;
 mod task;
+mod template;
+
+/// Checks every effects backend must pass
+#[cfg(test)]
+pub(crate) mod conformance;
 
 /// Story side effects
 pub use story::StoryEffects;
 
 /// Task side effects
 pub use task::TaskEffects;
+
+/// Label side effects
+pub use label::LabelEffects;
+
+/// Comment side effects
+pub use comment::CommentEffects;
+
+/// Search side effects
+pub use search::SearchEffects;
+
+/// Template side effects
+pub use template::TemplateEffects;
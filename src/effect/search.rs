@@ -0,0 +1,12 @@
+use crate::{
+    Result,
+    domain::{Page, PageParams, SearchHit, SearchQuery},
+};
+use async_trait::async_trait;
+
+/// Abstract type for full-text search effects across stories and tasks.
+#[async_trait]
+pub trait SearchEffects: Send + Sync {
+    /// Fetch a page of stories and tasks matching a query, most relevant first
+    async fn search(&self, query: SearchQuery, page_params: PageParams) -> Result<Page<SearchHit>>;
+}
@@ -1,21 +1,54 @@
 use crate::{
     Result,
-    domain::{Page, PageParams, Story, StoryId},
+    domain::{
+        ImportSummary, NewStory, PageParams, Story, StoryChanges, StoryExport, StoryFilter,
+        StoryId, StoryImport, StoryKeyset, StoryPage, StorySort, View,
+    },
 };
 use async_trait::async_trait;
+use futures_util::stream::BoxStream;
 
 /// Abstract type for stateful I/O effects that can be performed on stories.
 #[async_trait]
 pub trait StoryEffects: Send + Sync {
     /// Create a new story
-    async fn create(&self, name: String) -> Result<Story>;
+    async fn create(&self, story: NewStory) -> Result<Story>;
 
-    /// Fetch a page of stories
-    async fn list(&self, page_params: PageParams) -> Result<Page<Story>>;
+    /// Fetch a story, with task progress if requested
+    async fn get(&self, story_id: StoryId, view: View, with_stats: bool) -> Result<Story>;
+
+    /// Fetch a page of stories in sort order, with task progress if requested
+    async fn list(
+        &self,
+        page_params: PageParams,
+        filter: StoryFilter,
+        sort: StorySort,
+        after: Option<StoryKeyset>,
+        view: View,
+        with_stats: bool,
+    ) -> Result<StoryPage>;
 
     /// Update an existing story
-    async fn update(&self, story_id: StoryId, name: String) -> Result<Story>;
+    async fn update(&self, story_id: StoryId, changes: StoryChanges) -> Result<Story>;
 
     /// Delete an existing story
     async fn delete(&self, story_id: StoryId) -> Result<()>;
+
+    /// Copy a story with all of its tasks under a new name, optionally making every task
+    /// incomplete
+    async fn duplicate(
+        &self,
+        story_id: StoryId,
+        name: String,
+        reset_statuses: bool,
+    ) -> Result<Story>;
+
+    /// Stream all stories, or just one, with their tasks
+    async fn export(
+        &self,
+        story_id: Option<StoryId>,
+    ) -> Result<BoxStream<'static, Result<StoryExport>>>;
+
+    /// Import a batch of stories with their tasks, all or nothing
+    async fn import(&self, stories: Vec<StoryImport>) -> Result<ImportSummary>;
 }
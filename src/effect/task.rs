@@ -1,20 +1,70 @@
 use crate::{
     Result,
-    domain::{Status, StoryId, Task, TaskId},
+    domain::{
+        Keyset, KeysetPage, Limit, NewTask, Placement, Status, StoryId, Task, TaskChanges,
+        TaskFilter, TaskId, TaskOrder, TaskResult, TaskSelection, View,
+    },
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
 /// Abstract type for stateful I/O effects that can be performed on tasks.
 #[async_trait]
 pub trait TaskEffects: Send + Sync {
     /// Create a new task
-    async fn create(&self, story_id: StoryId, name: String, status: Status) -> Result<Task>;
+    async fn create(&self, story_id: StoryId, task: NewTask) -> Result<Task>;
+
+    /// Create several tasks in order, all or nothing
+    async fn batch_create(&self, story_id: StoryId, tasks: Vec<NewTask>) -> Result<Vec<Task>>;
 
     /// Fetch all tasks for a story
-    async fn list(&self, story_id: StoryId) -> Result<Vec<Task>>;
+    async fn list(
+        &self,
+        story_id: StoryId,
+        sort: TaskOrder,
+        filter: TaskFilter,
+        view: View,
+    ) -> Result<Vec<Task>>;
+
+    /// Fetch a page of incomplete tasks due before a time, across all stories
+    async fn list_overdue(
+        &self,
+        due_before: DateTime<Utc>,
+        after: Option<Keyset>,
+        limit: Limit,
+    ) -> Result<KeysetPage<Task>>;
+
+    /// Fetch a page of tasks assigned to a user across all stories, optionally with a status
+    async fn list_by_assignee(
+        &self,
+        assignee: String,
+        status: Option<Status>,
+        after: Option<Keyset>,
+        limit: Limit,
+    ) -> Result<KeysetPage<Task>>;
+
+    /// Set or clear the assignee of a task
+    async fn assign(&self, task_id: TaskId, assignee: Option<String>) -> Result<Task>;
+
+    /// Update an existing task, returning the next occurrence too when completing a recurring task
+    async fn update(&self, task_id: TaskId, changes: TaskChanges) -> Result<(Task, Option<Task>)>;
+
+    /// Set the status of many tasks at once, all or nothing
+    async fn batch_update_status(
+        &self,
+        selection: TaskSelection,
+        status: Status,
+        force: bool,
+    ) -> Result<Vec<TaskResult>>;
+
+    /// Move a task before or after a sibling task
+    async fn reorder(&self, task_id: TaskId, placement: Placement) -> Result<Task>;
+
+    /// Make a task wait on a blocker task
+    async fn add_dependency(&self, task_id: TaskId, blocker_id: TaskId) -> Result<Task>;
 
-    /// Update an existing task
-    async fn update(&self, task_id: TaskId, name: Option<String>, status: Status) -> Result<Task>;
+    /// Stop a task waiting on a blocker task
+    async fn remove_dependency(&self, task_id: TaskId, blocker_id: TaskId) -> Result<Task>;
 
     /// Delete an existing task.
     async fn delete(&self, task_id: TaskId) -> Result<()>;
@@ -0,0 +1,18 @@
+use crate::{
+    Result,
+    domain::{NewTemplate, Page, PageParams, Story, Task, Template, TemplateId},
+};
+use async_trait::async_trait;
+
+/// Abstract type for stateful I/O effects that can be performed on story templates.
+#[async_trait]
+pub trait TemplateEffects: Send + Sync {
+    /// Create a new template
+    async fn create(&self, template: NewTemplate) -> Result<Template>;
+
+    /// Fetch a page of templates
+    async fn list(&self, page_params: PageParams) -> Result<Page<Template>>;
+
+    /// Create a story and its tasks from a template, filling in placeholders for today
+    async fn instantiate(&self, template_id: TemplateId) -> Result<(Story, Vec<Task>)>;
+}
@@ -1,12 +1,40 @@
+use std::fmt;
+
 /// Project level error type
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    #[error("invalid arguments: {messages:?}")]
-    InvalidArgs { messages: Vec<String> },
+    #[error("aborted error: {message}")]
+    Aborted { message: String },
+    #[error("already exists error: {message}")]
+    AlreadyExists { message: String },
+    #[error("invalid config: {messages:?}")]
+    Config { messages: Vec<String> },
+    #[error("failed precondition error: {message}")]
+    FailedPrecondition { message: String },
+    #[error("invalid arguments: {violations:?}")]
+    InvalidArgs { violations: Vec<Violation> },
     #[error("internal error: {message}")]
     Internal { message: String },
-    #[error("not found error: {message}")]
-    NotFound { message: String },
+    #[error("not found error: {resource} not found: {id}")]
+    NotFound { resource: String, id: String },
+    #[error("permission denied: {message}")]
+    PermissionDenied { message: String },
+    #[error("unauthenticated: {message}")]
+    Unauthenticated { message: String },
+}
+
+/// A description of why a request field is invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub field: String,
+    pub description: String,
+}
+
+// Display as "field: description".
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.description)
+    }
 }
 
 // Error helpers
@@ -17,15 +45,60 @@ impl Error {
         }
     }
 
-    pub fn not_found(message: impl Into<String>) -> Self {
-        Error::NotFound {
+    pub fn aborted(message: impl Into<String>) -> Self {
+        Error::Aborted {
+            message: message.into(),
+        }
+    }
+
+    pub fn already_exists(message: impl Into<String>) -> Self {
+        Error::AlreadyExists {
+            message: message.into(),
+        }
+    }
+
+    pub fn failed_precondition(message: impl Into<String>) -> Self {
+        Error::FailedPrecondition {
             message: message.into(),
         }
     }
 
-    pub fn invalid_args(message: impl Into<String>) -> Self {
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        Error::PermissionDenied {
+            message: message.into(),
+        }
+    }
+
+    pub fn unauthenticated(message: impl Into<String>) -> Self {
+        Error::Unauthenticated {
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(resource: impl Into<String>, id: impl fmt::Display) -> Self {
+        Error::NotFound {
+            resource: resource.into(),
+            id: id.to_string(),
+        }
+    }
+
+    pub fn invalid_args(field: impl Into<String>, description: impl Into<String>) -> Self {
         Error::InvalidArgs {
-            messages: vec![message.into()],
+            violations: vec![Violation {
+                field: field.into(),
+                description: description.into(),
+            }],
+        }
+    }
+
+    /// Unwrap field violations, or describe any other error as a violation without a field.
+    pub fn into_violations(self) -> Vec<Violation> {
+        match self {
+            Error::InvalidArgs { violations } => violations,
+            err => vec![Violation {
+                field: String::new(),
+                description: err.to_string(),
+            }],
         }
     }
 }
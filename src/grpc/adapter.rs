@@ -1,20 +1,82 @@
 use crate::Error;
-use crate::domain::{Status, Story, StoryId, Task};
-use crate::proto::{StoryData, TaskData, TaskStatus};
+use crate::domain::{
+    Comment, CommentEdit, Label, Priority, SearchHit, Status, Story, StoryExport, StoryId,
+    StoryStats, Task, TaskLayout, TaskNode, TaskOrder, TaskResult, Template, View,
+};
+use crate::proto::{
+    CommentData, CommentEdit as ProtoCommentEdit, ExportStoriesResponse, LabelData,
+    SearchHit as ProtoSearchHit, SearchHitKind, StoryData, StoryStats as ProtoStoryStats, TaskData,
+    TaskLayout as ProtoTaskLayout, TaskPriority, TaskResult as ProtoTaskResult, TaskSort,
+    TaskStatus, TemplateData, View as ProtoView,
+};
 
 use chrono::{DateTime, Utc};
 use prost_types::Timestamp;
-use tonic::Status as GrpcStatus;
+use std::{collections::HashMap, time::Duration};
+use tonic::{Code, Status as GrpcStatus};
+use tonic_types::{ErrorDetails, StatusExt};
+use uuid::Uuid;
 
-/// Map project errors to grpc status.
+/// The error domain reported in google.rpc.ErrorInfo details.
+const ERROR_DOMAIN: &str = "gsdx.v1";
+
+/// The suggested delay before retrying an aborted request.
+const RETRY_DELAY_MS: u64 = 100;
+
+/// Map project errors to grpc status with google.rpc.Status details.
 impl From<Error> for GrpcStatus {
     fn from(err: Error) -> Self {
+        let mut details = ErrorDetails::new();
         match err {
-            Error::NotFound { message } => GrpcStatus::not_found(message),
-            Error::InvalidArgs { messages } => GrpcStatus::invalid_argument(messages.join(",")),
-            Error::Internal { message } => {
-                log::error!("Internal error in service: {}", message);
-                GrpcStatus::internal(message)
+            Error::Aborted { message } => {
+                details
+                    .set_error_info("ABORTED", ERROR_DOMAIN, HashMap::new())
+                    .set_retry_info(Some(Duration::from_millis(RETRY_DELAY_MS)));
+                GrpcStatus::with_error_details(Code::Aborted, message, details)
+            }
+            Error::AlreadyExists { message } => {
+                details.set_error_info("ALREADY_EXISTS", ERROR_DOMAIN, HashMap::new());
+                GrpcStatus::with_error_details(Code::AlreadyExists, message, details)
+            }
+            Error::FailedPrecondition { message } => {
+                details.set_error_info("FAILED_PRECONDITION", ERROR_DOMAIN, HashMap::new());
+                GrpcStatus::with_error_details(Code::FailedPrecondition, message, details)
+            }
+            Error::NotFound { resource, id } => {
+                let reason = format!("{}_NOT_FOUND", resource.to_uppercase());
+                details
+                    .set_error_info(reason, ERROR_DOMAIN, HashMap::new())
+                    .set_resource_info(&resource, &id, "", "");
+                let message = format!("{resource} not found: {id}");
+                GrpcStatus::with_error_details(Code::NotFound, message, details)
+            }
+            Error::PermissionDenied { message } => {
+                details.set_error_info("PERMISSION_DENIED", ERROR_DOMAIN, HashMap::new());
+                GrpcStatus::with_error_details(Code::PermissionDenied, message, details)
+            }
+            Error::Unauthenticated { message } => {
+                details.set_error_info("UNAUTHENTICATED", ERROR_DOMAIN, HashMap::new());
+                GrpcStatus::with_error_details(Code::Unauthenticated, message, details)
+            }
+            Error::InvalidArgs { violations } => {
+                let message = violations
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                for violation in violations {
+                    details.add_bad_request_violation(violation.field, violation.description);
+                }
+                details.set_error_info("INVALID_ARGUMENT", ERROR_DOMAIN, HashMap::new());
+                GrpcStatus::with_error_details(Code::InvalidArgument, message, details)
+            }
+            Error::Config { .. } | Error::Internal { .. } => {
+                let correlation_id = Uuid::new_v4().to_string();
+                log::error!("Internal error in service [{correlation_id}]: {err}");
+                let message = format!("internal error (correlation id: {correlation_id})");
+                let metadata = HashMap::from([("correlation_id".to_string(), correlation_id)]);
+                details.set_error_info("INTERNAL", ERROR_DOMAIN, metadata);
+                GrpcStatus::with_error_details(Code::Internal, message, details)
             }
         }
     }
@@ -29,6 +91,106 @@ impl From<Story> for StoryData {
             name: story.name,
             created_at: to_timestamp(story.created_at),
             updated_at: to_timestamp(story.updated_at),
+            labels: story.labels.into_iter().map(LabelData::from).collect(),
+            description: story.description,
+            stats: story.stats.map(ProtoStoryStats::from),
+        }
+    }
+}
+
+/// Map a domain story export to gRPC response type
+impl From<StoryExport> for ExportStoriesResponse {
+    fn from(export: StoryExport) -> Self {
+        Self {
+            story: Some(StoryData::from(export.story)),
+            tasks: export.tasks.into_iter().map(TaskData::from).collect(),
+        }
+    }
+}
+
+/// Map domain story stats to gRPC response type
+impl From<StoryStats> for ProtoStoryStats {
+    fn from(stats: StoryStats) -> Self {
+        Self {
+            incomplete_tasks: stats.incomplete_tasks,
+            complete_tasks: stats.complete_tasks,
+            total_tasks: stats.total_tasks(),
+            percent_complete: stats.percent_complete(),
+            last_activity_at: to_timestamp(stats.last_activity_at),
+        }
+    }
+}
+
+/// Map domain label to gRPC response type
+impl From<Label> for LabelData {
+    fn from(label: Label) -> Self {
+        Self {
+            label_id: label.id.to_string(),
+            name: label.name,
+            created_at: to_timestamp(label.created_at),
+            updated_at: to_timestamp(label.updated_at),
+        }
+    }
+}
+
+/// Map domain comment to gRPC comment data
+impl From<Comment> for CommentData {
+    fn from(comment: Comment) -> Self {
+        Self {
+            comment_id: comment.id.to_string(),
+            task_id: comment.task_id.to_string(),
+            author: comment.author,
+            body: comment.body,
+            edited: comment.edited,
+            history: comment
+                .history
+                .into_iter()
+                .map(ProtoCommentEdit::from)
+                .collect(),
+            created_at: to_timestamp(comment.created_at),
+            updated_at: to_timestamp(comment.updated_at),
+        }
+    }
+}
+
+/// Map a domain comment edit to gRPC
+impl From<CommentEdit> for ProtoCommentEdit {
+    fn from(edit: CommentEdit) -> Self {
+        Self {
+            body: edit.body,
+            replaced_at: to_timestamp(edit.replaced_at),
+        }
+    }
+}
+
+/// Map a domain template to gRPC template data
+impl From<Template> for TemplateData {
+    fn from(template: Template) -> Self {
+        Self {
+            template_id: template.id.to_string(),
+            name: template.name,
+            name_pattern: template.name_pattern,
+            task_names: template.task_names,
+            created_at: to_timestamp(template.created_at),
+            updated_at: to_timestamp(template.updated_at),
+        }
+    }
+}
+
+/// Map a domain search hit to gRPC response type
+impl From<SearchHit> for ProtoSearchHit {
+    fn from(hit: SearchHit) -> Self {
+        let kind = match hit.task_id {
+            Some(_) => SearchHitKind::Task,
+            None => SearchHitKind::Story,
+        };
+        Self {
+            kind: kind as i32,
+            story_id: hit.story_id.to_string(),
+            task_id: hit.task_id.map(|id| id.to_string()),
+            name: hit.name,
+            snippet: hit.snippet,
+            rank: hit.rank,
         }
     }
 }
@@ -55,17 +217,95 @@ impl From<TaskStatus> for Status {
     }
 }
 
+/// Map domain priority to gRPC task priority
+impl From<Priority> for TaskPriority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => TaskPriority::Low,
+            Priority::Medium => TaskPriority::Medium,
+            Priority::High => TaskPriority::High,
+            Priority::Urgent => TaskPriority::Urgent,
+        }
+    }
+}
+
+/// Map gRPC task sort to domain task order
+impl From<TaskSort> for TaskOrder {
+    fn from(sort: TaskSort) -> Self {
+        match sort {
+            TaskSort::Unspecified | TaskSort::Position => TaskOrder::Position,
+            TaskSort::Priority => TaskOrder::Priority,
+            TaskSort::CreatedAt => TaskOrder::CreatedAt,
+        }
+    }
+}
+
+/// Map gRPC view to domain view
+impl From<ProtoView> for View {
+    fn from(view: ProtoView) -> Self {
+        match view {
+            ProtoView::Unspecified | ProtoView::Basic => View::Basic,
+            ProtoView::Full => View::Full,
+        }
+    }
+}
+
 /// Map domain task to gRPC response type
 impl From<Task> for TaskData {
     fn from(task: Task) -> Self {
+        let overdue = task.is_overdue(Utc::now());
         let status = TaskStatus::from(task.status) as i32;
+        let priority = TaskPriority::from(task.priority) as i32;
         Self {
             task_id: task.id.to_string(),
             story_id: task.story_id.to_string(),
+            parent_task_id: task.parent_id.map(|id| id.to_string()),
             name: task.name,
             status,
             created_at: to_timestamp(task.created_at),
             updated_at: to_timestamp(task.updated_at),
+            due_at: task.due_at.and_then(to_timestamp),
+            overdue,
+            priority,
+            position: task.position,
+            labels: task.labels.into_iter().map(LabelData::from).collect(),
+            description: task.description,
+            subtasks: Vec::new(),
+            blocker_task_ids: task.blocker_ids.iter().map(ToString::to_string).collect(),
+            blocked: task.blocked,
+            assignee: task.assignee,
+            recurrence: task.recurrence.map(|rule| rule.to_string()),
+        }
+    }
+}
+
+/// Map a domain batch result to gRPC response type
+impl From<TaskResult> for ProtoTaskResult {
+    fn from(result: TaskResult) -> Self {
+        Self {
+            task_id: result.task_id.to_string(),
+            found: result.task.is_some(),
+            task: result.task.map(TaskData::from),
+        }
+    }
+}
+
+/// Map a domain task tree to gRPC response type
+impl From<TaskNode> for TaskData {
+    fn from(node: TaskNode) -> Self {
+        Self {
+            subtasks: node.subtasks.into_iter().map(TaskData::from).collect(),
+            ..TaskData::from(node.task)
+        }
+    }
+}
+
+/// Map gRPC task layout to domain task layout
+impl From<ProtoTaskLayout> for TaskLayout {
+    fn from(layout: ProtoTaskLayout) -> Self {
+        match layout {
+            ProtoTaskLayout::Unspecified | ProtoTaskLayout::Flat => TaskLayout::Flat,
+            ProtoTaskLayout::Tree => TaskLayout::Tree,
         }
     }
 }
@@ -77,3 +317,37 @@ fn to_timestamp(dt: DateTime<Utc>) -> Option<Timestamp> {
         nanos: dt.timestamp_subsec_nanos() as i32,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_args_field_violations() {
+        let status = GrpcStatus::from(Error::invalid_args("name", "cannot be empty"));
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let details = status.get_error_details();
+        let violations = details.bad_request().unwrap().field_violations.clone();
+        assert_eq!(violations[0].field, "name");
+        assert_eq!(details.error_info().unwrap().reason, "INVALID_ARGUMENT");
+    }
+
+    #[test]
+    fn not_found_resource_info() {
+        let status = GrpcStatus::from(Error::not_found("story", "1234"));
+        assert_eq!(status.code(), Code::NotFound);
+        let details = status.get_error_details();
+        assert_eq!(details.resource_info().unwrap().resource_name, "1234");
+        assert_eq!(details.error_info().unwrap().reason, "STORY_NOT_FOUND");
+    }
+
+    #[test]
+    fn internal_message_hidden() {
+        let status = GrpcStatus::from(Error::internal("relation \"stories\" does not exist"));
+        assert_eq!(status.code(), Code::Internal);
+        assert!(!status.message().contains("stories"));
+        let details = status.get_error_details();
+        let metadata = &details.error_info().unwrap().metadata;
+        assert!(status.message().contains(&metadata["correlation_id"]));
+    }
+}
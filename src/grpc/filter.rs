@@ -0,0 +1,387 @@
+use crate::{
+    Error, Result,
+    domain::{Comparator, StoryExpr, StorySort, StorySortField, TextMatch},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Maximum filter length, in characters.
+const FILTER_MAX_LEN: usize = 1024;
+
+/// Maximum nesting of parentheses and negations in a filter.
+const FILTER_MAX_DEPTH: usize = 16;
+
+/// A lexical token in a filter string.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Minus,
+    Has,
+    Compare(Comparator),
+    Word(String),
+    Quoted(String),
+}
+
+/// Parses an AIP-160 style story filter into an expression, or `None` when blank.
+///
+/// Supports `AND`, `OR` (binding tighter than `AND`), `NOT` or `-` negation, parentheses,
+/// and juxtaposed terms as an implicit `AND`. Fields are `name` with `:`, `=` and `!=`, and
+/// `created_at` or `updated_at` with `=`, `!=`, `<`, `<=`, `>` and `>=` against an RFC 3339
+/// timestamp or a `YYYY-MM-DD` date.
+pub(crate) fn parse_story_filter(input: &str) -> Result<Option<StoryExpr>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let len = input.chars().count();
+    if len > FILTER_MAX_LEN {
+        return Err(filter_error(format!(
+            "too long ({len} > {FILTER_MAX_LEN} characters)"
+        )));
+    }
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.expression()?;
+    match parser.peek() {
+        None => Ok(Some(expr)),
+        Some(token) => Err(filter_error(format!("unexpected {}", describe(token)))),
+    }
+}
+
+/// Parses an AIP-132 style story order_by: one of `name`, `created_at` or `updated_at`,
+/// optionally followed by `asc` or `desc`. Blank means the default creation order.
+pub(crate) fn parse_story_order_by(input: &str) -> Result<StorySort> {
+    let invalid = |message: &str| Error::invalid_args("order_by", message);
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(StorySort::default());
+    }
+    if input.contains(',') {
+        return Err(invalid("only one sort field is supported"));
+    }
+    let mut words = input.split_whitespace();
+    let field = match words.next() {
+        Some("name") => StorySortField::Name,
+        Some("created_at") => StorySortField::CreatedAt,
+        Some("updated_at") => StorySortField::UpdatedAt,
+        _ => return Err(invalid("expected name, created_at or updated_at")),
+    };
+    let descending = match words.next() {
+        None | Some("asc") => false,
+        Some("desc") => true,
+        Some(_) => return Err(invalid("direction must be asc or desc")),
+    };
+    if words.next().is_some() {
+        return Err(invalid("expected a field and an optional direction"));
+    }
+    Ok(StorySort { field, descending })
+}
+
+/// An invalid filter argument error.
+fn filter_error(message: impl Into<String>) -> Error {
+    Error::invalid_args("filter", message)
+}
+
+/// Describe a token for error messages.
+fn describe(token: &Token) -> String {
+    match token {
+        Token::LParen => "\"(\"".into(),
+        Token::RParen => "\")\"".into(),
+        Token::Minus => "\"-\"".into(),
+        Token::Has => "\":\"".into(),
+        Token::Compare(_) => "comparison".into(),
+        Token::Word(word) => format!("\"{word}\""),
+        Token::Quoted(text) => format!("string \"{text}\""),
+    }
+}
+
+/// Split a filter into tokens. Words end at whitespace, parentheses, operators and quotes, except
+/// that a value after an operator keeps its colons, so timestamps need not be quoted.
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '-' => Token::Minus,
+            ':' => Token::Has,
+            '=' => Token::Compare(Comparator::Eq),
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Compare(Comparator::Ne),
+            '!' => return Err(filter_error("expected \"=\" after \"!\"")),
+            '<' if chars.next_if_eq(&'=').is_some() => Token::Compare(Comparator::Le),
+            '<' => Token::Compare(Comparator::Lt),
+            '>' if chars.next_if_eq(&'=').is_some() => Token::Compare(Comparator::Ge),
+            '>' => Token::Compare(Comparator::Gt),
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => text.push(escaped),
+                            None => return Err(filter_error("unterminated string")),
+                        },
+                        Some(c) => text.push(c),
+                        None => return Err(filter_error("unterminated string")),
+                    }
+                }
+                Token::Quoted(text)
+            }
+            c => {
+                let value = matches!(tokens.last(), Some(Token::Has | Token::Compare(_)));
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| !is_word_end(*c, value)) {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Whether a character ends a bare word. A colon only ends a field name, not a value.
+fn is_word_end(c: char, value: bool) -> bool {
+    c.is_whitespace()
+        || matches!(c, '(' | ')' | '=' | '!' | '<' | '>' | '"')
+        || (c == ':' && !value)
+}
+
+/// A recursive descent parser over filter tokens.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    /// The next token, if any.
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Take the next token, if any.
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Take the next token if it is the given keyword.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Word(word)) if word == keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Whether the next token can start another term of an implicit AND sequence.
+    fn starts_term(&self) -> bool {
+        match self.peek() {
+            Some(Token::LParen | Token::Minus) => true,
+            Some(Token::Word(word)) => word != "AND" && word != "OR",
+            _ => false,
+        }
+    }
+
+    /// Enter a nested expression, guarding against unbounded recursion.
+    fn nest(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.depth > FILTER_MAX_DEPTH {
+            return Err(filter_error(format!(
+                "nested too deeply (more than {FILTER_MAX_DEPTH} levels)"
+            )));
+        }
+        Ok(())
+    }
+
+    /// expression = sequence { "AND" sequence }
+    fn expression(&mut self) -> Result<StoryExpr> {
+        let mut expr = self.sequence()?;
+        while self.keyword("AND") {
+            let right = self.sequence()?;
+            expr = StoryExpr::And(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    /// sequence = factor { factor }
+    fn sequence(&mut self) -> Result<StoryExpr> {
+        let mut expr = self.factor()?;
+        while self.starts_term() {
+            let right = self.factor()?;
+            expr = StoryExpr::And(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    /// factor = term { "OR" term }
+    fn factor(&mut self) -> Result<StoryExpr> {
+        let mut expr = self.term()?;
+        while self.keyword("OR") {
+            let right = self.term()?;
+            expr = StoryExpr::Or(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    /// term = [ "NOT" | "-" ] simple
+    fn term(&mut self) -> Result<StoryExpr> {
+        if self.keyword("NOT") || self.peek() == Some(&Token::Minus) {
+            if self.peek() == Some(&Token::Minus) {
+                self.pos += 1;
+            }
+            self.nest()?;
+            let expr = self.simple()?;
+            self.depth -= 1;
+            return Ok(StoryExpr::Not(Box::new(expr)));
+        }
+        self.simple()
+    }
+
+    /// simple = "(" expression ")" | comparison
+    fn simple(&mut self) -> Result<StoryExpr> {
+        if self.peek() != Some(&Token::LParen) {
+            return self.comparison();
+        }
+        self.pos += 1;
+        self.nest()?;
+        let expr = self.expression()?;
+        match self.next() {
+            Some(Token::RParen) => {
+                self.depth -= 1;
+                Ok(expr)
+            }
+            _ => Err(filter_error("missing \")\"")),
+        }
+    }
+
+    /// comparison = field operator value
+    fn comparison(&mut self) -> Result<StoryExpr> {
+        let field = match self.next() {
+            Some(Token::Word(field)) => field,
+            Some(token) => return Err(filter_error(format!("unexpected {}", describe(&token)))),
+            None => return Err(filter_error("unexpected end of filter")),
+        };
+        let op = match self.next() {
+            Some(token @ (Token::Has | Token::Compare(_))) => token,
+            _ => return Err(filter_error(format!("expected an operator after {field}"))),
+        };
+        let value = match self.next() {
+            Some(Token::Word(value) | Token::Quoted(value)) => value,
+            _ => return Err(filter_error(format!("expected a value for {field}"))),
+        };
+        match (field.as_str(), op) {
+            ("name", Token::Has) => Ok(StoryExpr::Name(TextMatch::Has(value))),
+            ("name", Token::Compare(Comparator::Eq)) => {
+                Ok(StoryExpr::Name(TextMatch::Equals(value)))
+            }
+            ("name", Token::Compare(Comparator::Ne)) => {
+                Ok(StoryExpr::Name(TextMatch::NotEquals(value)))
+            }
+            ("name", _) => Err(filter_error("name supports \":\", \"=\" and \"!=\"")),
+            ("created_at", Token::Compare(op)) => Ok(StoryExpr::CreatedAt(op, parse_time(&value)?)),
+            ("updated_at", Token::Compare(op)) => Ok(StoryExpr::UpdatedAt(op, parse_time(&value)?)),
+            ("created_at" | "updated_at", _) => Err(filter_error(format!(
+                "{field} supports comparisons, not \":\""
+            ))),
+            _ => Err(filter_error(format!(
+                "unknown field \"{field}\"; expected name, created_at or updated_at"
+            ))),
+        }
+    }
+}
+
+/// Parse an RFC 3339 timestamp, or a date as midnight UTC.
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|ts| ts.and_utc())
+        .ok_or_else(|| filter_error(format!("invalid timestamp \"{value}\"")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_has(value: &str) -> Box<StoryExpr> {
+        Box::new(StoryExpr::Name(TextMatch::Has(value.into())))
+    }
+
+    #[test]
+    fn parse_filter_example() {
+        let expr = parse_story_filter(r#"name:"release*" AND created_at > "2026-01-01""#)
+            .unwrap()
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let since = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let created = Box::new(StoryExpr::CreatedAt(Comparator::Gt, since));
+        assert_eq!(expr, StoryExpr::And(name_has("release*"), created));
+    }
+
+    #[test]
+    fn parse_filter_unquoted_timestamp() {
+        let expr = parse_story_filter("updated_at>=2026-01-01T09:30:00+02:00 name:a:b")
+            .unwrap()
+            .unwrap();
+        let since = DateTime::parse_from_rfc3339("2026-01-01T07:30:00Z").unwrap();
+        let updated = Box::new(StoryExpr::UpdatedAt(Comparator::Ge, since.into()));
+        assert_eq!(expr, StoryExpr::And(updated, name_has("a:b")));
+        assert!(parse_story_filter("name:a updated_at:2026-01-01T09:30:00Z").is_err());
+    }
+
+    #[test]
+    fn parse_filter_precedence() {
+        // OR binds tighter than AND, and juxtaposition is an implicit AND
+        let expr = parse_story_filter("name:a name:b OR -name:c")
+            .unwrap()
+            .unwrap();
+        let not_c = Box::new(StoryExpr::Not(name_has("c")));
+        let b_or_not_c = Box::new(StoryExpr::Or(name_has("b"), not_c));
+        assert_eq!(expr, StoryExpr::And(name_has("a"), b_or_not_c));
+
+        let expr = parse_story_filter("NOT (name:a AND name:b)")
+            .unwrap()
+            .unwrap();
+        let a_and_b = Box::new(StoryExpr::And(name_has("a"), name_has("b")));
+        assert_eq!(expr, StoryExpr::Not(a_and_b));
+    }
+
+    #[test]
+    fn parse_filter_errors() {
+        assert!(parse_story_filter("  ").unwrap().is_none());
+        assert!(parse_story_filter("seqno = 1").is_err());
+        assert!(parse_story_filter("name < b").is_err());
+        assert!(parse_story_filter("created_at:2026").is_err());
+        assert!(parse_story_filter("created_at > yesterday").is_err());
+        assert!(parse_story_filter("(name:a").is_err());
+        assert!(parse_story_filter("name:\"a").is_err());
+        assert!(parse_story_filter("name:a)").is_err());
+        assert!(
+            parse_story_filter(&format!("{}name:a{}", "(".repeat(17), ")".repeat(17))).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_order_by() {
+        assert_eq!(parse_story_order_by("").unwrap(), StorySort::default());
+        let sort = parse_story_order_by(" created_at desc ").unwrap();
+        assert_eq!(sort.field, StorySortField::CreatedAt);
+        assert!(sort.descending);
+        assert!(!parse_story_order_by("name").unwrap().descending);
+        assert!(parse_story_order_by("name, created_at").is_err());
+        assert!(parse_story_order_by("name down").is_err());
+        assert!(parse_story_order_by("priority").is_err());
+    }
+}
@@ -1,44 +1,146 @@
 use crate::{
-    domain::{Page, Status},
-    effect::{StoryEffects, TaskEffects},
+    config::Limits,
+    domain::{
+        ImportSummary, KeysetPage, LabelTarget, NewStory, NewTask, Page, SearchQuery, Status,
+        StoryChanges, StoryFilter, TaskChanges, TaskFilter, TaskLayout, TaskNode, TaskOrder,
+        TaskSelection, View,
+    },
+    effect::{
+        CommentEffects, LabelEffects, SearchEffects, StoryEffects, TaskEffects, TemplateEffects,
+    },
     proto::gsdx_service_server::GsdxService,
     proto::{
+        AddCommentRequest, AddCommentResponse, AddDependencyRequest, AddDependencyResponse,
+        AddLabelsRequest, AddLabelsResponse, AssignTaskRequest, AssignTaskResponse,
+        BatchCreateTasksRequest, BatchCreateTasksResponse, BatchUpdateTaskStatusRequest,
+        BatchUpdateTaskStatusResponse, CommentData, CreateLabelRequest, CreateLabelResponse,
         CreateStoryRequest, CreateStoryResponse, CreateTaskRequest, CreateTaskResponse,
-        DeleteStoryRequest, DeleteStoryResponse, DeleteTaskRequest, DeleteTaskResponse,
-        ListStoriesRequest, ListStoriesResponse, ListTasksRequest, ListTasksResponse, StoryData,
-        TaskData, TaskStatus, UpdateStoryRequest, UpdateStoryResponse, UpdateTaskRequest,
-        UpdateTaskResponse,
+        CreateTemplateRequest, CreateTemplateResponse, DeleteCommentRequest, DeleteCommentResponse,
+        DeleteLabelRequest, DeleteLabelResponse, DeleteStoryRequest, DeleteStoryResponse,
+        DeleteTaskRequest, DeleteTaskResponse, DuplicateStoryRequest, DuplicateStoryResponse,
+        EditCommentRequest, EditCommentResponse, ExportStoriesRequest, ExportStoriesResponse,
+        GetStoryRequest, GetStoryResponse, ImportStoriesRequest, ImportStoriesResponse,
+        InstantiateTemplateRequest, InstantiateTemplateResponse, LabelData, ListCommentsRequest,
+        ListCommentsResponse, ListLabelsRequest, ListLabelsResponse, ListOverdueTasksRequest,
+        ListOverdueTasksResponse, ListStoriesRequest, ListStoriesResponse,
+        ListTasksByAssigneeRequest, ListTasksByAssigneeResponse, ListTasksRequest,
+        ListTasksResponse, ListTemplatesRequest, ListTemplatesResponse, RemoveDependencyRequest,
+        RemoveDependencyResponse, RemoveLabelsRequest, RemoveLabelsResponse, ReorderTaskRequest,
+        ReorderTaskResponse, SearchHit, SearchRequest, SearchResponse, StoryData, TaskData,
+        TaskLayout as ProtoTaskLayout, TaskResult as ProtoTaskResult, TaskSort, TaskStatus,
+        TemplateData, UnassignTaskRequest, UnassignTaskResponse, UpdateStoryRequest,
+        UpdateStoryResponse, UpdateTaskRequest, UpdateTaskResponse, View as ProtoView,
+        add_labels_request, batch_update_task_status_request, remove_labels_request,
     },
 };
-use tonic::{Request, Response, Status as GrpcStatus};
+use chrono::Utc;
+use futures_util::{StreamExt, TryStreamExt, stream::BoxStream};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status as GrpcStatus, Streaming};
 
 // Conversions between grpc and domain types.
 mod adapter;
 
+// Parsing of AIP-160 filter and order_by strings.
+mod filter;
+use filter::{parse_story_filter, parse_story_order_by};
+
 // Stateless validation utility functions.
-mod validate;
+pub(crate) mod validate;
 use validate::{
-    clamp_page_bounds, validate_name, validate_optional_name, validate_story_id, validate_task_id,
+    ValidateAll, clamp_limit, clamp_page_bounds, validate_assignee, validate_author,
+    validate_batch_task_ids, validate_batch_tasks, validate_blocker_task_id, validate_comment_body,
+    validate_comment_id, validate_description, validate_due_at_change, validate_duration,
+    validate_import_story, validate_label_id, validate_label_ids, validate_label_target,
+    validate_name, validate_new_template, validate_optional_description, validate_optional_name,
+    validate_optional_story_id, validate_page_token, validate_parent_change,
+    validate_parent_task_id, validate_placement, validate_priority, validate_recurrence,
+    validate_recurrence_change, validate_required_status, validate_search_query,
+    validate_status_filter, validate_statuses, validate_story_id, validate_story_page_token,
+    validate_story_task_filter, validate_task_id, validate_task_selection, validate_template_id,
+    validate_time_range, validate_timestamp, with_field_prefix,
 };
 
 /// GSDX gRPC implementation.
-pub struct Gsdx<S, T> {
-    stories: S,
+pub struct Gsdx<S, T, L, C, F, P> {
+    stories: Arc<S>,
     tasks: T,
+    labels: L,
+    comments: C,
+    search: F,
+    templates: P,
+    limits: Limits,
 }
 
-impl<S: StoryEffects, T: TaskEffects> Gsdx<S, T> {
+impl<S, T, L, C, F, P> Gsdx<S, T, L, C, F, P>
+where
+    S: StoryEffects,
+    T: TaskEffects,
+    L: LabelEffects,
+    C: CommentEffects,
+    F: SearchEffects,
+    P: TemplateEffects,
+{
     /// Constructor
-    pub fn new(stories: S, tasks: T) -> Self {
-        Self { stories, tasks }
+    pub fn new(
+        stories: S,
+        tasks: T,
+        labels: L,
+        comments: C,
+        search: F,
+        templates: P,
+        limits: Limits,
+    ) -> Self {
+        Self {
+            stories: Arc::new(stories),
+            tasks,
+            labels,
+            comments,
+            search,
+            templates,
+            limits,
+        }
+    }
+}
+
+/// How many imported stories to commit in each transaction.
+const IMPORT_BATCH_SIZE: usize = 100;
+
+/// How many progress responses to buffer for a slow client before pausing an import.
+const IMPORT_PROGRESS_BUFFER: usize = 4;
+
+/// Running totals for a story import.
+#[derive(Default)]
+struct ImportProgress {
+    stories: u64,
+    tasks: u64,
+}
+
+impl ImportProgress {
+    /// Add a committed batch to the totals, returning the progress response for it.
+    fn record(&mut self, summary: ImportSummary) -> ImportStoriesResponse {
+        self.stories += summary.story_ids.len() as u64;
+        self.tasks += summary.tasks_imported;
+        ImportStoriesResponse {
+            story_ids: summary.story_ids.iter().map(ToString::to_string).collect(),
+            tasks_imported: summary.tasks_imported,
+            total_stories: self.stories,
+            total_tasks: self.tasks,
+        }
     }
 }
 
 #[tonic::async_trait]
-impl<S, T> GsdxService for Gsdx<S, T>
+impl<S, T, L, C, F, P> GsdxService for Gsdx<S, T, L, C, F, P>
 where
     S: StoryEffects + 'static,
     T: TaskEffects + 'static,
+    L: LabelEffects + 'static,
+    C: CommentEffects + 'static,
+    F: SearchEffects + 'static,
+    P: TemplateEffects + 'static,
 {
     /// Create a new story.
     async fn create_story(
@@ -47,8 +149,12 @@ where
     ) -> Result<Response<CreateStoryResponse>, GrpcStatus> {
         log::debug!("Create story");
         let request = request.into_inner();
-        let name = validate_name(request.name)?;
-        let story = self.stories.create(name).await?;
+        let (name, description) = (
+            validate_name(request.name, self.limits.story_name_max_len),
+            validate_description(request.description, self.limits.description_max_bytes),
+        )
+            .validate_all()?;
+        let story = self.stories.create(NewStory { name, description }).await?;
         Ok(Response::new(CreateStoryResponse {
             story: Some(StoryData::from(story)),
         }))
@@ -73,11 +179,42 @@ where
     ) -> Result<Response<ListStoriesResponse>, GrpcStatus> {
         log::debug!("List stories");
         let request = request.get_ref();
-        let page_params = clamp_page_bounds(request.cursor, request.limit);
-        let Page(next_cursor, stories) = self.stories.list(page_params).await?;
+        let (label_ids, expr, sort) = (
+            validate_label_ids(&request.label_ids),
+            parse_story_filter(&request.filter),
+            parse_story_order_by(&request.order_by),
+        )
+            .validate_all()?;
+        let after = validate_story_page_token(&request.page_token, &sort)?;
+        let page_params = clamp_page_bounds(request.cursor, request.limit, &self.limits);
+        let filter = StoryFilter { label_ids, expr };
+        let view = View::from(ProtoView::try_from(request.view).unwrap_or(ProtoView::Unspecified));
+        let page = self
+            .stories
+            .list(page_params, filter, sort, after, view, request.with_stats)
+            .await?;
         Ok(Response::new(ListStoriesResponse {
-            next_cursor,
-            stories: stories.into_iter().map(StoryData::from).collect(),
+            next_cursor: page.next_cursor,
+            stories: page.stories.into_iter().map(StoryData::from).collect(),
+            next_page_token: page
+                .next_keyset
+                .map(|keyset| keyset.to_string())
+                .unwrap_or_default(),
+        }))
+    }
+
+    /// Get a single story.
+    async fn get_story(
+        &self,
+        request: Request<GetStoryRequest>,
+    ) -> Result<Response<GetStoryResponse>, GrpcStatus> {
+        log::debug!("Get story");
+        let request = request.get_ref();
+        let story_id = validate_story_id(&request.story_id)?;
+        let view = View::from(ProtoView::try_from(request.view).unwrap_or(ProtoView::Unspecified));
+        let story = self.stories.get(story_id, view, request.with_stats).await?;
+        Ok(Response::new(GetStoryResponse {
+            story: Some(StoryData::from(story)),
         }))
     }
 
@@ -87,15 +224,117 @@ where
         request: Request<UpdateStoryRequest>,
     ) -> Result<Response<UpdateStoryResponse>, GrpcStatus> {
         log::debug!("Update story");
-        let request = request.get_ref();
-        let story_id = validate_story_id(&request.story_id)?;
-        let name = validate_name(&request.name)?;
-        let story = self.stories.update(story_id, name).await?;
+        let request = request.into_inner();
+        let (story_id, name, description) = (
+            validate_story_id(&request.story_id),
+            validate_name(request.name, self.limits.story_name_max_len),
+            validate_optional_description(request.description, self.limits.description_max_bytes),
+        )
+            .validate_all()?;
+        let changes = StoryChanges { name, description };
+        let story = self.stories.update(story_id, changes).await?;
         Ok(Response::new(UpdateStoryResponse {
             story: Some(StoryData::from(story)),
         }))
     }
 
+    /// Copy a story with all of its tasks under a new name.
+    async fn duplicate_story(
+        &self,
+        request: Request<DuplicateStoryRequest>,
+    ) -> Result<Response<DuplicateStoryResponse>, GrpcStatus> {
+        log::debug!("Duplicate story");
+        let request = request.into_inner();
+        let (story_id, name) = (
+            validate_story_id(&request.story_id),
+            validate_name(request.name, self.limits.story_name_max_len),
+        )
+            .validate_all()?;
+        let story = self
+            .stories
+            .duplicate(story_id, name, request.reset_statuses)
+            .await?;
+        Ok(Response::new(DuplicateStoryResponse {
+            story: Some(StoryData::from(story)),
+        }))
+    }
+
+    type ImportStoriesStream = ReceiverStream<Result<ImportStoriesResponse, GrpcStatus>>;
+
+    /// Import a stream of stories with their tasks, committing in batches and reporting progress
+    /// after each one.
+    async fn import_stories(
+        &self,
+        request: Request<Streaming<ImportStoriesRequest>>,
+    ) -> Result<Response<Self::ImportStoriesStream>, GrpcStatus> {
+        log::debug!("Import stories");
+        let mut input = request.into_inner();
+        let stories = Arc::clone(&self.stories);
+        let limits = self.limits;
+        let (tx, rx) = mpsc::channel(IMPORT_PROGRESS_BUFFER);
+        tokio::spawn(async move {
+            let mut progress = ImportProgress::default();
+            let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+            let mut index = 0;
+            loop {
+                let done = match input.message().await {
+                    Ok(Some(request)) => {
+                        let prefix = format!("stories[{index}]");
+                        match with_field_prefix(&prefix, validate_import_story(request, &limits)) {
+                            Ok(story) => batch.push(story),
+                            Err(err) => {
+                                let _ = tx.send(Err(GrpcStatus::from(err))).await;
+                                return;
+                            }
+                        }
+                        index += 1;
+                        false
+                    }
+                    Ok(None) => true,
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    }
+                };
+                if batch.len() == IMPORT_BATCH_SIZE || (done && !batch.is_empty()) {
+                    let result = stories.import(std::mem::take(&mut batch)).await;
+                    let response = result
+                        .map(|summary| progress.record(summary))
+                        .map_err(GrpcStatus::from);
+                    let failed = response.is_err();
+                    if tx.send(response).await.is_err() || failed {
+                        return;
+                    }
+                }
+                if done {
+                    if index == 0 {
+                        let _ = tx.send(Ok(progress.record(Default::default()))).await;
+                    }
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    type ExportStoriesStream = BoxStream<'static, Result<ExportStoriesResponse, GrpcStatus>>;
+
+    /// Stream all stories, or just one, with their tasks.
+    async fn export_stories(
+        &self,
+        request: Request<ExportStoriesRequest>,
+    ) -> Result<Response<Self::ExportStoriesStream>, GrpcStatus> {
+        log::debug!("Export stories");
+        let request = request.get_ref();
+        let story_id = validate_optional_story_id(request.story_id.as_deref())?;
+        let exports = self.stories.export(story_id).await?;
+        let responses = exports
+            .map_ok(ExportStoriesResponse::from)
+            .map_err(GrpcStatus::from)
+            .boxed();
+        Ok(Response::new(responses))
+    }
+
     /// List all tasks for a story.
     async fn list_tasks(
         &self,
@@ -103,11 +342,36 @@ where
     ) -> Result<Response<ListTasksResponse>, GrpcStatus> {
         log::debug!("List tasks");
         let request = request.get_ref();
-        let story_id = validate_story_id(&request.story_id)?;
-        let tasks = self.tasks.list(story_id).await?;
-        Ok(Response::new(ListTasksResponse {
-            tasks: tasks.into_iter().map(TaskData::from).collect(),
-        }))
+        let (story_id, label_ids, statuses, created, updated) = (
+            validate_story_id(&request.story_id),
+            validate_label_ids(&request.label_ids),
+            validate_statuses(&request.statuses),
+            validate_time_range("created", request.created_after, request.created_before),
+            validate_time_range("updated", request.updated_after, request.updated_before),
+        )
+            .validate_all()?;
+        let sort = TaskSort::try_from(request.sort).unwrap_or(TaskSort::Unspecified);
+        let filter = TaskFilter {
+            label_ids,
+            statuses,
+            created,
+            updated,
+        };
+        let view = View::from(ProtoView::try_from(request.view).unwrap_or(ProtoView::Unspecified));
+        let layout =
+            ProtoTaskLayout::try_from(request.layout).unwrap_or(ProtoTaskLayout::Unspecified);
+        let tasks = self
+            .tasks
+            .list(story_id, TaskOrder::from(sort), filter, view)
+            .await?;
+        let tasks = match TaskLayout::from(layout) {
+            TaskLayout::Flat => tasks.into_iter().map(TaskData::from).collect(),
+            TaskLayout::Tree => TaskNode::build(tasks)
+                .into_iter()
+                .map(TaskData::from)
+                .collect(),
+        };
+        Ok(Response::new(ListTasksResponse { tasks }))
     }
 
     /// Create a new task.
@@ -116,17 +380,52 @@ where
         request: Request<CreateTaskRequest>,
     ) -> Result<Response<CreateTaskResponse>, GrpcStatus> {
         log::debug!("Create task");
-        let request = request.get_ref();
-        let story_id = validate_story_id(&request.story_id)?;
-        let name = validate_name(&request.name)?;
+        let request = request.into_inner();
+        let (story_id, parent_id, name, description, due_at, priority, recurrence) = (
+            validate_story_id(&request.story_id),
+            validate_parent_task_id(request.parent_task_id),
+            validate_name(request.name, self.limits.task_name_max_len),
+            validate_description(request.description, self.limits.description_max_bytes),
+            validate_timestamp("due_at", request.due_at),
+            validate_priority(request.priority),
+            validate_recurrence(request.recurrence),
+        )
+            .validate_all()?;
         let task_status = TaskStatus::try_from(request.status).unwrap_or(TaskStatus::Unspecified);
         let status = Status::from(task_status);
-        let task = self.tasks.create(story_id, name, status).await?;
+        let new_task = NewTask {
+            parent_id,
+            name,
+            description,
+            status,
+            priority: priority.unwrap_or_default(),
+            due_at,
+            recurrence,
+        };
+        let task = self.tasks.create(story_id, new_task).await?;
         Ok(Response::new(CreateTaskResponse {
             task: Some(TaskData::from(task)),
         }))
     }
 
+    /// Create several tasks in a story at once.
+    async fn batch_create_tasks(
+        &self,
+        request: Request<BatchCreateTasksRequest>,
+    ) -> Result<Response<BatchCreateTasksResponse>, GrpcStatus> {
+        log::debug!("Batch create tasks");
+        let request = request.into_inner();
+        let (story_id, tasks) = (
+            validate_story_id(&request.story_id),
+            validate_batch_tasks(request.tasks, &self.limits),
+        )
+            .validate_all()?;
+        let tasks = self.tasks.batch_create(story_id, tasks).await?;
+        Ok(Response::new(BatchCreateTasksResponse {
+            tasks: tasks.into_iter().map(TaskData::from).collect(),
+        }))
+    }
+
     /// Delete an existing task.
     async fn delete_task(
         &self,
@@ -146,13 +445,410 @@ where
     ) -> Result<Response<UpdateTaskResponse>, GrpcStatus> {
         log::debug!("Update task");
         let request = request.into_inner();
-        let task_id = validate_task_id(&request.task_id)?;
-        let maybe_name = validate_optional_name(request.name)?;
+        let (task_id, parent_id, name, description, due_at, priority, recurrence) = (
+            validate_task_id(&request.task_id),
+            validate_parent_change(request.parent_task_id, request.clear_parent_task_id),
+            validate_optional_name(request.name, self.limits.task_name_max_len),
+            validate_optional_description(request.description, self.limits.description_max_bytes),
+            validate_due_at_change(request.due_at, request.clear_due_at),
+            validate_priority(request.priority),
+            validate_recurrence_change(request.recurrence, request.clear_recurrence),
+        )
+            .validate_all()?;
         let task_status = TaskStatus::try_from(request.status).unwrap_or(TaskStatus::Unspecified);
         let status = Status::from(task_status);
-        let task = self.tasks.update(task_id, maybe_name, status).await?;
+        let changes = TaskChanges {
+            parent_id,
+            name,
+            description,
+            status,
+            priority,
+            due_at,
+            recurrence,
+            force: request.force,
+        };
+        let (task, next) = self.tasks.update(task_id, changes).await?;
         Ok(Response::new(UpdateTaskResponse {
             task: Some(TaskData::from(task)),
+            next_occurrence: next.map(TaskData::from),
+        }))
+    }
+
+    /// Get a page of overdue tasks across all stories.
+    async fn list_overdue_tasks(
+        &self,
+        request: Request<ListOverdueTasksRequest>,
+    ) -> Result<Response<ListOverdueTasksResponse>, GrpcStatus> {
+        log::debug!("List overdue tasks");
+        let request = request.into_inner();
+        let (after, due_within) = (
+            validate_page_token(&request.page_token),
+            validate_duration("due_within", request.due_within),
+        )
+            .validate_all()?;
+        let limit = clamp_limit(request.limit, &self.limits);
+        let due_before = Utc::now() + due_within;
+        let KeysetPage(next, tasks) = self.tasks.list_overdue(due_before, after, limit).await?;
+        Ok(Response::new(ListOverdueTasksResponse {
+            next_page_token: next.map(|keyset| keyset.to_string()).unwrap_or_default(),
+            tasks: tasks.into_iter().map(TaskData::from).collect(),
+        }))
+    }
+
+    /// Set the status of many tasks at once.
+    async fn batch_update_task_status(
+        &self,
+        request: Request<BatchUpdateTaskStatusRequest>,
+    ) -> Result<Response<BatchUpdateTaskStatusResponse>, GrpcStatus> {
+        log::debug!("Batch update task status");
+        let request = request.into_inner();
+        let selection = request.selector.map(|selector| match selector {
+            batch_update_task_status_request::Selector::TaskIds(list) => {
+                validate_batch_task_ids(&list.ids, &self.limits).map(TaskSelection::Ids)
+            }
+            batch_update_task_status_request::Selector::StoryFilter(filter) => {
+                validate_story_task_filter(filter)
+                    .map(|(story_id, filter)| TaskSelection::Story(story_id, filter))
+            }
+        });
+        let (selection, status) = (
+            validate_task_selection(selection),
+            validate_required_status(request.status),
+        )
+            .validate_all()?;
+        let results = self
+            .tasks
+            .batch_update_status(selection, status, request.force)
+            .await?;
+        Ok(Response::new(BatchUpdateTaskStatusResponse {
+            results: results.into_iter().map(ProtoTaskResult::from).collect(),
+        }))
+    }
+
+    /// Move a task before or after a sibling task.
+    async fn reorder_task(
+        &self,
+        request: Request<ReorderTaskRequest>,
+    ) -> Result<Response<ReorderTaskResponse>, GrpcStatus> {
+        log::debug!("Reorder task");
+        let request = request.into_inner();
+        let (task_id, placement) = (
+            validate_task_id(&request.task_id),
+            validate_placement(request.anchor),
+        )
+            .validate_all()?;
+        let task = self.tasks.reorder(task_id, placement).await?;
+        Ok(Response::new(ReorderTaskResponse {
+            task: Some(TaskData::from(task)),
+        }))
+    }
+
+    /// Make a task wait on a blocker task.
+    async fn add_dependency(
+        &self,
+        request: Request<AddDependencyRequest>,
+    ) -> Result<Response<AddDependencyResponse>, GrpcStatus> {
+        log::debug!("Add dependency");
+        let request = request.get_ref();
+        let (task_id, blocker_id) = (
+            validate_task_id(&request.task_id),
+            validate_blocker_task_id(&request.blocker_task_id),
+        )
+            .validate_all()?;
+        let task = self.tasks.add_dependency(task_id, blocker_id).await?;
+        Ok(Response::new(AddDependencyResponse {
+            task: Some(TaskData::from(task)),
+        }))
+    }
+
+    /// Stop a task waiting on a blocker task.
+    async fn remove_dependency(
+        &self,
+        request: Request<RemoveDependencyRequest>,
+    ) -> Result<Response<RemoveDependencyResponse>, GrpcStatus> {
+        log::debug!("Remove dependency");
+        let request = request.get_ref();
+        let (task_id, blocker_id) = (
+            validate_task_id(&request.task_id),
+            validate_blocker_task_id(&request.blocker_task_id),
+        )
+            .validate_all()?;
+        let task = self.tasks.remove_dependency(task_id, blocker_id).await?;
+        Ok(Response::new(RemoveDependencyResponse {
+            task: Some(TaskData::from(task)),
+        }))
+    }
+
+    /// Set the user working on a task.
+    async fn assign_task(
+        &self,
+        request: Request<AssignTaskRequest>,
+    ) -> Result<Response<AssignTaskResponse>, GrpcStatus> {
+        log::debug!("Assign task");
+        let request = request.get_ref();
+        let (task_id, assignee) = (
+            validate_task_id(&request.task_id),
+            validate_assignee(&request.assignee),
+        )
+            .validate_all()?;
+        let task = self.tasks.assign(task_id, Some(assignee)).await?;
+        Ok(Response::new(AssignTaskResponse {
+            task: Some(TaskData::from(task)),
+        }))
+    }
+
+    /// Clear the user working on a task.
+    async fn unassign_task(
+        &self,
+        request: Request<UnassignTaskRequest>,
+    ) -> Result<Response<UnassignTaskResponse>, GrpcStatus> {
+        log::debug!("Unassign task");
+        let task_id = validate_task_id(&request.get_ref().task_id)?;
+        let task = self.tasks.assign(task_id, None).await?;
+        Ok(Response::new(UnassignTaskResponse {
+            task: Some(TaskData::from(task)),
+        }))
+    }
+
+    /// Get a page of tasks assigned to a user across all stories.
+    async fn list_tasks_by_assignee(
+        &self,
+        request: Request<ListTasksByAssigneeRequest>,
+    ) -> Result<Response<ListTasksByAssigneeResponse>, GrpcStatus> {
+        log::debug!("List tasks by assignee");
+        let request = request.get_ref();
+        let (assignee, status, after) = (
+            validate_assignee(&request.assignee),
+            validate_status_filter(request.status),
+            validate_page_token(&request.page_token),
+        )
+            .validate_all()?;
+        let limit = clamp_limit(request.limit, &self.limits);
+        let KeysetPage(next, tasks) = self
+            .tasks
+            .list_by_assignee(assignee, status, after, limit)
+            .await?;
+        Ok(Response::new(ListTasksByAssigneeResponse {
+            next_page_token: next.map(|keyset| keyset.to_string()).unwrap_or_default(),
+            tasks: tasks.into_iter().map(TaskData::from).collect(),
+        }))
+    }
+
+    /// List all labels.
+    async fn list_labels(
+        &self,
+        _request: Request<ListLabelsRequest>,
+    ) -> Result<Response<ListLabelsResponse>, GrpcStatus> {
+        log::debug!("List labels");
+        let labels = self.labels.list().await?;
+        Ok(Response::new(ListLabelsResponse {
+            labels: labels.into_iter().map(LabelData::from).collect(),
+        }))
+    }
+
+    /// Create a new label.
+    async fn create_label(
+        &self,
+        request: Request<CreateLabelRequest>,
+    ) -> Result<Response<CreateLabelResponse>, GrpcStatus> {
+        log::debug!("Create label");
+        let request = request.into_inner();
+        let name = validate_name(request.name, self.limits.label_name_max_len)?;
+        let label = self.labels.create(name).await?;
+        Ok(Response::new(CreateLabelResponse {
+            label: Some(LabelData::from(label)),
+        }))
+    }
+
+    /// Delete an existing label.
+    async fn delete_label(
+        &self,
+        request: Request<DeleteLabelRequest>,
+    ) -> Result<Response<DeleteLabelResponse>, GrpcStatus> {
+        log::debug!("Delete label");
+        let request = request.get_ref();
+        let label_id = validate_label_id(&request.label_id)?;
+        self.labels.delete(label_id).await?;
+        Ok(Response::new(DeleteLabelResponse {}))
+    }
+
+    /// Attach labels to a story or task.
+    async fn add_labels(
+        &self,
+        request: Request<AddLabelsRequest>,
+    ) -> Result<Response<AddLabelsResponse>, GrpcStatus> {
+        log::debug!("Add labels");
+        let request = request.into_inner();
+        let target = request.target.map(|target| match target {
+            add_labels_request::Target::StoryId(id) => {
+                validate_story_id(&id).map(LabelTarget::Story)
+            }
+            add_labels_request::Target::TaskId(id) => validate_task_id(&id).map(LabelTarget::Task),
+        });
+        let (target, label_ids) = (
+            validate_label_target(target),
+            validate_label_ids(&request.label_ids),
+        )
+            .validate_all()?;
+        let labels = self.labels.add(target, label_ids).await?;
+        Ok(Response::new(AddLabelsResponse {
+            labels: labels.into_iter().map(LabelData::from).collect(),
+        }))
+    }
+
+    /// Detach labels from a story or task.
+    async fn remove_labels(
+        &self,
+        request: Request<RemoveLabelsRequest>,
+    ) -> Result<Response<RemoveLabelsResponse>, GrpcStatus> {
+        log::debug!("Remove labels");
+        let request = request.into_inner();
+        let target = request.target.map(|target| match target {
+            remove_labels_request::Target::StoryId(id) => {
+                validate_story_id(&id).map(LabelTarget::Story)
+            }
+            remove_labels_request::Target::TaskId(id) => {
+                validate_task_id(&id).map(LabelTarget::Task)
+            }
+        });
+        let (target, label_ids) = (
+            validate_label_target(target),
+            validate_label_ids(&request.label_ids),
+        )
+            .validate_all()?;
+        let labels = self.labels.remove(target, label_ids).await?;
+        Ok(Response::new(RemoveLabelsResponse {
+            labels: labels.into_iter().map(LabelData::from).collect(),
+        }))
+    }
+
+    /// Comment on a task.
+    async fn add_comment(
+        &self,
+        request: Request<AddCommentRequest>,
+    ) -> Result<Response<AddCommentResponse>, GrpcStatus> {
+        log::debug!("Add comment");
+        let author = validate_author(request.metadata())?;
+        let request = request.into_inner();
+        let (task_id, body) = (
+            validate_task_id(&request.task_id),
+            validate_comment_body(request.body, self.limits.comment_max_bytes),
+        )
+            .validate_all()?;
+        let comment = self.comments.add(task_id, author, body).await?;
+        Ok(Response::new(AddCommentResponse {
+            comment: Some(CommentData::from(comment)),
+        }))
+    }
+
+    /// Get a page of comments on a task.
+    async fn list_comments(
+        &self,
+        request: Request<ListCommentsRequest>,
+    ) -> Result<Response<ListCommentsResponse>, GrpcStatus> {
+        log::debug!("List comments");
+        let request = request.get_ref();
+        let task_id = validate_task_id(&request.task_id)?;
+        let page_params = clamp_page_bounds(request.cursor, request.limit, &self.limits);
+        let view = View::from(ProtoView::try_from(request.view).unwrap_or(ProtoView::Unspecified));
+        let Page(next_cursor, comments) = self.comments.list(task_id, page_params, view).await?;
+        Ok(Response::new(ListCommentsResponse {
+            next_cursor,
+            comments: comments.into_iter().map(CommentData::from).collect(),
+        }))
+    }
+
+    /// Edit a comment.
+    async fn edit_comment(
+        &self,
+        request: Request<EditCommentRequest>,
+    ) -> Result<Response<EditCommentResponse>, GrpcStatus> {
+        log::debug!("Edit comment");
+        let author = validate_author(request.metadata())?;
+        let request = request.into_inner();
+        let (comment_id, body) = (
+            validate_comment_id(&request.comment_id),
+            validate_comment_body(request.body, self.limits.comment_max_bytes),
+        )
+            .validate_all()?;
+        let comment = self.comments.edit(comment_id, author, body).await?;
+        Ok(Response::new(EditCommentResponse {
+            comment: Some(CommentData::from(comment)),
+        }))
+    }
+
+    /// Delete a comment.
+    async fn delete_comment(
+        &self,
+        request: Request<DeleteCommentRequest>,
+    ) -> Result<Response<DeleteCommentResponse>, GrpcStatus> {
+        log::debug!("Delete comment");
+        let author = validate_author(request.metadata())?;
+        let comment_id = validate_comment_id(&request.get_ref().comment_id)?;
+        self.comments.delete(comment_id, author).await?;
+        Ok(Response::new(DeleteCommentResponse {}))
+    }
+
+    /// Find stories and tasks by name and description.
+    async fn search(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<SearchResponse>, GrpcStatus> {
+        log::debug!("Search");
+        let request = request.get_ref();
+        let (terms, story_id) = (
+            validate_search_query(&request.query),
+            validate_optional_story_id(request.story_id.as_deref()),
+        )
+            .validate_all()?;
+        let page_params = clamp_page_bounds(request.cursor, request.limit, &self.limits);
+        let query = SearchQuery { terms, story_id };
+        let Page(next_cursor, hits) = self.search.search(query, page_params).await?;
+        Ok(Response::new(SearchResponse {
+            next_cursor,
+            hits: hits.into_iter().map(SearchHit::from).collect(),
+        }))
+    }
+
+    /// Create a story template.
+    async fn create_template(
+        &self,
+        request: Request<CreateTemplateRequest>,
+    ) -> Result<Response<CreateTemplateResponse>, GrpcStatus> {
+        log::debug!("Create template");
+        let template = validate_new_template(request.into_inner(), &self.limits)?;
+        let template = self.templates.create(template).await?;
+        Ok(Response::new(CreateTemplateResponse {
+            template: Some(TemplateData::from(template)),
+        }))
+    }
+
+    /// Get a page of story templates.
+    async fn list_templates(
+        &self,
+        request: Request<ListTemplatesRequest>,
+    ) -> Result<Response<ListTemplatesResponse>, GrpcStatus> {
+        log::debug!("List templates");
+        let request = request.get_ref();
+        let page_params = clamp_page_bounds(request.cursor, request.limit, &self.limits);
+        let Page(next_cursor, templates) = self.templates.list(page_params).await?;
+        Ok(Response::new(ListTemplatesResponse {
+            next_cursor,
+            templates: templates.into_iter().map(TemplateData::from).collect(),
+        }))
+    }
+
+    /// Create a story with its tasks from a template.
+    async fn instantiate_template(
+        &self,
+        request: Request<InstantiateTemplateRequest>,
+    ) -> Result<Response<InstantiateTemplateResponse>, GrpcStatus> {
+        log::debug!("Instantiate template");
+        let template_id = validate_template_id(&request.get_ref().template_id)?;
+        let (story, tasks) = self.templates.instantiate(template_id).await?;
+        Ok(Response::new(InstantiateTemplateResponse {
+            story: Some(StoryData::from(story)),
+            tasks: tasks.into_iter().map(TaskData::from).collect(),
         }))
     }
 }
@@ -1,22 +1,64 @@
 use crate::{
     Error, Result,
+    config::Limits,
     domain::{
-        Cursor, Limit, PAGE_CURSOR_MAX, PAGE_CURSOR_MIN, PAGE_LIMIT_MAX, PAGE_LIMIT_MIN,
-        PageParams, StoryId, TaskId,
+        CommentId, Cursor, Keyset, LabelId, LabelTarget, Limit, NewTask, NewTemplate,
+        PAGE_CURSOR_MAX, PAGE_CURSOR_MIN, PageParams, Placement, Priority, Recurrence, Status,
+        StoryId, StoryImport, StoryKeyset, StorySort, TASK_DEPTH_MAX, TaskFilter, TaskId,
+        TaskImport, TaskSelection, TemplateId, TimeRange, render_pattern,
+    },
+    error::Violation,
+    proto::{
+        BatchCreateTaskItem, CreateTemplateRequest, ImportStoriesRequest, ImportTask,
+        StoryTaskFilter, TaskPriority, TaskStatus, reorder_task_request::Anchor,
     },
 };
+use chrono::{DateTime, TimeDelta, Utc};
+use prost_types::{Duration, Timestamp};
+use std::{collections::HashMap, str::FromStr};
+use tonic::metadata::MetadataMap;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 use uuid::Uuid;
 
-const MAX_STR_LEN: usize = 1000;
+/// Invisible characters that are never legitimate in a name. Zero-width (non-)joiners are
+/// allowed since emoji sequences and several scripts depend on them.
+const INVISIBLE_CHARS: [char; 4] = ['\u{200B}', '\u{2060}', '\u{FEFF}', '\u{180E}'];
+
+/// The request metadata key naming who is making a comment request.
+pub(crate) const AUTHOR_METADATA_KEY: &str = "x-gsdx-author";
+
+/// Maximum author name length, in graphemes.
+const AUTHOR_MAX_LEN: usize = 128;
+
+/// Maximum assignee id length, in bytes.
+const ASSIGNEE_MAX_BYTES: usize = 255;
 
-/// Validates name length (0 < name.len() < 1000).
-pub(crate) fn validate_name<S: Into<String>>(name: S) -> Result<String> {
-    let name = name.into().trim().to_string();
+/// Maximum search query length, in characters.
+const SEARCH_QUERY_MAX_LEN: usize = 256;
+
+/// Validates and normalizes a name: NFC normalized, no control or invisible characters, and
+/// 0 < graphemes <= max_len.
+pub(crate) fn validate_name<S: Into<String>>(name: S, max_len: usize) -> Result<String> {
+    let name: String = name.into().trim().nfc().collect();
     if name.is_empty() {
-        return Err(Error::invalid_args("name cannot be empty"));
+        return Err(Error::invalid_args("name", "cannot be empty"));
+    }
+    if name
+        .chars()
+        .any(|c| c.is_control() || INVISIBLE_CHARS.contains(&c))
+    {
+        return Err(Error::invalid_args(
+            "name",
+            "cannot contain control or invisible characters",
+        ));
     }
-    if name.len() > MAX_STR_LEN {
-        return Err(Error::invalid_args("name is too long"));
+    let len = name.graphemes(true).count();
+    if len > max_len {
+        return Err(Error::invalid_args(
+            "name",
+            format!("too long ({len} > {max_len} characters)"),
+        ));
     }
     Ok(name)
 }
@@ -24,33 +66,757 @@ pub(crate) fn validate_name<S: Into<String>>(name: S) -> Result<String> {
 /// Validates an optional name if provided.
 pub(crate) fn validate_optional_name<S: Into<String>>(
     maybe_name: Option<S>,
+    max_len: usize,
 ) -> Result<Option<String>> {
-    maybe_name.map(validate_name).transpose()
+    maybe_name
+        .map(|name| validate_name(name, max_len))
+        .transpose()
+}
+
+/// Validates a Markdown description: no control characters other than whitespace, and at most
+/// max_bytes long.
+pub(crate) fn validate_description<S: Into<String>>(
+    description: S,
+    max_bytes: usize,
+) -> Result<String> {
+    validate_markdown("description", description.into(), max_bytes)
+}
+
+/// Validates a Markdown comment body: not blank, otherwise the same rules as descriptions.
+pub(crate) fn validate_comment_body<S: Into<String>>(body: S, max_bytes: usize) -> Result<String> {
+    let body = body.into();
+    if body.trim().is_empty() {
+        return Err(Error::invalid_args("body", "cannot be empty"));
+    }
+    validate_markdown("body", body, max_bytes)
+}
+
+/// Shared Markdown text rules: no control characters other than whitespace, and at most
+/// max_bytes long.
+fn validate_markdown(field: &str, text: String, max_bytes: usize) -> Result<String> {
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+    {
+        return Err(Error::invalid_args(
+            field,
+            "cannot contain control characters",
+        ));
+    }
+    let len = text.len();
+    if len > max_bytes {
+        return Err(Error::invalid_args(
+            field,
+            format!("too long ({len} > {max_bytes} bytes)"),
+        ));
+    }
+    Ok(text)
+}
+
+/// Validates an optional description if provided.
+pub(crate) fn validate_optional_description<S: Into<String>>(
+    maybe_description: Option<S>,
+    max_bytes: usize,
+) -> Result<Option<String>> {
+    maybe_description
+        .map(|description| validate_description(description, max_bytes))
+        .transpose()
 }
 
 /// Ensure a story id value can be created from a string
 pub(crate) fn validate_story_id(input: &str) -> Result<StoryId> {
-    let uuid = validate_uuid(input)?;
+    let uuid = validate_uuid("story_id", input)?;
     Ok(StoryId(uuid))
 }
 
 /// Ensure a task id value can be created from a string
 pub(crate) fn validate_task_id(input: &str) -> Result<TaskId> {
-    let uuid = validate_uuid(input)?;
+    let uuid = validate_uuid("task_id", input)?;
+    Ok(TaskId(uuid))
+}
+
+/// Ensure a comment id value can be created from a string
+pub(crate) fn validate_comment_id(input: &str) -> Result<CommentId> {
+    let uuid = validate_uuid("comment_id", input)?;
+    Ok(CommentId(uuid))
+}
+
+/// Read the comment author from request metadata. A missing author means the caller has not
+/// identified themselves; a present one must be a valid name.
+pub(crate) fn validate_author(metadata: &MetadataMap) -> Result<String> {
+    let author = metadata
+        .get(AUTHOR_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .unwrap_or_default();
+    if author.is_empty() {
+        return Err(Error::unauthenticated(format!(
+            "missing {AUTHOR_METADATA_KEY} metadata"
+        )));
+    }
+    validate_name(author, AUTHOR_MAX_LEN)
+        .map_err(|_| Error::invalid_args(AUTHOR_METADATA_KEY, "must be a valid name"))
+}
+
+/// Ensure a blocker task id value can be created from a string
+pub(crate) fn validate_blocker_task_id(input: &str) -> Result<TaskId> {
+    let uuid = validate_uuid("blocker_task_id", input)?;
     Ok(TaskId(uuid))
 }
 
+/// Ensure an optional parent task id is valid if provided.
+pub(crate) fn validate_parent_task_id(maybe_input: Option<String>) -> Result<Option<TaskId>> {
+    maybe_input
+        .map(|input| validate_uuid("parent_task_id", &input).map(TaskId))
+        .transpose()
+}
+
+/// Ensure a parent change either sets or clears the parent, but not both.
+pub(crate) fn validate_parent_change(
+    maybe_input: Option<String>,
+    clear: bool,
+) -> Result<Option<Option<TaskId>>> {
+    match (validate_parent_task_id(maybe_input)?, clear) {
+        (Some(_), true) => Err(Error::invalid_args(
+            "clear_parent_task_id",
+            "cannot be combined with parent_task_id",
+        )),
+        (Some(parent_id), false) => Ok(Some(Some(parent_id))),
+        (None, true) => Ok(Some(None)),
+        (None, false) => Ok(None),
+    }
+}
+
+/// Ensure a template id value can be created from a string
+pub(crate) fn validate_template_id(input: &str) -> Result<TemplateId> {
+    let uuid = validate_uuid("template_id", input)?;
+    Ok(TemplateId(uuid))
+}
+
+/// Ensure a label id value can be created from a string
+pub(crate) fn validate_label_id(input: &str) -> Result<LabelId> {
+    let uuid = validate_uuid("label_id", input)?;
+    Ok(LabelId(uuid))
+}
+
+/// Ensure a list of label ids are valid, dropping duplicates.
+pub(crate) fn validate_label_ids(inputs: &[String]) -> Result<Vec<LabelId>> {
+    let mut violations = Vec::new();
+    let mut label_ids = Vec::with_capacity(inputs.len());
+    for (i, input) in inputs.iter().enumerate() {
+        match validate_uuid(&format!("label_ids[{i}]"), input) {
+            Ok(uuid) if !label_ids.contains(&LabelId(uuid)) => label_ids.push(LabelId(uuid)),
+            Ok(_) => {}
+            Err(err) => violations.extend(err.into_violations()),
+        }
+    }
+    if violations.is_empty() {
+        Ok(label_ids)
+    } else {
+        Err(Error::InvalidArgs { violations })
+    }
+}
+
+/// Ensure a batch has at least one and at most max_size items.
+pub(crate) fn validate_batch_size(field: &str, size: usize, max_size: usize) -> Result<()> {
+    if size == 0 {
+        return Err(Error::invalid_args(field, "cannot be empty"));
+    }
+    if size > max_size {
+        return Err(Error::invalid_args(
+            field,
+            format!("too many items ({size} > {max_size})"),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate every task in a batch up front, reporting violations by index as `tasks[i].field`.
+pub(crate) fn validate_batch_tasks(
+    items: Vec<BatchCreateTaskItem>,
+    limits: &Limits,
+) -> Result<Vec<NewTask>> {
+    validate_batch_size("tasks", items.len(), limits.batch_max_size)?;
+    let mut violations = Vec::new();
+    let mut tasks = Vec::with_capacity(items.len());
+    for (i, item) in items.into_iter().enumerate() {
+        let name = validate_name(item.name, limits.task_name_max_len);
+        let status = TaskStatus::try_from(item.status)
+            .map(Status::from)
+            .map_err(|_| Error::invalid_args("status", "unknown status"));
+        match with_field_prefix(&format!("tasks[{i}]"), (name, status).validate_all()) {
+            Ok((name, status)) => tasks.push(NewTask::new(name, status)),
+            Err(err) => violations.extend(err.into_violations()),
+        }
+    }
+    if violations.is_empty() {
+        Ok(tasks)
+    } else {
+        Err(Error::InvalidArgs { violations })
+    }
+}
+
+/// Validate a batch of task ids, dropping duplicates.
+pub(crate) fn validate_batch_task_ids(inputs: &[String], limits: &Limits) -> Result<Vec<TaskId>> {
+    validate_batch_size("task_ids.ids", inputs.len(), limits.batch_max_size)?;
+    let mut violations = Vec::new();
+    let mut task_ids = Vec::with_capacity(inputs.len());
+    for (i, input) in inputs.iter().enumerate() {
+        match validate_uuid(&format!("task_ids.ids[{i}]"), input) {
+            Ok(uuid) if !task_ids.contains(&TaskId(uuid)) => task_ids.push(TaskId(uuid)),
+            Ok(_) => {}
+            Err(err) => violations.extend(err.into_violations()),
+        }
+    }
+    if violations.is_empty() {
+        Ok(task_ids)
+    } else {
+        Err(Error::InvalidArgs { violations })
+    }
+}
+
+/// Validate a template pattern: a name with only known placeholders, which still fits within
+/// the max length once filled in. Placeholders fill in to the same width on any date.
+pub(crate) fn validate_pattern(field: &str, pattern: String, max_len: usize) -> Result<String> {
+    let in_field = |err: Error| Error::InvalidArgs {
+        violations: err
+            .into_violations()
+            .into_iter()
+            .map(|v| Violation {
+                field: field.to_string(),
+                ..v
+            })
+            .collect(),
+    };
+    let pattern = validate_name(pattern, usize::MAX).map_err(in_field)?;
+    let name = render_pattern(&pattern, Utc::now().date_naive())
+        .map_err(|err| Error::invalid_args(field, err))?;
+    validate_name(name, max_len).map_err(in_field)?;
+    Ok(pattern)
+}
+
+/// Validate a new story template, with up to a batch of task names.
+pub(crate) fn validate_new_template(
+    request: CreateTemplateRequest,
+    limits: &Limits,
+) -> Result<NewTemplate> {
+    let task_count = request.task_names.len();
+    if task_count > limits.batch_max_size {
+        return Err(Error::invalid_args(
+            "task_names",
+            format!("too many items ({task_count} > {})", limits.batch_max_size),
+        ));
+    }
+    let mut violations = Vec::new();
+    let mut task_names = Vec::with_capacity(task_count);
+    for (i, pattern) in request.task_names.into_iter().enumerate() {
+        let field = format!("task_names[{i}]");
+        match validate_pattern(&field, pattern, limits.task_name_max_len) {
+            Ok(pattern) => task_names.push(pattern),
+            Err(err) => violations.extend(err.into_violations()),
+        }
+    }
+    let task_names = if violations.is_empty() {
+        Ok(task_names)
+    } else {
+        Err(Error::InvalidArgs { violations })
+    };
+    let (name, name_pattern, task_names) = (
+        validate_name(request.name, limits.story_name_max_len),
+        validate_pattern(
+            "name_pattern",
+            request.name_pattern,
+            limits.story_name_max_len,
+        ),
+        task_names,
+    )
+        .validate_all()?;
+    Ok(NewTemplate {
+        name,
+        name_pattern,
+        task_names,
+    })
+}
+
+/// Validate a story task filter selecting tasks for a batch update.
+pub(crate) fn validate_story_task_filter(filter: StoryTaskFilter) -> Result<(StoryId, TaskFilter)> {
+    let result = (
+        validate_story_id(&filter.story_id),
+        validate_label_ids(&filter.label_ids),
+        validate_statuses(&filter.statuses),
+        validate_time_range("created", filter.created_after, filter.created_before),
+        validate_time_range("updated", filter.updated_after, filter.updated_before),
+    )
+        .validate_all();
+    let (story_id, label_ids, statuses, created, updated) =
+        with_field_prefix("story_filter", result)?;
+    let filter = TaskFilter {
+        label_ids,
+        statuses,
+        created,
+        updated,
+    };
+    Ok((story_id, filter))
+}
+
+/// Ensure a batch update selects tasks either by id or by story filter.
+pub(crate) fn validate_task_selection(
+    maybe_selection: Option<Result<TaskSelection>>,
+) -> Result<TaskSelection> {
+    maybe_selection.unwrap_or_else(|| {
+        Err(Error::invalid_args(
+            "selector",
+            "one of task_ids or story_filter is required",
+        ))
+    })
+}
+
+/// Ensure a required status is specified and known.
+pub(crate) fn validate_required_status(value: i32) -> Result<Status> {
+    validate_status_filter(value)?.ok_or_else(|| Error::invalid_args("status", "must be specified"))
+}
+
+/// Validate a story to import with all of its tasks. Task parents must be earlier tasks in the
+/// same story, nested no deeper than a task could be.
+pub(crate) fn validate_import_story(
+    request: ImportStoriesRequest,
+    limits: &Limits,
+) -> Result<StoryImport> {
+    let (id, name, description, created_at, updated_at, tasks) = (
+        validate_optional_story_id(request.story_id.as_deref()),
+        validate_name(request.name, limits.story_name_max_len),
+        validate_description(request.description, limits.description_max_bytes),
+        validate_timestamp("created_at", request.created_at),
+        validate_timestamp("updated_at", request.updated_at),
+        validate_import_tasks(request.tasks, limits),
+    )
+        .validate_all()?;
+    Ok(StoryImport {
+        id,
+        name,
+        description,
+        created_at,
+        updated_at,
+        tasks,
+    })
+}
+
+/// Validate the tasks of a story to import, reporting violations by index as `tasks[i].field`.
+fn validate_import_tasks(items: Vec<ImportTask>, limits: &Limits) -> Result<Vec<TaskImport>> {
+    let mut violations = Vec::new();
+    let mut depths: HashMap<TaskId, i32> = HashMap::new();
+    let mut tasks = Vec::with_capacity(items.len());
+    for (i, item) in items.into_iter().enumerate() {
+        let result = validate_import_task(item, limits).and_then(|task| {
+            let depth = match &task.task.parent_id {
+                Some(parent_id) => match depths.get(parent_id) {
+                    Some(depth) => depth + 1,
+                    None => {
+                        return Err(Error::invalid_args(
+                            "parent_task_id",
+                            "must be an earlier task in the same story",
+                        ));
+                    }
+                },
+                None => 1,
+            };
+            if depth > TASK_DEPTH_MAX {
+                return Err(Error::invalid_args(
+                    "parent_task_id",
+                    format!("subtasks cannot be nested more than {TASK_DEPTH_MAX} levels deep"),
+                ));
+            }
+            if let Some(id) = &task.id
+                && depths.insert(id.clone(), depth).is_some()
+            {
+                return Err(Error::invalid_args("task_id", "duplicate task id"));
+            }
+            Ok(task)
+        });
+        match with_field_prefix(&format!("tasks[{i}]"), result) {
+            Ok(task) => tasks.push(task),
+            Err(err) => violations.extend(err.into_violations()),
+        }
+    }
+    if violations.is_empty() {
+        Ok(tasks)
+    } else {
+        Err(Error::InvalidArgs { violations })
+    }
+}
+
+/// Validate the fields of one task to import.
+fn validate_import_task(item: ImportTask, limits: &Limits) -> Result<TaskImport> {
+    let status = TaskStatus::try_from(item.status)
+        .map(Status::from)
+        .map_err(|_| Error::invalid_args("status", "unknown status"));
+    let assignee = item.assignee.as_deref().map(validate_assignee).transpose();
+    let (
+        id,
+        parent_id,
+        name,
+        description,
+        status,
+        priority,
+        due_at,
+        assignee,
+        created_at,
+        updated_at,
+        recurrence,
+    ) = (
+        item.task_id.as_deref().map(validate_task_id).transpose(),
+        validate_parent_task_id(item.parent_task_id),
+        validate_name(item.name, limits.task_name_max_len),
+        validate_description(item.description, limits.description_max_bytes),
+        status,
+        validate_priority(item.priority),
+        validate_timestamp("due_at", item.due_at),
+        assignee,
+        validate_timestamp("created_at", item.created_at),
+        validate_timestamp("updated_at", item.updated_at),
+        validate_recurrence(item.recurrence),
+    )
+        .validate_all()?;
+    let task = NewTask {
+        parent_id,
+        name,
+        description,
+        status,
+        priority: priority.unwrap_or_default(),
+        due_at,
+        recurrence,
+    };
+    Ok(TaskImport {
+        id,
+        task,
+        assignee,
+        created_at,
+        updated_at,
+    })
+}
+
+/// Qualify the fields of any violations with the path of the message they were found in.
+pub(crate) fn with_field_prefix<T>(prefix: &str, result: Result<T>) -> Result<T> {
+    result.map_err(|err| match err {
+        Error::InvalidArgs { violations } => Error::InvalidArgs {
+            violations: violations
+                .into_iter()
+                .map(|v| Violation {
+                    field: format!("{prefix}.{}", v.field),
+                    ..v
+                })
+                .collect(),
+        },
+        err => err,
+    })
+}
+
+/// Ensure a label request names a story or task.
+pub(crate) fn validate_label_target(
+    maybe_target: Option<Result<LabelTarget>>,
+) -> Result<LabelTarget> {
+    maybe_target.unwrap_or_else(|| {
+        Err(Error::invalid_args(
+            "target",
+            "one of story_id or task_id is required",
+        ))
+    })
+}
+
 /// Ensure a uuid value can be created from a string
-fn validate_uuid(value: &str) -> Result<Uuid> {
-    let uuid = Uuid::parse_str(value.trim()).map_err(|err| Error::invalid_args(err.to_string()))?;
+fn validate_uuid(field: &str, value: &str) -> Result<Uuid> {
+    let uuid =
+        Uuid::parse_str(value.trim()).map_err(|err| Error::invalid_args(field, err.to_string()))?;
     Ok(uuid)
 }
 
+/// Combine validation results so every failure in a request is reported, not just the first.
+pub(crate) trait ValidateAll {
+    type Output;
+    fn validate_all(self) -> Result<Self::Output>;
+}
+
+// Implement for tuples of results, merging all failure messages into one error.
+macro_rules! impl_validate_all {
+    ($($t:ident),+) => {
+        impl<$($t),+> ValidateAll for ($(Result<$t>,)+) {
+            type Output = ($($t,)+);
+
+            #[allow(non_snake_case)]
+            fn validate_all(self) -> Result<Self::Output> {
+                match self {
+                    ($(Ok($t),)+) => Ok(($($t,)+)),
+                    ($($t,)+) => {
+                        let mut violations = Vec::new();
+                        $(
+                            if let Err(err) = $t {
+                                violations.extend(err.into_violations());
+                            }
+                        )+
+                        Err(Error::InvalidArgs { violations })
+                    }
+                }
+            }
+        }
+    };
+}
+
+impl_validate_all!(A, B);
+impl_validate_all!(A, B, C);
+impl_validate_all!(A, B, C, D);
+impl_validate_all!(A, B, C, D, E);
+impl_validate_all!(A, B, C, D, E, F);
+impl_validate_all!(A, B, C, D, E, F, G);
+impl_validate_all!(A, B, C, D, E, F, G, H);
+impl_validate_all!(A, B, C, D, E, F, G, H, I);
+impl_validate_all!(A, B, C, D, E, F, G, H, I, J);
+impl_validate_all!(A, B, C, D, E, F, G, H, I, J, K);
+
+/// Ensure a priority is a known value; unspecified maps to `None`.
+pub(crate) fn validate_priority(value: i32) -> Result<Option<Priority>> {
+    match TaskPriority::try_from(value) {
+        Ok(TaskPriority::Unspecified) => Ok(None),
+        Ok(TaskPriority::Low) => Ok(Some(Priority::Low)),
+        Ok(TaskPriority::Medium) => Ok(Some(Priority::Medium)),
+        Ok(TaskPriority::High) => Ok(Some(Priority::High)),
+        Ok(TaskPriority::Urgent) => Ok(Some(Priority::Urgent)),
+        Err(_) => Err(Error::invalid_args("priority", "unknown priority")),
+    }
+}
+
+/// Ensure a status filter is known, where unspecified means any status.
+pub(crate) fn validate_status_filter(value: i32) -> Result<Option<Status>> {
+    match TaskStatus::try_from(value) {
+        Ok(TaskStatus::Unspecified) => Ok(None),
+        Ok(TaskStatus::Incomplete) => Ok(Some(Status::Incomplete)),
+        Ok(TaskStatus::Complete) => Ok(Some(Status::Complete)),
+        Err(_) => Err(Error::invalid_args("status", "unknown status")),
+    }
+}
+
+/// Ensure every status in a list filter is known, dropping duplicates.
+pub(crate) fn validate_statuses(values: &[i32]) -> Result<Vec<Status>> {
+    let mut violations = Vec::new();
+    let mut statuses = Vec::with_capacity(values.len());
+    for (i, value) in values.iter().enumerate() {
+        let field = format!("statuses[{i}]");
+        match validate_status_filter(*value) {
+            Ok(Some(status)) if !statuses.contains(&status) => statuses.push(status),
+            Ok(Some(_)) => {}
+            Ok(None) => violations
+                .extend(Error::invalid_args(field, "cannot be unspecified").into_violations()),
+            Err(_) => {
+                violations.extend(Error::invalid_args(field, "unknown status").into_violations())
+            }
+        }
+    }
+    if violations.is_empty() {
+        Ok(statuses)
+    } else {
+        Err(Error::InvalidArgs { violations })
+    }
+}
+
+/// Validates an opaque assignee id: trimmed, not empty, no control characters and at most
+/// ASSIGNEE_MAX_BYTES long.
+pub(crate) fn validate_assignee(assignee: &str) -> Result<String> {
+    let assignee = assignee.trim();
+    if assignee.is_empty() {
+        return Err(Error::invalid_args("assignee", "cannot be empty"));
+    }
+    if assignee.chars().any(char::is_control) {
+        return Err(Error::invalid_args(
+            "assignee",
+            "cannot contain control characters",
+        ));
+    }
+    let len = assignee.len();
+    if len > ASSIGNEE_MAX_BYTES {
+        return Err(Error::invalid_args(
+            "assignee",
+            format!("too long ({len} > {ASSIGNEE_MAX_BYTES} bytes)"),
+        ));
+    }
+    Ok(assignee.to_string())
+}
+
+/// Validates search terms: trimmed, not empty, no control characters and at most
+/// SEARCH_QUERY_MAX_LEN characters.
+pub(crate) fn validate_search_query(query: &str) -> Result<String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(Error::invalid_args("query", "cannot be empty"));
+    }
+    if query.chars().any(char::is_control) {
+        return Err(Error::invalid_args(
+            "query",
+            "cannot contain control characters",
+        ));
+    }
+    let len = query.chars().count();
+    if len > SEARCH_QUERY_MAX_LEN {
+        return Err(Error::invalid_args(
+            "query",
+            format!("too long ({len} > {SEARCH_QUERY_MAX_LEN} characters)"),
+        ));
+    }
+    Ok(query.to_string())
+}
+
+/// Ensure an optional story id is valid if provided.
+pub(crate) fn validate_optional_story_id(input: Option<&str>) -> Result<Option<StoryId>> {
+    input.map(validate_story_id).transpose()
+}
+
+/// Ensure a reorder request names a valid sibling task.
+pub(crate) fn validate_placement(maybe_anchor: Option<Anchor>) -> Result<Placement> {
+    match maybe_anchor {
+        Some(Anchor::BeforeTaskId(id)) => {
+            let uuid = validate_uuid("before_task_id", &id)?;
+            Ok(Placement::Before(TaskId(uuid)))
+        }
+        Some(Anchor::AfterTaskId(id)) => {
+            let uuid = validate_uuid("after_task_id", &id)?;
+            Ok(Placement::After(TaskId(uuid)))
+        }
+        None => Err(Error::invalid_args(
+            "anchor",
+            "one of before_task_id or after_task_id is required",
+        )),
+    }
+}
+
+/// Ensure an optional gRPC timestamp is a valid date time.
+pub(crate) fn validate_timestamp(
+    field: &str,
+    maybe_ts: Option<Timestamp>,
+) -> Result<Option<DateTime<Utc>>> {
+    maybe_ts
+        .map(|ts| {
+            u32::try_from(ts.nanos)
+                .ok()
+                .and_then(|nanos| DateTime::from_timestamp(ts.seconds, nanos))
+                .ok_or_else(|| Error::invalid_args(field, "invalid timestamp"))
+        })
+        .transpose()
+}
+
+/// Ensure a time window has valid bounds, with the lower bound before the upper bound.
+pub(crate) fn validate_time_range(
+    field: &str,
+    maybe_after: Option<Timestamp>,
+    maybe_before: Option<Timestamp>,
+) -> Result<TimeRange> {
+    let (after, before) = (
+        validate_timestamp(&format!("{field}_after"), maybe_after),
+        validate_timestamp(&format!("{field}_before"), maybe_before),
+    )
+        .validate_all()?;
+    match (after, before) {
+        (Some(a), Some(b)) if a >= b => Err(Error::invalid_args(
+            format!("{field}_before"),
+            format!("must be later than {field}_after"),
+        )),
+        _ => Ok(TimeRange { after, before }),
+    }
+}
+
+/// Ensure a due date change either sets or clears the due date, but not both.
+pub(crate) fn validate_due_at_change(
+    maybe_ts: Option<Timestamp>,
+    clear: bool,
+) -> Result<Option<Option<DateTime<Utc>>>> {
+    match (validate_timestamp("due_at", maybe_ts)?, clear) {
+        (Some(_), true) => Err(Error::invalid_args(
+            "clear_due_at",
+            "cannot be combined with due_at",
+        )),
+        (Some(due_at), false) => Ok(Some(Some(due_at))),
+        (None, true) => Ok(Some(None)),
+        (None, false) => Ok(None),
+    }
+}
+
+/// Parse an optional recurrence rule, where blank is none.
+pub(crate) fn validate_recurrence(maybe_input: Option<String>) -> Result<Option<Recurrence>> {
+    match maybe_input.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(input) => Recurrence::from_str(input)
+            .map(Some)
+            .map_err(|err| Error::invalid_args("recurrence", err)),
+    }
+}
+
+/// Validate a recurrence change: a new rule, clearing the rule, or leaving it unchanged.
+pub(crate) fn validate_recurrence_change(
+    maybe_input: Option<String>,
+    clear: bool,
+) -> Result<Option<Option<Recurrence>>> {
+    match (validate_recurrence(maybe_input)?, clear) {
+        (Some(_), true) => Err(Error::invalid_args(
+            "clear_recurrence",
+            "cannot be combined with recurrence",
+        )),
+        (Some(rule), false) => Ok(Some(Some(rule))),
+        (None, true) => Ok(Some(None)),
+        (None, false) => Ok(None),
+    }
+}
+
+/// Ensure an optional gRPC duration is a non-negative time delta, defaulting to zero.
+pub(crate) fn validate_duration(
+    field: &str,
+    maybe_duration: Option<Duration>,
+) -> Result<TimeDelta> {
+    let Some(duration) = maybe_duration else {
+        return Ok(TimeDelta::zero());
+    };
+    u32::try_from(duration.nanos)
+        .ok()
+        .and_then(|nanos| TimeDelta::new(duration.seconds, nanos))
+        .filter(|delta| *delta >= TimeDelta::zero())
+        .ok_or_else(|| Error::invalid_args(field, "must be a non-negative duration"))
+}
+
+/// Ensure a page token is either empty, or came from a previous response.
+pub(crate) fn validate_page_token(token: &str) -> Result<Option<Keyset>> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Ok(None);
+    }
+    let keyset = Keyset::from_str(token).map_err(|err| Error::invalid_args("page_token", err))?;
+    Ok(Some(keyset))
+}
+
+/// Parse an optional story page token, which must have been made for the same sort.
+pub(crate) fn validate_story_page_token(
+    token: &str,
+    sort: &StorySort,
+) -> Result<Option<StoryKeyset>> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Ok(None);
+    }
+    let keyset =
+        StoryKeyset::from_str(token).map_err(|err| Error::invalid_args("page_token", err))?;
+    if keyset.sort != *sort {
+        return Err(Error::invalid_args(
+            "page_token",
+            format!("was made for order_by \"{}\"", keyset.sort),
+        ));
+    }
+    Ok(Some(keyset))
+}
+
 /// Ensure a paging params are within reasonable bounds.
-pub(crate) fn clamp_page_bounds(cursor: Cursor, limit: Limit) -> PageParams {
+pub(crate) fn clamp_page_bounds(cursor: Cursor, limit: Limit, limits: &Limits) -> PageParams {
     let cursor = cursor.clamp(PAGE_CURSOR_MIN, PAGE_CURSOR_MAX);
-    let limit = limit.clamp(PAGE_LIMIT_MIN, PAGE_LIMIT_MAX);
-    PageParams(cursor, limit)
+    PageParams(cursor, clamp_limit(limit, limits))
+}
+
+/// Ensure a page size limit is within reasonable bounds.
+pub(crate) fn clamp_limit(limit: Limit, limits: &Limits) -> Limit {
+    limit.clamp(limits.page_limit_min, limits.page_limit_max)
 }
 
 #[cfg(test)]
@@ -59,32 +825,297 @@ mod tests {
 
     #[test]
     fn validate_string_success() {
-        let result = validate_name(" test ").unwrap();
+        let result = validate_name(" test ", 1000).unwrap();
         assert_eq!(result, "test");
     }
 
+    #[test]
+    fn validate_name_normalizes() {
+        let result = validate_name("Cafe\u{301}", 1000).unwrap();
+        assert_eq!(result, "Caf\u{e9}");
+    }
+
+    #[test]
+    fn control_chars_fail() {
+        assert!(validate_name("to\u{7}do", 1000).is_err());
+        assert!(validate_name("line\nbreak", 1000).is_err());
+        assert!(validate_name("zero\u{200B}width", 1000).is_err());
+    }
+
+    #[test]
+    fn max_len_counts_graphemes() {
+        let input = "\u{6f22}".repeat(400);
+        assert!(validate_name(&input, 1000).is_ok());
+        let input = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}".repeat(2);
+        assert!(validate_name(&input, 2).is_ok());
+        assert!(validate_name(&input, 1).is_err());
+    }
+
+    #[test]
+    fn validate_all_aggregates() {
+        let result = (validate_uuid("id", "4ac0160a"), validate_name(" ", 1000)).validate_all();
+        let Err(Error::InvalidArgs { violations }) = result else {
+            panic!("expected invalid args");
+        };
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[1].field, "name");
+    }
+
     #[test]
     fn whitespace_only_fail() {
-        assert!(validate_name("  ").is_err());
-        assert!(validate_name("\t\t").is_err());
-        assert!(validate_name("\n\n").is_err());
+        assert!(validate_name("  ", 1000).is_err());
+        assert!(validate_name("\t\t", 1000).is_err());
+        assert!(validate_name("\n\n", 1000).is_err());
     }
 
     #[test]
     fn max_len_fail() {
         let input = "0123456789!".repeat(100);
-        assert!(validate_name(&input).is_err());
+        assert!(validate_name(&input, 1000).is_err());
+    }
+
+    #[test]
+    fn validate_due_at_change_conflict() {
+        let ts = Some(Timestamp::default());
+        assert!(validate_due_at_change(ts, true).is_err());
+        assert_eq!(validate_due_at_change(None, true).unwrap(), Some(None));
+        assert_eq!(validate_due_at_change(None, false).unwrap(), None);
+    }
+
+    #[test]
+    fn validate_recurrence_change_conflict() {
+        let rule = || Some("FREQ=DAILY".to_string());
+        assert!(validate_recurrence_change(rule(), true).is_err());
+        assert!(matches!(
+            validate_recurrence_change(rule(), false),
+            Ok(Some(Some(_)))
+        ));
+        assert_eq!(
+            validate_recurrence_change(Some(" ".into()), true).unwrap(),
+            Some(None)
+        );
+        assert_eq!(validate_recurrence_change(None, false).unwrap(), None);
+        let Err(err) = validate_recurrence(Some("FREQ=HOURLY".into())) else {
+            panic!("expected an invalid rule");
+        };
+        assert_eq!(err.into_violations()[0].field, "recurrence");
+    }
+
+    #[test]
+    fn validate_timestamp_fail() {
+        let ts = Timestamp {
+            seconds: 0,
+            nanos: -1,
+        };
+        assert!(validate_timestamp("due_at", Some(ts)).is_err());
     }
 
     #[test]
     fn validate_uuid_success() {
-        let input = format!(" {} ", Uuid::new_v4().to_string());
-        let result = validate_uuid(&input).unwrap();
+        let input = format!(" {} ", Uuid::new_v4());
+        let result = validate_uuid("id", &input).unwrap();
         assert_eq!(result.to_string(), input.trim());
     }
 
+    #[test]
+    fn validate_description_limits() {
+        let markdown = "# Notes\n\n- [ ] read\n\tindented\r\n";
+        assert_eq!(validate_description(markdown, 100).unwrap(), markdown);
+        assert!(validate_description("nul\u{0}byte", 100).is_err());
+        assert!(validate_description("\u{e9}".repeat(3), 5).is_err());
+    }
+
+    #[test]
+    fn validate_comment_body_blank_fail() {
+        assert!(validate_comment_body(" \n\t", 100).is_err());
+        assert!(validate_comment_body("ok", 1).is_err());
+        assert_eq!(validate_comment_body(" ok\n", 100).unwrap(), " ok\n");
+    }
+
+    #[test]
+    fn validate_author_metadata() {
+        let mut metadata = MetadataMap::new();
+        let result = validate_author(&metadata);
+        assert!(matches!(result, Err(Error::Unauthenticated { .. })));
+        metadata.insert(AUTHOR_METADATA_KEY, " alice ".parse().unwrap());
+        assert_eq!(validate_author(&metadata).unwrap(), "alice");
+    }
+
+    #[test]
+    fn validate_assignee_trims() {
+        assert_eq!(validate_assignee(" user-1 ").unwrap(), "user-1");
+        assert!(validate_assignee("  ").is_err());
+        assert!(validate_assignee("tab\there").is_err());
+        assert!(validate_assignee(&"x".repeat(256)).is_err());
+    }
+
+    #[test]
+    fn validate_status_filter_unspecified() {
+        assert_eq!(validate_status_filter(0).unwrap(), None);
+        assert_eq!(validate_status_filter(2).unwrap(), Some(Status::Complete));
+        assert!(validate_status_filter(9).is_err());
+    }
+
+    #[test]
+    fn validate_statuses_dedup() {
+        assert_eq!(validate_statuses(&[2, 1, 2]).unwrap().len(), 2);
+        let Err(err) = validate_statuses(&[1, 0, 9]) else {
+            panic!("expected invalid statuses");
+        };
+        let violations = err.into_violations();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[1].field, "statuses[2]");
+    }
+
+    #[test]
+    fn validate_time_range_order() {
+        let ts = |seconds| Some(Timestamp { seconds, nanos: 0 });
+        let range = validate_time_range("updated", ts(1), ts(2)).unwrap();
+        assert!(range.after < range.before);
+        assert!(validate_time_range("updated", None, ts(2)).is_ok());
+        let Err(err) = validate_time_range("updated", ts(2), ts(2)) else {
+            panic!("expected invalid time range");
+        };
+        assert_eq!(err.into_violations()[0].field, "updated_before");
+    }
+
+    #[test]
+    fn validate_batch_tasks_indexed() {
+        let item = |name: &str, status| BatchCreateTaskItem {
+            name: name.into(),
+            status,
+        };
+        let limits = Limits::default();
+        let tasks = validate_batch_tasks(vec![item(" a ", 0), item("b", 2)], &limits).unwrap();
+        assert_eq!(tasks[0], NewTask::new("a", Status::Incomplete));
+        assert_eq!(tasks[1].status, Status::Complete);
+        let Err(err) = validate_batch_tasks(vec![item("a", 1), item(" ", 9)], &limits) else {
+            panic!("expected invalid batch");
+        };
+        let fields: Vec<_> = err.into_violations().into_iter().map(|v| v.field).collect();
+        assert_eq!(fields, ["tasks[1].name", "tasks[1].status"]);
+        assert!(validate_batch_tasks(Vec::new(), &limits).is_err());
+    }
+
+    #[test]
+    fn validate_new_template_patterns() {
+        let limits = Limits {
+            story_name_max_len: 13,
+            ..Limits::default()
+        };
+        let request = |name_pattern: &str, task_names: &[&str]| CreateTemplateRequest {
+            name: "Weekly".into(),
+            name_pattern: name_pattern.into(),
+            task_names: task_names.iter().map(|name| name.to_string()).collect(),
+        };
+        let template = validate_new_template(request(" Week {week} ", &["Plan"]), &limits).unwrap();
+        assert_eq!(template.name_pattern, "Week {week}");
+        assert_eq!(template.task_names, ["Plan"]);
+
+        // The filled in name must fit, as well as the pattern
+        let Err(err) = validate_new_template(request("Review {week}", &[]), &limits) else {
+            panic!("expected a name too long");
+        };
+        assert_eq!(err.into_violations()[0].field, "name_pattern");
+
+        let Err(err) = validate_new_template(request("{day}", &["ok", "{x", " "]), &limits) else {
+            panic!("expected invalid patterns");
+        };
+        let fields: Vec<_> = err.into_violations().into_iter().map(|v| v.field).collect();
+        assert_eq!(fields, ["name_pattern", "task_names[1]", "task_names[2]"]);
+    }
+
+    #[test]
+    fn validate_batch_task_ids_dedup() {
+        let limits = Limits::default();
+        let id = Uuid::new_v4().to_string();
+        let result = validate_batch_task_ids(&[id.clone(), id], &limits).unwrap();
+        assert_eq!(result.len(), 1);
+        let Err(err) = validate_batch_task_ids(&["a".into()], &limits) else {
+            panic!("expected invalid task ids");
+        };
+        assert_eq!(err.into_violations()[0].field, "task_ids.ids[0]");
+        let limits = Limits {
+            batch_max_size: 1,
+            ..Limits::default()
+        };
+        let ids = [Uuid::new_v4().to_string(), Uuid::new_v4().to_string()];
+        assert!(validate_batch_task_ids(&ids, &limits).is_err());
+    }
+
+    #[test]
+    fn validate_story_task_filter_prefixed() {
+        let filter = StoryTaskFilter {
+            story_id: "a".into(),
+            statuses: vec![0],
+            ..Default::default()
+        };
+        let Err(err) = validate_story_task_filter(filter) else {
+            panic!("expected invalid filter");
+        };
+        let fields: Vec<_> = err.into_violations().into_iter().map(|v| v.field).collect();
+        assert_eq!(
+            fields,
+            ["story_filter.story_id", "story_filter.statuses[0]"]
+        );
+    }
+
+    #[test]
+    fn validate_required_status_unspecified() {
+        assert!(validate_required_status(0).is_err());
+        assert_eq!(validate_required_status(2).unwrap(), Status::Complete);
+    }
+
+    #[test]
+    fn validate_import_story_parents() {
+        let parent = Uuid::new_v4().to_string();
+        let task = |id: Option<&str>, parent: Option<&str>| ImportTask {
+            task_id: id.map(Into::into),
+            parent_task_id: parent.map(Into::into),
+            name: "task".into(),
+            ..Default::default()
+        };
+        let request = ImportStoriesRequest {
+            name: "story".into(),
+            tasks: vec![task(Some(&parent), None), task(None, Some(&parent))],
+            ..Default::default()
+        };
+        let story = validate_import_story(request, &Limits::default()).unwrap();
+        assert_eq!(story.tasks[1].task.parent_id, story.tasks[0].id);
+        let request = ImportStoriesRequest {
+            name: " ".into(),
+            tasks: vec![task(None, Some(&parent)), task(Some(&parent), None)],
+            ..Default::default()
+        };
+        let Err(err) = validate_import_story(request, &Limits::default()) else {
+            panic!("expected invalid import");
+        };
+        let fields: Vec<_> = err.into_violations().into_iter().map(|v| v.field).collect();
+        assert_eq!(fields, ["name", "tasks[0].parent_task_id"]);
+    }
+
+    #[test]
+    fn validate_search_query_limits() {
+        let result = validate_search_query(" \"blood meridian\" ").unwrap();
+        assert_eq!(result, "\"blood meridian\"");
+        assert!(validate_search_query(" ").is_err());
+        assert!(validate_search_query(&"a".repeat(257)).is_err());
+    }
+
+    #[test]
+    fn validate_label_ids_dedup() {
+        let id = Uuid::new_v4().to_string();
+        let result = validate_label_ids(&[id.clone(), id]).unwrap();
+        assert_eq!(result.len(), 1);
+        let Err(err) = validate_label_ids(&["a".into(), "b".into()]) else {
+            panic!("expected invalid label ids");
+        };
+        let violations = err.into_violations();
+        assert_eq!(violations[1].field, "label_ids[1]");
+    }
+
     #[test]
     fn validate_uuid_fail() {
-        assert!(validate_uuid("4ac0160a").is_err());
+        assert!(validate_uuid("id", "4ac0160a").is_err());
     }
 }
@@ -14,7 +14,7 @@ pub mod proto {
         tonic::include_file_descriptor_set!("gsdx_v1_descriptor");
 }
 
-/// Configuration from environment variables.
+/// Configuration from files, environment variables and flags.
 pub mod config;
 
 /// The service layer.
@@ -32,12 +32,21 @@ pub mod error;
 /// A light-weight abstraction over the database.
 pub mod repo;
 
+/// In-memory effects, for tests and demos without a database.
+pub mod memory;
+
 /// The GSDX transport server.
 pub mod server;
 
 /// gRPC implementation layer.
 pub mod grpc;
 
+/// Export and import of stories as files.
+pub mod transfer;
+
+/// A command line client for the GSDX gRPC service.
+pub mod client;
+
 /// Export error type
 pub use error::Error;
 
@@ -1,12 +1,28 @@
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-use gsdx::{config::Config, server::Server};
+use gsdx::{
+    client::{ClientArgs, ClientCmd},
+    config::{Config, ConfigArgs, Storage},
+    domain::StoryId,
+    repo::Repo,
+    server::Server,
+    transfer::{ExportFormat, ImportFormat, export_stories, import_stories},
+};
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use dotenvy::dotenv;
 use sqlx::migrate::Migrator;
-use std::error::Error;
+use std::{
+    env,
+    error::Error,
+    fs::File,
+    io::{self, BufReader, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+use uuid::Uuid;
 
 // Embed migrations into the GSDX binary.
 pub static MIGRATOR: Migrator = sqlx::migrate!();
@@ -17,41 +33,207 @@ pub static MIGRATOR: Migrator = sqlx::migrate!();
 struct Cli {
     #[command(subcommand)]
     cmd: Cmd,
+    #[command(flatten)]
+    config: ConfigArgs,
 }
 
 /// GSDX command line interface subcommands for running the server or migrations.
-#[derive(Subcommand, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(Subcommand, Debug, Eq, PartialEq, Hash)]
 enum Cmd {
     Migrate,
     Server,
+    /// Inspect configuration
+    Config {
+        #[command(subcommand)]
+        cmd: ConfigCmd,
+    },
+    /// Write stories and their tasks to stdout
+    Export {
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        /// Export only this story
+        #[arg(long)]
+        story: Option<Uuid>,
+    },
+    /// Import stories and their tasks from a file, keeping their ids and timestamps
+    Import {
+        #[arg(long, value_enum)]
+        format: ImportFormat,
+        file: PathBuf,
+        /// Validate the file without importing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Call a running server
+    Client {
+        #[command(flatten)]
+        args: Box<ClientArgs>,
+        #[command(subcommand)]
+        cmd: ClientCmd,
+    },
+    /// Print a shell completion script
+    Completions {
+        shell: Shell,
+    },
+}
+
+/// GSDX configuration subcommands.
+#[derive(Subcommand, Debug, Eq, PartialEq, Hash)]
+enum ConfigCmd {
+    /// Validate and print the effective config, with secrets redacted
+    Check,
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> Result<ExitCode, Box<dyn Error>> {
     // Load env vars and init global logger
     dotenv().ok();
     env_logger::init();
 
-    // Parse command line arguments
+    // Parse command line arguments and run the command provided.
     let cli = Cli::parse();
+    run(cli, |key| env::var(key).ok(), io::stdout().lock()).await
+}
 
-    // Load config from environment variables.
-    let config = Config::load();
-
-    // Load database connection pool.
-    let pool = config.db_pool_opts().connect(&config.db_url).await?;
+/// Run a command, looking up env vars with the given function and writing output to `out`.
+async fn run(
+    cli: Cli,
+    lookup: impl Fn(&str) -> Option<String>,
+    mut out: impl Write,
+) -> Result<ExitCode, Box<dyn Error>> {
+    // Only the commands that use config load it, so the client and completions work without it.
+    // Commands that always use postgres need a database whatever the storage setting.
+    macro_rules! config {
+        () => {
+            config!(false)
+        };
+        (postgres) => {
+            config!(true)
+        };
+        ($postgres:expr) => {
+            match load_config(&cli.config, &lookup, $postgres)? {
+                Some(config) => config,
+                None => return Ok(ExitCode::FAILURE),
+            }
+        };
+    }
 
-    // Run the command provided.
     match cli.cmd {
         Cmd::Migrate => {
+            let config = config!(postgres);
+            let pool = config.db_pool_opts().connect(&config.database.url).await?;
             log::info!("Running migrations");
             MIGRATOR.run(&pool).await?;
         }
         Cmd::Server => {
-            let server = Server::new(pool);
-            server.listen(config.listen_addr).await?;
+            let config = config!();
+            let server = match config.server.storage {
+                Storage::Postgres => {
+                    let pool = config.db_pool_opts().connect(&config.database.url).await?;
+                    Server::new(pool, config)
+                }
+                Storage::Memory => Server::in_memory(config),
+            };
+            server.listen().await?;
+        }
+        Cmd::Config {
+            cmd: ConfigCmd::Check,
+        } => {
+            let config = config!();
+            write!(out, "{}", config.to_redacted_toml()?)?;
+        }
+        Cmd::Export { format, story } => {
+            let config = config!(postgres);
+            let pool = config.db_pool_opts().connect(&config.database.url).await?;
+            let repo = Repo::new(pool);
+            let count = export_stories(&repo, format, story.map(StoryId), out).await?;
+            log::info!("Exported {count} stories");
+        }
+        Cmd::Import {
+            format,
+            file,
+            dry_run,
+        } => {
+            let config = config!(postgres);
+            let input = BufReader::new(File::open(&file)?);
+            let pool = config.db_pool_opts().connect(&config.database.url).await?;
+            let repo = Repo::new(pool);
+            let report = import_stories(&repo, format, input, &config.limits, dry_run).await?;
+            if !report.errors.is_empty() {
+                eprintln!("Invalid import file {}:", file.display());
+                report.errors.iter().for_each(|err| eprintln!("  {err}"));
+                return Ok(ExitCode::FAILURE);
+            }
+            let verb = if dry_run { "Would import" } else { "Imported" };
+            writeln!(
+                out,
+                "{verb} {} stories with {} tasks",
+                report.stories, report.tasks
+            )?;
+        }
+        Cmd::Client { args, cmd } => {
+            if let Err(err) = gsdx::client::run(&args, cmd, out).await {
+                eprintln!("{err}");
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Cmd::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "gsdx", &mut out);
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Load config from file, environment variables and flags, or print the problems and return
+/// `None` when it is invalid or lacks a database the command needs.
+fn load_config(
+    args: &ConfigArgs,
+    lookup: impl Fn(&str) -> Option<String>,
+    postgres: bool,
+) -> Result<Option<Config>, Box<dyn Error>> {
+    let config = Config::load_with(args, lookup).and_then(|config| {
+        if postgres {
+            config.require_database()?;
+        }
+        Ok(config)
+    });
+    match config {
+        Ok(config) => Ok(Some(config)),
+        Err(gsdx::Error::Config { messages }) => {
+            eprintln!("Invalid configuration:");
+            messages.iter().for_each(|m| eprintln!("  - {m}"));
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn completions_without_config() {
+        let cli = Cli::try_parse_from(["gsdx", "completions", "bash"]).unwrap();
+        let mut out = Vec::new();
+        let code = run(cli, |_| None, &mut out).await.unwrap();
+        assert_eq!(code, ExitCode::SUCCESS);
+        assert!(String::from_utf8(out).unwrap().contains("_gsdx()"));
+    }
+
+    #[tokio::test]
+    async fn export_requires_database() {
+        let cli = Cli::try_parse_from(["gsdx", "export", "--format", "json"]).unwrap();
+        let memory = |key: &str| (key == "GSDX_STORAGE").then(|| "memory".to_string());
+        let code = run(cli, memory, Vec::new()).await.unwrap();
+        assert_eq!(code, ExitCode::FAILURE);
+    }
+
+    #[tokio::test]
+    async fn server_requires_config() {
+        let cli = Cli::try_parse_from(["gsdx", "server"]).unwrap();
+        let code = run(cli, |_| None, Vec::new()).await.unwrap();
+        assert_eq!(code, ExitCode::FAILURE);
+    }
 }
@@ -0,0 +1,114 @@
+use super::{CommentRecord, MemoryStore, State};
+use crate::{
+    Error, Result,
+    domain::{Comment, CommentEdit, CommentId, Page, PageParams, TaskId, View},
+    effect::CommentEffects,
+};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+#[async_trait]
+impl CommentEffects for MemoryStore {
+    /// Add a comment to a task
+    async fn add(&self, TaskId(task_id): TaskId, author: String, body: String) -> Result<Comment> {
+        self.write(|state, now| {
+            state.task_record(&task_id)?;
+            state.comment_seqno += 1;
+            let record = CommentRecord {
+                id: Uuid::new_v4(),
+                task_id,
+                author,
+                body,
+                seqno: state.comment_seqno,
+                created_at: now,
+                updated_at: now,
+            };
+            let comment = state.comment(&record, true);
+            state.comments.insert(record.id, record);
+            Ok(comment)
+        })
+    }
+
+    /// Fetch a page of comments for a task
+    async fn list(
+        &self,
+        TaskId(task_id): TaskId,
+        PageParams(cursor, limit): PageParams,
+        view: View,
+    ) -> Result<Page<Comment>> {
+        self.read(|state| {
+            state.task_record(&task_id)?;
+            let mut records: Vec<&CommentRecord> = state
+                .comments
+                .values()
+                .filter(|c| c.task_id == task_id && c.seqno >= cursor)
+                .collect();
+            records.sort_by_key(|c| c.seqno);
+            records.truncate(limit.max(0) as usize);
+            let next_cursor = records.last().map(|c| c.seqno + 1).unwrap_or_default();
+            let comments = records
+                .into_iter()
+                .map(|record| state.comment(record, view.is_full()))
+                .collect();
+            Ok(Page(next_cursor, comments))
+        })
+    }
+
+    /// Replace the body of a comment, if written by the author
+    async fn edit(
+        &self,
+        CommentId(comment_id): CommentId,
+        author: String,
+        body: String,
+    ) -> Result<Comment> {
+        self.write(|state, now| {
+            let record = state.own_comment(&comment_id, &author)?;
+            if record.body == body {
+                return Ok(state.comment(record, true));
+            }
+            let previous = CommentEdit {
+                body: record.body.clone(),
+                replaced_at: now,
+            };
+            state
+                .comment_edits
+                .entry(comment_id)
+                .or_default()
+                .push(previous);
+            if let Some(record) = state.comments.get_mut(&comment_id) {
+                record.body = body;
+                record.updated_at = now;
+            }
+            Ok(state.comment(&state.comments[&comment_id], true))
+        })
+    }
+
+    /// Delete a comment, if written by the author
+    async fn delete(&self, CommentId(comment_id): CommentId, author: String) -> Result<()> {
+        self.write(|state, _| {
+            state.own_comment(&comment_id, &author)?;
+            state.delete_comment(&comment_id);
+            Ok(())
+        })
+    }
+}
+
+// Comment changes on the stored data.
+impl State {
+    /// Look up a comment, ensuring it was written by the author.
+    fn own_comment(&self, comment_id: &Uuid, author: &str) -> Result<&CommentRecord> {
+        let record = self.comment_record(comment_id)?;
+        if record.author != author {
+            return Err(Error::permission_denied(
+                "only the author can change a comment",
+            ));
+        }
+        Ok(record)
+    }
+
+    /// Delete a comment and its edit history.
+    pub(super) fn delete_comment(&mut self, comment_id: &Uuid) {
+        self.comments.remove(comment_id);
+        self.comment_edits.remove(comment_id);
+    }
+}
@@ -0,0 +1,113 @@
+use super::{MemoryStore, State};
+use crate::{
+    Error, Result,
+    domain::{Label, LabelId, LabelTarget, StoryId, TaskId},
+    effect::LabelEffects,
+};
+use async_trait::async_trait;
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+#[async_trait]
+impl LabelEffects for MemoryStore {
+    /// Create a new label
+    async fn create(&self, name: String) -> Result<Label> {
+        self.write(|state, now| {
+            let lower = name.to_lowercase();
+            if state
+                .labels
+                .values()
+                .any(|l| l.name.to_lowercase() == lower)
+            {
+                return Err(Error::already_exists(
+                    "duplicate value for labels_name_index",
+                ));
+            }
+            let label = Label {
+                id: LabelId(Uuid::new_v4()),
+                name,
+                created_at: now,
+                updated_at: now,
+            };
+            state.labels.insert(label.id.0, label.clone());
+            Ok(label)
+        })
+    }
+
+    /// List all labels, by name
+    async fn list(&self) -> Result<Vec<Label>> {
+        self.read(|state| {
+            let mut labels: Vec<Label> = state.labels.values().cloned().collect();
+            labels.sort_by_key(|label| label.name.to_lowercase());
+            Ok(labels)
+        })
+    }
+
+    /// Delete a label, detaching it everywhere
+    async fn delete(&self, LabelId(label_id): LabelId) -> Result<()> {
+        self.write(|state, _| {
+            state
+                .labels
+                .remove(&label_id)
+                .ok_or_else(|| Error::not_found("label", label_id))?;
+            state.story_labels.retain(|(_, id)| *id != label_id);
+            state.task_labels.retain(|(_, id)| *id != label_id);
+            Ok(())
+        })
+    }
+
+    /// Attach labels to a story or task, returning all of its labels
+    async fn add(&self, target: LabelTarget, label_ids: Vec<LabelId>) -> Result<Vec<Label>> {
+        self.write(|state, _| {
+            let owner_id = state.label_owner(&target)?;
+            if let Some(LabelId(missing)) = label_ids
+                .iter()
+                .find(|id| !state.labels.contains_key(&id.0))
+            {
+                return Err(Error::not_found("label", missing));
+            }
+            let links = state.label_links(&target);
+            links.extend(label_ids.into_iter().map(|id| (owner_id, id.0)));
+            Ok(state.linked_labels(state.label_links_ref(&target), &owner_id))
+        })
+    }
+
+    /// Detach labels from a story or task, returning the labels left
+    async fn remove(&self, target: LabelTarget, label_ids: Vec<LabelId>) -> Result<Vec<Label>> {
+        self.write(|state, _| {
+            let owner_id = state.label_owner(&target)?;
+            let links = state.label_links(&target);
+            for LabelId(label_id) in label_ids {
+                links.remove(&(owner_id, label_id));
+            }
+            Ok(state.linked_labels(state.label_links_ref(&target), &owner_id))
+        })
+    }
+}
+
+// Label links for stories and tasks.
+impl State {
+    /// The id of the story or task labels are attached to, failing if it is missing.
+    fn label_owner(&self, target: &LabelTarget) -> Result<Uuid> {
+        match target {
+            LabelTarget::Story(StoryId(story_id)) => self.story_record(story_id).map(|s| s.id),
+            LabelTarget::Task(TaskId(task_id)) => self.task_record(task_id).map(|t| t.id),
+        }
+    }
+
+    /// The label links for the kind of target.
+    fn label_links(&mut self, target: &LabelTarget) -> &mut BTreeSet<(Uuid, Uuid)> {
+        match target {
+            LabelTarget::Story(_) => &mut self.story_labels,
+            LabelTarget::Task(_) => &mut self.task_labels,
+        }
+    }
+
+    /// The label links for the kind of target, read only.
+    fn label_links_ref(&self, target: &LabelTarget) -> &BTreeSet<(Uuid, Uuid)> {
+        match target {
+            LabelTarget::Story(_) => &self.story_labels,
+            LabelTarget::Task(_) => &self.task_labels,
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    Error, Result,
+    domain::{
+        Comment, CommentEdit, CommentId, Label, Priority, Recurrence, Status, Story, StoryId, Task,
+        TaskId, Template,
+    },
+};
+use chrono::{DateTime, SubsecRound, Utc};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+use uuid::Uuid;
+
+mod comment;
+mod label;
+mod search;
+mod story;
+mod task;
+mod template;
+
+/// An in-memory stand-in for the database, implementing every effect trait with the same
+/// semantics as the Postgres backed services. Clones share the same data.
+///
+/// Each change is applied to a copy of the data that replaces the original only if the whole
+/// change succeeds, so multi-step changes are all or nothing like a transaction.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    state: Arc<Mutex<State>>,
+}
+
+impl MemoryStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock the data. A panic while holding the lock cannot leave a change half applied, so a
+    /// poisoned lock is safe to reuse.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Read from a consistent snapshot of the data.
+    fn read<T>(&self, f: impl FnOnce(&State) -> Result<T>) -> Result<T> {
+        f(&self.lock())
+    }
+
+    /// Change the data all or nothing, with a single timestamp for every row touched.
+    fn write<T>(&self, f: impl FnOnce(&mut State, DateTime<Utc>) -> Result<T>) -> Result<T> {
+        let mut state = self.lock();
+        let mut draft = state.clone();
+        let value = f(&mut draft, now())?;
+        *state = draft;
+        Ok(value)
+    }
+}
+
+/// The current time, at the microsecond precision Postgres keeps.
+fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
+}
+
+/// All stored rows. Links between rows are kept as id pairs, like join tables.
+#[derive(Clone, Default)]
+struct State {
+    stories: HashMap<Uuid, StoryRecord>,
+    tasks: HashMap<Uuid, TaskRecord>,
+    labels: HashMap<Uuid, Label>,
+    /// (story id, label id) pairs.
+    story_labels: BTreeSet<(Uuid, Uuid)>,
+    /// (task id, label id) pairs.
+    task_labels: BTreeSet<(Uuid, Uuid)>,
+    /// (task id, blocker id) pairs.
+    dependencies: BTreeSet<(Uuid, Uuid)>,
+    comments: HashMap<Uuid, CommentRecord>,
+    /// Previous comment bodies by comment id, oldest first.
+    comment_edits: HashMap<Uuid, Vec<CommentEdit>>,
+    templates: HashMap<Uuid, TemplateRecord>,
+    story_seqno: i64,
+    comment_seqno: i64,
+    template_seqno: i64,
+}
+
+/// A stored story.
+#[derive(Clone)]
+struct StoryRecord {
+    id: Uuid,
+    name: String,
+    description: String,
+    seqno: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// A stored task.
+#[derive(Clone)]
+struct TaskRecord {
+    id: Uuid,
+    story_id: Uuid,
+    parent_id: Option<Uuid>,
+    name: String,
+    description: String,
+    status: Status,
+    priority: Priority,
+    position: String,
+    due_at: Option<DateTime<Utc>>,
+    assignee: Option<String>,
+    recurrence: Option<Recurrence>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// A stored task comment.
+#[derive(Clone)]
+struct CommentRecord {
+    id: Uuid,
+    task_id: Uuid,
+    author: String,
+    body: String,
+    seqno: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// A stored template, with its creation order.
+#[derive(Clone)]
+struct TemplateRecord {
+    template: Template,
+    seqno: i64,
+}
+
+// Look up rows by id, and map them to domain objects with their linked rows.
+impl State {
+    /// Look up a story, or fail with not found.
+    fn story_record(&self, story_id: &Uuid) -> Result<&StoryRecord> {
+        self.stories
+            .get(story_id)
+            .ok_or_else(|| Error::not_found("story", story_id))
+    }
+
+    /// Look up a task, or fail with not found.
+    fn task_record(&self, task_id: &Uuid) -> Result<&TaskRecord> {
+        self.tasks
+            .get(task_id)
+            .ok_or_else(|| Error::not_found("task", task_id))
+    }
+
+    /// Look up a comment, or fail with not found.
+    fn comment_record(&self, comment_id: &Uuid) -> Result<&CommentRecord> {
+        self.comments
+            .get(comment_id)
+            .ok_or_else(|| Error::not_found("comment", comment_id))
+    }
+
+    /// A story with its labels, and its description if asked for.
+    fn story(&self, record: &StoryRecord, with_description: bool) -> Story {
+        Story {
+            id: StoryId(record.id),
+            name: record.name.clone(),
+            description: with_description.then(|| record.description.clone()),
+            labels: self.linked_labels(&self.story_labels, &record.id),
+            stats: None,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+
+    /// A task with its labels and blockers, and its description if asked for.
+    fn task(&self, record: &TaskRecord, with_description: bool) -> Task {
+        let blocker_ids: Vec<Uuid> = linked_ids(&self.dependencies, &record.id).collect();
+        let blocked = blocker_ids.iter().any(|id| {
+            self.tasks
+                .get(id)
+                .is_some_and(|b| b.status == Status::Incomplete)
+        });
+        Task {
+            id: TaskId(record.id),
+            story_id: StoryId(record.story_id),
+            parent_id: record.parent_id.map(TaskId),
+            name: record.name.clone(),
+            description: with_description.then(|| record.description.clone()),
+            status: record.status,
+            priority: record.priority,
+            position: record.position.clone(),
+            due_at: record.due_at,
+            assignee: record.assignee.clone(),
+            recurrence: record.recurrence,
+            labels: self.linked_labels(&self.task_labels, &record.id),
+            blocker_ids: blocker_ids.into_iter().map(TaskId).collect(),
+            blocked,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+
+    /// A comment, with its edit history if asked for.
+    fn comment(&self, record: &CommentRecord, with_history: bool) -> Comment {
+        let history = self.comment_edits.get(&record.id);
+        Comment {
+            id: CommentId(record.id),
+            task_id: TaskId(record.task_id),
+            author: record.author.clone(),
+            body: record.body.clone(),
+            edited: history.is_some_and(|edits| !edits.is_empty()),
+            history: history
+                .filter(|_| with_history)
+                .cloned()
+                .unwrap_or_default(),
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+
+    /// The labels linked to an owner id, ordered by name ignoring case.
+    fn linked_labels(&self, links: &BTreeSet<(Uuid, Uuid)>, owner_id: &Uuid) -> Vec<Label> {
+        let mut labels: Vec<Label> = linked_ids(links, owner_id)
+            .filter_map(|id| self.labels.get(&id).cloned())
+            .collect();
+        labels.sort_by_key(|label| label.name.to_lowercase());
+        labels
+    }
+
+    /// Delete tasks with their subtasks, comments and links.
+    fn delete_tasks(&mut self, task_ids: &[Uuid]) {
+        let mut doomed: BTreeSet<Uuid> = task_ids.iter().copied().collect();
+        doomed.extend(task_ids.iter().flat_map(|id| self.descendants(id)));
+        self.tasks.retain(|id, _| !doomed.contains(id));
+        self.task_labels.retain(|(id, _)| !doomed.contains(id));
+        self.dependencies
+            .retain(|(id, blocker_id)| !doomed.contains(id) && !doomed.contains(blocker_id));
+        let comment_ids: Vec<Uuid> = self
+            .comments
+            .values()
+            .filter(|c| doomed.contains(&c.task_id))
+            .map(|c| c.id)
+            .collect();
+        for comment_id in comment_ids {
+            self.delete_comment(&comment_id);
+        }
+    }
+}
+
+/// The ids linked to an owner id in a set of pairs, in id order.
+fn linked_ids(links: &BTreeSet<(Uuid, Uuid)>, &owner_id: &Uuid) -> impl Iterator<Item = Uuid> {
+    links
+        .range((owner_id, Uuid::nil())..=(owner_id, Uuid::max()))
+        .map(|&(_, id)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effect::conformance;
+
+    #[tokio::test]
+    async fn story_conformance() {
+        let store = MemoryStore::new();
+        conformance::stories(&store, &store, &store).await;
+    }
+
+    #[tokio::test]
+    async fn task_conformance() {
+        let store = MemoryStore::new();
+        conformance::tasks(&store, &store).await;
+    }
+}
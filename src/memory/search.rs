@@ -0,0 +1,233 @@
+use super::MemoryStore;
+use crate::{
+    Result,
+    domain::{Page, PageParams, SearchHit, SearchQuery, StoryId, TaskId},
+    effect::SearchEffects,
+};
+use async_trait::async_trait;
+use std::cmp::Ordering;
+
+/// Common words left out of queries and documents, like the english text search config does.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "of", "on",
+    "or", "that", "the", "this", "to", "was", "with",
+];
+
+/// Weights for name and description matches, like the `A` and `B` weights of `ts_rank`.
+const NAME_WEIGHT: f32 = 1.0;
+const DESCRIPTION_WEIGHT: f32 = 0.4;
+
+/// The most words in a snippet.
+const SNIPPET_WORDS: usize = 24;
+
+#[async_trait]
+impl SearchEffects for MemoryStore {
+    /// Fetch a page of stories and tasks matching a query, most relevant first
+    async fn search(
+        &self,
+        query: SearchQuery,
+        PageParams(cursor, limit): PageParams,
+    ) -> Result<Page<SearchHit>> {
+        let groups = parse(&query.terms);
+        self.read(|state| {
+            let story_id = query.story_id.as_ref().map(|StoryId(id)| *id);
+            if let Some(story_id) = &story_id {
+                state.story_record(story_id)?;
+            }
+            let stories = state
+                .stories
+                .values()
+                .filter(|s| story_id.is_none_or(|id| id == s.id))
+                .map(|s| (s.id, None, &s.name, &s.description));
+            let tasks = state
+                .tasks
+                .values()
+                .filter(|t| story_id.is_none_or(|id| id == t.story_id))
+                .map(|t| (t.story_id, Some(t.id), &t.name, &t.description));
+            let mut hits: Vec<SearchHit> = stories
+                .chain(tasks)
+                .filter_map(|(story_id, task_id, name, description)| {
+                    let rank = rank(&groups, name, description)?;
+                    Some(SearchHit {
+                        story_id: StoryId(story_id),
+                        task_id: task_id.map(TaskId),
+                        name: name.clone(),
+                        snippet: snippet(&groups, &format!("{name} {description}")),
+                        rank,
+                    })
+                })
+                .collect();
+            hits.sort_by(|a, b| {
+                b.rank
+                    .partial_cmp(&a.rank)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| a.story_id.cmp(&b.story_id))
+                    .then_with(|| a.task_id.cmp(&b.task_id))
+            });
+            let hits: Vec<SearchHit> = hits
+                .into_iter()
+                .skip((cursor - 1).max(0) as usize)
+                .take(limit.max(0) as usize)
+                .collect();
+            let next_cursor = match hits.len() as i64 {
+                len if len == limit => cursor + len,
+                _ => 0,
+            };
+            Ok(Page(next_cursor, hits))
+        })
+    }
+}
+
+/// A search term: a word, or a quoted phrase of words, that must or must not appear.
+#[derive(Debug, PartialEq)]
+struct Term {
+    words: Vec<String>,
+    exclude: bool,
+}
+
+/// Parse web search syntax into alternative groups of terms, where every term in a group must
+/// hold for the group to match.
+fn parse(terms: &str) -> Vec<Vec<Term>> {
+    let mut groups = vec![Vec::new()];
+    let mut rest = terms.trim_start();
+    while !rest.is_empty() {
+        let exclude = rest.starts_with('-');
+        if exclude {
+            rest = &rest[1..];
+        }
+        let text;
+        (text, rest) = match rest.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => rest.split_once(char::is_whitespace).unwrap_or((rest, "")),
+        };
+        rest = rest.trim_start();
+        if !exclude && text.eq_ignore_ascii_case("or") {
+            groups.push(Vec::new());
+            continue;
+        }
+        let words = words(text);
+        if let Some(group) = groups.last_mut()
+            && !words.is_empty()
+        {
+            group.push(Term { words, exclude });
+        }
+    }
+    groups.retain(|group| group.iter().any(|term| !term.exclude));
+    groups
+}
+
+/// The stemmed words of some text, without stop words.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter_map(stem)
+        .collect()
+}
+
+/// Lowercase a word and strip common suffixes, or `None` for stop words. A crude stand-in for
+/// the snowball stemmer, good enough for plurals and simple verb forms.
+fn stem(word: &str) -> Option<String> {
+    let word = word.to_lowercase();
+    if word.is_empty() || STOP_WORDS.contains(&word.as_str()) {
+        return None;
+    }
+    let stem = ["ing", "ed", "es", "s"]
+        .iter()
+        .find_map(|suffix| {
+            word.strip_suffix(suffix)
+                .filter(|stem| stem.chars().count() >= 3)
+        })
+        .unwrap_or(&word);
+    Some(stem.to_string())
+}
+
+/// Count the times a term appears in stemmed words.
+fn occurrences(term: &Term, words: &[String]) -> usize {
+    words
+        .windows(term.words.len())
+        .filter(|window| *window == term.words.as_slice())
+        .count()
+}
+
+/// Rank a name and description against a query, or `None` when they do not match.
+fn rank(groups: &[Vec<Term>], name: &str, description: &str) -> Option<f32> {
+    let (name, description) = (words(name), words(description));
+    let mut best = None;
+    for group in groups {
+        let mut rank = 0.0;
+        let mut matched = true;
+        for term in group {
+            let hits = (occurrences(term, &name), occurrences(term, &description));
+            if term.exclude {
+                matched &= hits == (0, 0);
+            } else {
+                matched &= hits != (0, 0);
+                rank += hits.0 as f32 * NAME_WEIGHT + hits.1 as f32 * DESCRIPTION_WEIGHT;
+            }
+        }
+        if matched && best.is_none_or(|best| rank > best) {
+            best = Some(rank);
+        }
+    }
+    // Scale to be roughly in line with `ts_rank`.
+    best.map(|rank| rank / 10.0)
+}
+
+/// A window of the text around the first match, with matching words wrapped in `**`.
+fn snippet(groups: &[Vec<Term>], text: &str) -> String {
+    let matches = |word: &str| {
+        stem(word).is_some_and(|stem| {
+            groups
+                .iter()
+                .flatten()
+                .any(|term| !term.exclude && term.words.contains(&stem))
+        })
+    };
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let first = words
+        .iter()
+        .position(|word| matches(word.trim_matches(|c: char| !c.is_alphanumeric())))
+        .unwrap_or_default();
+    let start = first.saturating_sub(SNIPPET_WORDS / 4);
+    words
+        .iter()
+        .skip(start)
+        .take(SNIPPET_WORDS)
+        .map(|word| {
+            let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+            if core.is_empty() || !matches(core) {
+                return word.to_string();
+            }
+            word.replacen(core, &format!("**{core}**"), 1)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_web_search_syntax() {
+        let groups = parse(r#"reading "blood meridian" -desert or books"#);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].len(), 3);
+        assert_eq!(groups[0][1].words, vec!["blood", "meridian"]);
+        assert!(groups[0][2].exclude);
+        assert_eq!(groups[1][0].words, vec!["book"]);
+        assert!(parse("the -books").is_empty());
+    }
+
+    #[test]
+    fn rank_and_highlight() {
+        let groups = parse("read");
+        let name = rank(&groups, "Books To Read", "").unwrap();
+        let description = rank(&groups, "Blood Meridian", "A novel about reading").unwrap();
+        assert!(name > description);
+        assert_eq!(rank(&groups, "Blood Meridian", ""), None);
+        assert_eq!(
+            snippet(&groups, "Blood Meridian A novel about reading."),
+            "Blood Meridian A novel about **reading**."
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use super::{MemoryStore, State, StoryRecord, TaskRecord};
+use crate::{
+    Error, Result,
+    domain::{
+        Comparator, ImportSummary, NewStory, PageParams, Status, Story, StoryChanges, StoryExport,
+        StoryExpr, StoryFilter, StoryId, StoryImport, StoryKeyset, StoryPage, StorySort,
+        StorySortField, StorySortValue, StoryStats, TextMatch, View,
+    },
+    effect::StoryEffects,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, SubsecRound, Utc};
+use futures_util::{StreamExt, stream, stream::BoxStream};
+use std::{cmp::Ordering, collections::HashMap};
+use uuid::Uuid;
+
+#[async_trait]
+impl StoryEffects for MemoryStore {
+    /// Create a new story
+    async fn create(&self, story: NewStory) -> Result<Story> {
+        self.write(|state, now| {
+            let record = state.insert_story(Uuid::new_v4(), story.name, story.description, now);
+            Ok(state.story(&record, true))
+        })
+    }
+
+    /// Fetch a story, with task progress if requested
+    async fn get(&self, StoryId(story_id): StoryId, view: View, with_stats: bool) -> Result<Story> {
+        self.read(|state| {
+            let record = state.story_record(&story_id)?;
+            let mut story = state.story(record, view.is_full());
+            if with_stats {
+                story.stats = Some(state.story_stats(record));
+            }
+            Ok(story)
+        })
+    }
+
+    /// Fetch a page of stories in sort order, with task progress if requested
+    async fn list(
+        &self,
+        PageParams(cursor, limit): PageParams,
+        filter: StoryFilter,
+        sort: StorySort,
+        after: Option<StoryKeyset>,
+        view: View,
+        with_stats: bool,
+    ) -> Result<StoryPage> {
+        self.read(|state| {
+            let mut records: Vec<&StoryRecord> = state
+                .stories
+                .values()
+                .filter(|s| after.is_some() || !sort.is_default() || s.seqno >= cursor)
+                .filter(|s| state.has_story_labels(s, &filter))
+                .filter(|s| filter.expr.as_ref().is_none_or(|expr| matches(expr, s)))
+                .collect();
+            if let Some(after) = &after {
+                let position = keyset_position(after)?;
+                records.retain(|s| compare(s, &position, sort) == Ordering::Greater);
+            }
+            records.sort_by(|a, b| compare(a, &SortKey::of(b, sort), sort));
+            records.truncate(limit.max(0) as usize);
+
+            let next_cursor = match records.last() {
+                Some(last) if sort.is_default() => last.seqno + 1,
+                _ => 0,
+            };
+            let next_keyset = match records.last() {
+                Some(last) if records.len() as i64 == limit => Some(StoryKeyset {
+                    sort,
+                    value: match sort.field {
+                        StorySortField::Seqno => StorySortValue::Seqno,
+                        StorySortField::Name => StorySortValue::Name(last.name.clone()),
+                        StorySortField::CreatedAt => StorySortValue::Time(last.created_at),
+                        StorySortField::UpdatedAt => StorySortValue::Time(last.updated_at),
+                    },
+                    seqno: last.seqno,
+                }),
+                _ => None,
+            };
+            let stories = records
+                .into_iter()
+                .map(|record| {
+                    let mut story = state.story(record, view.is_full());
+                    if with_stats {
+                        story.stats = Some(state.story_stats(record));
+                    }
+                    story
+                })
+                .collect();
+            Ok(StoryPage {
+                next_cursor,
+                next_keyset,
+                stories,
+            })
+        })
+    }
+
+    /// Update an existing story
+    async fn update(&self, StoryId(story_id): StoryId, changes: StoryChanges) -> Result<Story> {
+        self.write(|state, now| {
+            let record = state.story_record(&story_id)?;
+            if changes.is_noop(&state.story(record, true)) {
+                log::debug!("Story is the same, skipping update");
+                return Ok(state.story(record, true));
+            }
+            log::debug!("Updating story");
+            let record = state
+                .stories
+                .get_mut(&story_id)
+                .ok_or_else(|| Error::not_found("story", story_id))?;
+            record.name = changes.name;
+            if let Some(description) = changes.description {
+                record.description = description;
+            }
+            record.updated_at = now;
+            let record = record.clone();
+            Ok(state.story(&record, true))
+        })
+    }
+
+    /// Delete an existing story
+    async fn delete(&self, StoryId(story_id): StoryId) -> Result<()> {
+        self.write(|state, _| {
+            state.story_record(&story_id)?;
+            let task_ids: Vec<Uuid> = state
+                .tasks
+                .values()
+                .filter(|t| t.story_id == story_id)
+                .map(|t| t.id)
+                .collect();
+            state.delete_tasks(&task_ids);
+            state.story_labels.retain(|(id, _)| *id != story_id);
+            state.stories.remove(&story_id);
+            Ok(())
+        })
+    }
+
+    /// Copy a story with all of its tasks under a new name, optionally making every task
+    /// incomplete
+    async fn duplicate(
+        &self,
+        StoryId(story_id): StoryId,
+        name: String,
+        reset_statuses: bool,
+    ) -> Result<Story> {
+        self.write(|state, now| {
+            let description = state.story_record(&story_id)?.description.clone();
+            let copy = state.insert_story(Uuid::new_v4(), name, description, now);
+            let label_ids: Vec<Uuid> = super::linked_ids(&state.story_labels, &story_id).collect();
+            for label_id in label_ids {
+                state.story_labels.insert((copy.id, label_id));
+            }
+            state.copy_story_tasks(&story_id, &copy.id, reset_statuses, now);
+            Ok(state.story(&copy, true))
+        })
+    }
+
+    /// Stream all stories, or just one, with their tasks
+    async fn export(
+        &self,
+        story_id: Option<StoryId>,
+    ) -> Result<BoxStream<'static, Result<StoryExport>>> {
+        let exports = self.read(|state| {
+            let mut records: Vec<&StoryRecord> = match &story_id {
+                Some(StoryId(story_id)) => vec![state.story_record(story_id)?],
+                None => state.stories.values().collect(),
+            };
+            records.sort_by_key(|s| s.seqno);
+            let mut tasks: Vec<&TaskRecord> = state.tasks.values().collect();
+            tasks.sort_by(|a, b| a.position.cmp(&b.position));
+            let mut tasks_by_story: HashMap<Uuid, Vec<&TaskRecord>> = HashMap::new();
+            for task in tasks {
+                tasks_by_story.entry(task.story_id).or_default().push(task);
+            }
+            let exports: Vec<StoryExport> = records
+                .into_iter()
+                .map(|record| StoryExport {
+                    story: state.story(record, true),
+                    tasks: tasks_by_story
+                        .remove(&record.id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|task| state.task(task, true))
+                        .collect(),
+                })
+                .collect();
+            Ok(exports)
+        })?;
+        Ok(stream::iter(exports.into_iter().map(Ok)).boxed())
+    }
+
+    /// Import a batch of stories with their tasks, all or nothing
+    async fn import(&self, stories: Vec<StoryImport>) -> Result<ImportSummary> {
+        self.write(|state, now| {
+            let mut summary = ImportSummary::default();
+            for story in stories {
+                let story_id = story.id.map_or_else(Uuid::new_v4, |id| id.0);
+                if state.stories.contains_key(&story_id) {
+                    return Err(Error::already_exists("duplicate value for stories_pkey"));
+                }
+                let created_at = story.created_at.map_or(now, |ts| ts.round_subsecs(6));
+                let updated_at = story
+                    .updated_at
+                    .map_or(created_at, |ts| ts.round_subsecs(6));
+                let mut record = state.insert_story(story_id, story.name, story.description, now);
+                record.created_at = created_at;
+                record.updated_at = updated_at;
+                state.stories.insert(story_id, record);
+                summary.tasks_imported += state.import_tasks(&story_id, story.tasks, now)?;
+                summary.story_ids.push(StoryId(story_id));
+            }
+            Ok(summary)
+        })
+    }
+}
+
+// Story queries and changes on the stored data.
+impl State {
+    /// Insert a new story at the end of the creation order, without labels.
+    pub(super) fn insert_story(
+        &mut self,
+        id: Uuid,
+        name: String,
+        description: String,
+        now: DateTime<Utc>,
+    ) -> StoryRecord {
+        self.story_seqno += 1;
+        let record = StoryRecord {
+            id,
+            name,
+            description,
+            seqno: self.story_seqno,
+            created_at: now,
+            updated_at: now,
+        };
+        self.stories.insert(id, record.clone());
+        record
+    }
+
+    /// Whether a story has all of the labels a filter asks for.
+    fn has_story_labels(&self, story: &StoryRecord, filter: &StoryFilter) -> bool {
+        filter
+            .label_ids
+            .iter()
+            .all(|label_id| self.story_labels.contains(&(story.id, label_id.0)))
+    }
+
+    /// Task progress for a story, with the latest change to it, its tasks or their comments.
+    fn story_stats(&self, story: &StoryRecord) -> StoryStats {
+        let tasks: Vec<&TaskRecord> = self
+            .tasks
+            .values()
+            .filter(|t| t.story_id == story.id)
+            .collect();
+        let complete_tasks = tasks
+            .iter()
+            .filter(|t| t.status == Status::Complete)
+            .count() as i64;
+        let comments = self
+            .comments
+            .values()
+            .filter(|c| tasks.iter().any(|t| t.id == c.task_id))
+            .map(|c| c.updated_at);
+        let last_activity_at = tasks
+            .iter()
+            .map(|t| t.updated_at)
+            .chain(comments)
+            .fold(story.updated_at, DateTime::max);
+        StoryStats {
+            incomplete_tasks: tasks.len() as i64 - complete_tasks,
+            complete_tasks,
+            last_activity_at,
+        }
+    }
+}
+
+/// The sort key of a story, or of the last story on the previous page.
+enum SortKey<'a> {
+    Name(&'a str, i64),
+    Time(DateTime<Utc>, i64),
+    Seqno(i64),
+}
+
+impl<'a> SortKey<'a> {
+    /// The sort key of a story.
+    fn of(story: &'a StoryRecord, sort: StorySort) -> Self {
+        match sort.field {
+            StorySortField::Seqno => Self::Seqno(story.seqno),
+            StorySortField::Name => Self::Name(&story.name, story.seqno),
+            StorySortField::CreatedAt => Self::Time(story.created_at, story.seqno),
+            StorySortField::UpdatedAt => Self::Time(story.updated_at, story.seqno),
+        }
+    }
+}
+
+/// The sort key of the last story on the previous page, if it matches the sort.
+fn keyset_position(after: &StoryKeyset) -> Result<SortKey<'_>> {
+    match (&after.value, after.sort.field) {
+        (StorySortValue::Seqno, StorySortField::Seqno) => Ok(SortKey::Seqno(after.seqno)),
+        (StorySortValue::Name(name), StorySortField::Name) => Ok(SortKey::Name(name, after.seqno)),
+        (StorySortValue::Time(ts), StorySortField::CreatedAt | StorySortField::UpdatedAt) => {
+            Ok(SortKey::Time(*ts, after.seqno))
+        }
+        _ => Err(Error::invalid_args("page_token", "does not match the sort")),
+    }
+}
+
+/// Compare a story with a sort key in sort order, breaking ties by creation order.
+fn compare(story: &StoryRecord, key: &SortKey<'_>, sort: StorySort) -> Ordering {
+    let ordering = match (SortKey::of(story, sort), key) {
+        (SortKey::Name(a, a_seqno), SortKey::Name(b, b_seqno)) => (a, a_seqno).cmp(&(b, *b_seqno)),
+        (SortKey::Time(a, a_seqno), SortKey::Time(b, b_seqno)) => (a, a_seqno).cmp(&(*b, *b_seqno)),
+        (SortKey::Seqno(a), SortKey::Seqno(b)) => a.cmp(b),
+        _ => Ordering::Equal,
+    };
+    if sort.descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+/// Whether a story matches a filter expression.
+fn matches(expr: &StoryExpr, story: &StoryRecord) -> bool {
+    match expr {
+        StoryExpr::And(left, right) => matches(left, story) && matches(right, story),
+        StoryExpr::Or(left, right) => matches(left, story) || matches(right, story),
+        StoryExpr::Not(inner) => !matches(inner, story),
+        StoryExpr::Name(TextMatch::Equals(value)) => story.name == *value,
+        StoryExpr::Name(TextMatch::NotEquals(value)) => story.name != *value,
+        StoryExpr::Name(TextMatch::Has(value)) => like(&story.name, value),
+        StoryExpr::CreatedAt(op, ts) => compares(story.created_at, *op, *ts),
+        StoryExpr::UpdatedAt(op, ts) => compares(story.updated_at, *op, *ts),
+    }
+}
+
+/// Whether a time compares to another as the comparator asks.
+fn compares(value: DateTime<Utc>, op: Comparator, other: DateTime<Utc>) -> bool {
+    match op {
+        Comparator::Eq => value == other,
+        Comparator::Ne => value != other,
+        Comparator::Lt => value < other,
+        Comparator::Le => value <= other,
+        Comparator::Gt => value > other,
+        Comparator::Ge => value >= other,
+    }
+}
+
+/// Match text against a `*` wildcard value ignoring case. Values without wildcards match
+/// anywhere.
+fn like(text: &str, value: &str) -> bool {
+    let text = text.to_lowercase();
+    let value = value.to_lowercase();
+    if !value.contains('*') {
+        return text.contains(&value);
+    }
+    let parts: Vec<&str> = value.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn like_wildcards() {
+        assert!(like("Books To Read", "books*"));
+        assert!(like("Books To Read", "to"));
+        assert!(like("Books To Read", "*t*read"));
+        assert!(!like("Books To Read", "to*"));
+        assert!(!like("Read", "read*read"));
+    }
+}
@@ -0,0 +1,758 @@
+use super::{MemoryStore, State, TaskRecord, linked_ids};
+use crate::{
+    Error, Result,
+    domain::{
+        Keyset, KeysetPage, Limit, NewTask, Placement, Priority, Status, StoryId, TASK_DEPTH_MAX,
+        Task, TaskChanges, TaskFilter, TaskId, TaskImport, TaskOrder, TaskResult, TaskSelection,
+        TimeRange, View, position_between,
+    },
+    effect::TaskEffects,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, SubsecRound, Utc};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use uuid::Uuid;
+
+// Put some reasonable upper limit when listing tasks for a story.
+const MAX_TASKS: usize = 100;
+
+#[async_trait]
+impl TaskEffects for MemoryStore {
+    /// Create a new task
+    async fn create(&self, StoryId(story_id): StoryId, task: NewTask) -> Result<Task> {
+        self.write(|state, now| {
+            let last = state.last_position(&story_id)?;
+            if let Some(TaskId(parent_id)) = &task.parent_id {
+                state.check_parent(None, &story_id, parent_id)?;
+            }
+            let ids = state.insert_new_tasks(&story_id, vec![task], last, now);
+            Ok(state.task(&state.tasks[&ids[0]], true))
+        })
+    }
+
+    /// Create several tasks in order, all or nothing
+    async fn batch_create(
+        &self,
+        StoryId(story_id): StoryId,
+        tasks: Vec<NewTask>,
+    ) -> Result<Vec<Task>> {
+        if tasks.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.write(|state, now| {
+            let last = state.last_position(&story_id)?;
+            let parent_ids: BTreeSet<Uuid> = tasks
+                .iter()
+                .filter_map(|t| t.parent_id.as_ref().map(|id| id.0))
+                .collect();
+            for parent_id in &parent_ids {
+                state.check_parent(None, &story_id, parent_id)?;
+            }
+            let ids = state.insert_new_tasks(&story_id, tasks, last, now);
+            Ok(ids
+                .iter()
+                .map(|id| state.task(&state.tasks[id], true))
+                .collect())
+        })
+    }
+
+    /// Fetch all tasks for a story
+    async fn list(
+        &self,
+        StoryId(story_id): StoryId,
+        sort: TaskOrder,
+        filter: TaskFilter,
+        view: View,
+    ) -> Result<Vec<Task>> {
+        self.read(|state| {
+            state.story_record(&story_id)?;
+            let mut records = state.select_tasks(&story_id, &filter);
+            records.sort_by(|a, b| match sort {
+                TaskOrder::Position => a.position.cmp(&b.position),
+                TaskOrder::Priority => (priority_rank(a.priority), &a.position)
+                    .cmp(&(priority_rank(b.priority), &b.position)),
+                TaskOrder::CreatedAt => {
+                    (a.created_at, &a.position).cmp(&(b.created_at, &b.position))
+                }
+            });
+            records.truncate(MAX_TASKS);
+            Ok(records
+                .into_iter()
+                .map(|record| state.task(record, view.is_full()))
+                .collect())
+        })
+    }
+
+    /// Fetch a page of incomplete tasks due before a time, across all stories
+    async fn list_overdue(
+        &self,
+        due_before: DateTime<Utc>,
+        after: Option<Keyset>,
+        limit: Limit,
+    ) -> Result<KeysetPage<Task>> {
+        self.read(|state| {
+            let page = state.keyset_page(after, limit, |t| {
+                let due_at = t.due_at.filter(|due_at| *due_at < due_before)?;
+                (t.status == Status::Incomplete).then_some(due_at)
+            });
+            Ok(page)
+        })
+    }
+
+    /// Fetch a page of tasks assigned to a user across all stories, optionally with a status
+    async fn list_by_assignee(
+        &self,
+        assignee: String,
+        status: Option<Status>,
+        after: Option<Keyset>,
+        limit: Limit,
+    ) -> Result<KeysetPage<Task>> {
+        self.read(|state| {
+            let page = state.keyset_page(after, limit, |t| {
+                let assigned = t.assignee.as_ref() == Some(&assignee);
+                let status = status.is_none_or(|status| t.status == status);
+                (assigned && status).then_some(t.created_at)
+            });
+            Ok(page)
+        })
+    }
+
+    /// Set or clear the assignee of a task
+    async fn assign(&self, TaskId(task_id): TaskId, assignee: Option<String>) -> Result<Task> {
+        self.write(|state, now| {
+            let record = state.task_record_mut(&task_id)?;
+            record.assignee = assignee;
+            record.updated_at = now;
+            Ok(state.task(&state.tasks[&task_id], true))
+        })
+    }
+
+    /// Update an existing task, returning the next occurrence too when completing a recurring task
+    async fn update(
+        &self,
+        TaskId(task_id): TaskId,
+        changes: TaskChanges,
+    ) -> Result<(Task, Option<Task>)> {
+        self.write(|state, now| {
+            let task = state.task(state.task_record(&task_id)?, true);
+            let force = changes.force;
+            state.update_task(changes.apply(task), force, now)
+        })
+    }
+
+    /// Set the status of many tasks at once, all or nothing
+    async fn batch_update_status(
+        &self,
+        selection: TaskSelection,
+        status: Status,
+        force: bool,
+    ) -> Result<Vec<TaskResult>> {
+        self.write(|state, now| {
+            let selected: Vec<Uuid> = match selection {
+                TaskSelection::Ids(ids) => ids.into_iter().map(|id| id.0).collect(),
+                TaskSelection::Story(StoryId(story_id), filter) => {
+                    state.story_record(&story_id)?;
+                    let mut records = state.select_tasks(&story_id, &filter);
+                    records.sort_by(|a, b| a.position.cmp(&b.position));
+                    records.into_iter().map(|t| t.id).collect()
+                }
+            };
+            let found: Vec<Uuid> = selected
+                .iter()
+                .filter(|id| state.tasks.contains_key(id))
+                .copied()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+
+            if status == Status::Complete {
+                let blocked = state.count_blocked(&found);
+                if blocked > 0 {
+                    return Err(Error::failed_precondition(format!(
+                        "{blocked} tasks are blocked by incomplete tasks"
+                    )));
+                }
+                let recurring = state.count_recurring(&found);
+                if recurring > 0 {
+                    return Err(Error::failed_precondition(format!(
+                        "{recurring} tasks recur; complete them one at a time"
+                    )));
+                }
+                state.complete_subtasks(&found, force, now)?;
+            }
+            for id in &found {
+                state.set_status(id, status, now);
+            }
+            if status == Status::Incomplete {
+                state.reopen_ancestors(&found, now);
+            }
+
+            let mut tasks: HashMap<Uuid, Task> = found
+                .iter()
+                .map(|id| (*id, state.task(&state.tasks[id], true)))
+                .collect();
+            Ok(selected
+                .into_iter()
+                .map(|id| TaskResult {
+                    task_id: TaskId(id),
+                    task: tasks.remove(&id),
+                })
+                .collect())
+        })
+    }
+
+    /// Move a task before or after a sibling task
+    async fn reorder(&self, TaskId(task_id): TaskId, placement: Placement) -> Result<Task> {
+        self.write(|state, now| {
+            let story_id = state.task_record(&task_id)?.story_id;
+            let (field, TaskId(anchor_id)) = match &placement {
+                Placement::Before(id) => ("before_task_id", id),
+                Placement::After(id) => ("after_task_id", id),
+            };
+            if *anchor_id == task_id {
+                return Err(Error::invalid_args(
+                    field,
+                    "cannot place a task relative to itself",
+                ));
+            }
+            let anchor = state
+                .tasks
+                .get(anchor_id)
+                .filter(|t| t.story_id == story_id)
+                .map(|t| t.position.clone())
+                .ok_or_else(|| Error::invalid_args(field, "must be a task in the same story"))?;
+            let siblings = state
+                .tasks
+                .values()
+                .filter(|t| t.story_id == story_id && t.id != task_id)
+                .map(|t| t.position.as_str());
+            let position = match placement {
+                Placement::Before(_) => {
+                    let prev = siblings.filter(|p| *p < anchor.as_str()).max();
+                    position_between(prev, Some(&anchor))
+                }
+                Placement::After(_) => {
+                    let next = siblings.filter(|p| *p > anchor.as_str()).min();
+                    position_between(Some(&anchor), next)
+                }
+            };
+            let record = state.task_record_mut(&task_id)?;
+            record.position = position;
+            record.updated_at = now;
+            Ok(state.task(&state.tasks[&task_id], true))
+        })
+    }
+
+    /// Make a task wait on a blocker task
+    async fn add_dependency(
+        &self,
+        TaskId(task_id): TaskId,
+        TaskId(blocker_id): TaskId,
+    ) -> Result<Task> {
+        self.write(|state, _| {
+            if task_id == blocker_id {
+                return Err(Error::invalid_args(
+                    "blocker_task_id",
+                    "a task cannot depend on itself",
+                ));
+            }
+            // Walk everything the blocker waits on; finding the task means a cycle.
+            let mut upstream = HashSet::new();
+            let mut pending = vec![blocker_id];
+            while let Some(id) = pending.pop() {
+                for next in linked_ids(&state.dependencies, &id) {
+                    if upstream.insert(next) {
+                        pending.push(next);
+                    }
+                }
+            }
+            if upstream.contains(&task_id) {
+                return Err(Error::invalid_args(
+                    "blocker_task_id",
+                    "would create a dependency cycle",
+                ));
+            }
+            state.task_record(&blocker_id)?;
+            state.task_record(&task_id)?;
+            state.dependencies.insert((task_id, blocker_id));
+            Ok(state.task(&state.tasks[&task_id], true))
+        })
+    }
+
+    /// Stop a task waiting on a blocker task
+    async fn remove_dependency(
+        &self,
+        TaskId(task_id): TaskId,
+        TaskId(blocker_id): TaskId,
+    ) -> Result<Task> {
+        self.write(|state, _| {
+            state.task_record(&task_id)?;
+            state.dependencies.remove(&(task_id, blocker_id));
+            Ok(state.task(&state.tasks[&task_id], true))
+        })
+    }
+
+    /// Delete an existing task.
+    async fn delete(&self, TaskId(task_id): TaskId) -> Result<()> {
+        self.write(|state, _| {
+            state.task_record(&task_id)?;
+            state.delete_tasks(&[task_id]);
+            Ok(())
+        })
+    }
+}
+
+// Task queries and changes on the stored data.
+impl State {
+    /// Look up a task to change, or fail with not found.
+    fn task_record_mut(&mut self, task_id: &Uuid) -> Result<&mut TaskRecord> {
+        self.tasks
+            .get_mut(task_id)
+            .ok_or_else(|| Error::not_found("task", task_id))
+    }
+
+    /// The last task position in a story, failing if the story is missing.
+    fn last_position(&self, story_id: &Uuid) -> Result<Option<String>> {
+        self.story_record(story_id)?;
+        let last = self
+            .tasks
+            .values()
+            .filter(|t| t.story_id == *story_id)
+            .map(|t| &t.position)
+            .max()
+            .cloned();
+        Ok(last)
+    }
+
+    /// The tasks in a story matching a filter, in no particular order.
+    fn select_tasks(&self, story_id: &Uuid, filter: &TaskFilter) -> Vec<&TaskRecord> {
+        let in_range = |ts: DateTime<Utc>, range: &TimeRange| {
+            range.after.is_none_or(|after| ts >= after)
+                && range.before.is_none_or(|before| ts < before)
+        };
+        self.tasks
+            .values()
+            .filter(|t| t.story_id == *story_id)
+            .filter(|t| {
+                filter
+                    .label_ids
+                    .iter()
+                    .all(|label_id| self.task_labels.contains(&(t.id, label_id.0)))
+            })
+            .filter(|t| filter.statuses.is_empty() || filter.statuses.contains(&t.status))
+            .filter(|t| in_range(t.created_at, &filter.created))
+            .filter(|t| in_range(t.updated_at, &filter.updated))
+            .collect()
+    }
+
+    /// A page of tasks across all stories, ordered by a time key and id. Tasks without a key are
+    /// left out. Descriptions are not loaded.
+    fn keyset_page(
+        &self,
+        after: Option<Keyset>,
+        limit: Limit,
+        key: impl Fn(&TaskRecord) -> Option<DateTime<Utc>>,
+    ) -> KeysetPage<Task> {
+        let mut keyed: Vec<(Keyset, &TaskRecord)> = self
+            .tasks
+            .values()
+            .filter_map(|t| key(t).map(|ts| (Keyset(ts, t.id), t)))
+            .filter(|(keyset, _)| after.is_none_or(|after| *keyset > after))
+            .collect();
+        keyed.sort_by_key(|(keyset, _)| *keyset);
+        keyed.truncate(limit.max(0) as usize);
+        let next = match keyed.last() {
+            Some((keyset, _)) if keyed.len() as Limit == limit => Some(*keyset),
+            _ => None,
+        };
+        let tasks = keyed
+            .into_iter()
+            .map(|(_, record)| self.task(record, false))
+            .collect();
+        KeysetPage(next, tasks)
+    }
+
+    /// Insert new tasks after the given last position of a story, reopening the ancestors of
+    /// incomplete subtasks. Ids are returned in input order.
+    pub(super) fn insert_new_tasks(
+        &mut self,
+        story_id: &Uuid,
+        tasks: Vec<NewTask>,
+        mut last: Option<String>,
+        now: DateTime<Utc>,
+    ) -> Vec<Uuid> {
+        let mut ids = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let position = position_between(last.as_deref(), None);
+            let record = TaskRecord {
+                id: Uuid::new_v4(),
+                story_id: *story_id,
+                parent_id: task.parent_id.map(|id| id.0),
+                name: task.name,
+                description: task.description,
+                status: task.status,
+                priority: task.priority,
+                position: position.clone(),
+                due_at: task.due_at,
+                assignee: None,
+                recurrence: task.recurrence,
+                created_at: now,
+                updated_at: now,
+            };
+            last = Some(position);
+            ids.push(record.id);
+            self.tasks.insert(record.id, record);
+        }
+        let incomplete: Vec<Uuid> = ids
+            .iter()
+            .filter(|id| self.tasks[*id].status == Status::Incomplete)
+            .copied()
+            .collect();
+        self.reopen_ancestors(&incomplete, now);
+        ids
+    }
+
+    /// Insert the imported tasks of a new story in order, keeping any ids and timestamps given,
+    /// and reopening the ancestors of incomplete subtasks. Returns the number inserted.
+    pub(super) fn import_tasks(
+        &mut self,
+        story_id: &Uuid,
+        tasks: Vec<TaskImport>,
+        now: DateTime<Utc>,
+    ) -> Result<u64> {
+        let mut last = None;
+        let mut count = 0;
+        let mut incomplete = Vec::new();
+        for import in tasks {
+            let id = import.id.map_or_else(Uuid::new_v4, |id| id.0);
+            if self.tasks.contains_key(&id) {
+                return Err(Error::already_exists("duplicate value for tasks_pkey"));
+            }
+            let parent_id = import.task.parent_id.map(|id| id.0);
+            if let Some(parent_id) = parent_id
+                && self
+                    .tasks
+                    .get(&parent_id)
+                    .is_none_or(|p| p.story_id != *story_id)
+            {
+                return Err(Error::not_found("task", parent_id));
+            }
+            let position = position_between(last.as_deref(), None);
+            let created_at = import.created_at.map_or(now, |ts| ts.round_subsecs(6));
+            let record = TaskRecord {
+                id,
+                story_id: *story_id,
+                parent_id,
+                name: import.task.name,
+                description: import.task.description,
+                status: import.task.status,
+                priority: import.task.priority,
+                position: position.clone(),
+                due_at: import.task.due_at,
+                assignee: import.assignee,
+                recurrence: import.task.recurrence,
+                created_at,
+                updated_at: import
+                    .updated_at
+                    .map_or(created_at, |ts| ts.round_subsecs(6)),
+            };
+            last = Some(position);
+            if parent_id.is_some() && record.status == Status::Incomplete {
+                incomplete.push(id);
+            }
+            self.tasks.insert(id, record);
+            count += 1;
+        }
+        self.reopen_ancestors(&incomplete, now);
+        Ok(count)
+    }
+
+    /// Update the mutable fields of a task.
+    ///
+    /// A task cannot be completed while it waits on an incomplete blocker. Completion rolls down
+    /// to subtasks, which must already be complete unless forced. An incomplete task rolls up,
+    /// reopening its ancestors.
+    ///
+    /// Completing a recurring task hands its rule on to the next occurrence, which is created
+    /// at the end of the story and returned too, unless the recurrence is over.
+    fn update_task(
+        &mut self,
+        task: Task,
+        force: bool,
+        now: DateTime<Utc>,
+    ) -> Result<(Task, Option<Task>)> {
+        let TaskId(task_id) = task.id;
+        let StoryId(story_id) = task.story_id;
+        let last = self.last_position(&story_id)?;
+        if let Some(TaskId(parent_id)) = &task.parent_id {
+            self.check_parent(Some(&task_id), &story_id, parent_id)?;
+        }
+        let previous = self.task_record(&task_id)?.status;
+        let completed = task.status == Status::Complete && previous != task.status;
+        if completed && self.count_blocked(&[task_id]) > 0 {
+            return Err(Error::failed_precondition(
+                "task is blocked by incomplete tasks",
+            ));
+        }
+        if task.status == Status::Complete {
+            self.complete_subtasks(&[task_id], force, now)?;
+        }
+        let record = self.task_record_mut(&task_id)?;
+        record.parent_id = task.parent_id.map(|id| id.0);
+        record.name = task.name;
+        if let Some(description) = task.description {
+            record.description = description;
+        }
+        record.status = task.status;
+        record.priority = task.priority;
+        record.due_at = task.due_at;
+        record.recurrence = task.recurrence.filter(|_| !completed);
+        record.updated_at = now;
+        let due_at = record.due_at.unwrap_or(record.updated_at);
+        if task.status == Status::Incomplete {
+            self.reopen_ancestors(&[task_id], now);
+        }
+        let next = match task.recurrence.filter(|_| completed) {
+            Some(rule) => rule.next(due_at).map(|(next_due_at, next_rule)| {
+                let mut next = self.tasks[&task_id].clone();
+                next.id = Uuid::new_v4();
+                next.status = Status::Incomplete;
+                next.position = position_between(last.as_deref(), None);
+                next.due_at = Some(next_due_at);
+                next.recurrence = Some(next_rule);
+                next.created_at = now;
+                next.updated_at = now;
+                let next_id = next.id;
+                self.tasks.insert(next_id, next);
+                let label_ids: Vec<Uuid> = linked_ids(&self.task_labels, &task_id).collect();
+                for label_id in label_ids {
+                    self.task_labels.insert((next_id, label_id));
+                }
+                self.reopen_ancestors(&[next_id], now);
+                next_id
+            }),
+            None => None,
+        };
+        let task = self.task(&self.tasks[&task_id], true);
+        let next = next.map(|next_id| self.task(&self.tasks[&next_id], true));
+        Ok((task, next))
+    }
+
+    /// Set the status of a task, if it is different.
+    fn set_status(&mut self, task_id: &Uuid, status: Status, now: DateTime<Utc>) {
+        if let Some(record) = self.tasks.get_mut(task_id)
+            && record.status != status
+        {
+            record.status = status;
+            record.updated_at = now;
+        }
+    }
+
+    /// Ensure a task can be placed under a parent: the parent must be in the same story, must not
+    /// be the task or one of its subtasks, and the result cannot be nested too deep.
+    fn check_parent(
+        &self,
+        task_id: Option<&Uuid>,
+        story_id: &Uuid,
+        parent_id: &Uuid,
+    ) -> Result<()> {
+        if self.task_record(parent_id)?.story_id != *story_id {
+            return Err(Error::invalid_args(
+                "parent_task_id",
+                "must be a task in the same story",
+            ));
+        }
+        let ancestors = self.ancestors(parent_id);
+        let height = match task_id {
+            Some(task_id) => {
+                if ancestors.contains(task_id) {
+                    return Err(Error::invalid_args(
+                        "parent_task_id",
+                        "cannot be the task itself or one of its subtasks",
+                    ));
+                }
+                self.subtree_height(task_id)
+            }
+            None => 1,
+        };
+        if ancestors.len() as i32 + height > TASK_DEPTH_MAX {
+            return Err(Error::invalid_args(
+                "parent_task_id",
+                format!("subtasks cannot be nested more than {TASK_DEPTH_MAX} levels deep"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// A task and its ancestors, nearest first.
+    fn ancestors(&self, task_id: &Uuid) -> Vec<Uuid> {
+        let mut ancestors = Vec::new();
+        let mut next = Some(*task_id);
+        while let Some(id) = next.filter(|id| !ancestors.contains(id)) {
+            ancestors.push(id);
+            next = self.tasks.get(&id).and_then(|t| t.parent_id);
+        }
+        ancestors
+    }
+
+    /// The subtasks of a task, and theirs, in no particular order.
+    pub(super) fn descendants(&self, task_id: &Uuid) -> Vec<Uuid> {
+        let mut descendants = Vec::new();
+        let mut pending = vec![*task_id];
+        while let Some(id) = pending.pop() {
+            for child in self.tasks.values().filter(|t| t.parent_id == Some(id)) {
+                descendants.push(child.id);
+                pending.push(child.id);
+            }
+        }
+        descendants
+    }
+
+    /// The number of levels in a task's subtree, counting the task itself.
+    fn subtree_height(&self, task_id: &Uuid) -> i32 {
+        self.tasks
+            .values()
+            .filter(|t| t.parent_id == Some(*task_id))
+            .map(|child| self.subtree_height(&child.id) + 1)
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Roll completion down to the subtasks of tasks being completed: fail if any outside the
+    /// completed tasks are incomplete, unless forced, in which case they are completed too.
+    fn complete_subtasks(
+        &mut self,
+        task_ids: &[Uuid],
+        force: bool,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let incomplete: Vec<Uuid> = task_ids
+            .iter()
+            .flat_map(|id| self.descendants(id))
+            .filter(|id| !task_ids.contains(id) && self.tasks[id].status == Status::Incomplete)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if incomplete.is_empty() {
+            return Ok(());
+        }
+        if !force {
+            let subject = if task_ids.len() == 1 {
+                "task has"
+            } else {
+                "tasks have"
+            };
+            return Err(Error::failed_precondition(format!(
+                "{subject} {} incomplete subtasks; force completion to complete them too",
+                incomplete.len()
+            )));
+        }
+        let completing: Vec<Uuid> = task_ids.iter().chain(&incomplete).copied().collect();
+        let blocked = self.count_blocked(&completing);
+        if blocked > 0 {
+            return Err(Error::failed_precondition(format!(
+                "{blocked} subtasks are blocked by incomplete tasks"
+            )));
+        }
+        let recurring = self.count_recurring(&incomplete);
+        if recurring > 0 {
+            return Err(Error::failed_precondition(format!(
+                "{recurring} subtasks recur; complete them one at a time"
+            )));
+        }
+        for id in &incomplete {
+            self.set_status(id, Status::Complete, now);
+        }
+        Ok(())
+    }
+
+    /// Roll incomplete tasks up to their ancestors, reopening any that were complete.
+    fn reopen_ancestors(&mut self, task_ids: &[Uuid], now: DateTime<Utc>) {
+        let ancestors: BTreeSet<Uuid> = task_ids
+            .iter()
+            .flat_map(|id| self.ancestors(id))
+            .filter(|id| !task_ids.contains(id))
+            .collect();
+        for id in &ancestors {
+            self.set_status(id, Status::Incomplete, now);
+        }
+    }
+
+    /// Count the incomplete tasks among those being completed that have a recurrence rule.
+    fn count_recurring(&self, task_ids: &[Uuid]) -> usize {
+        task_ids
+            .iter()
+            .map(|id| &self.tasks[id])
+            .filter(|t| t.status == Status::Incomplete && t.recurrence.is_some())
+            .count()
+    }
+
+    /// Count the incomplete tasks among those being completed that wait on an incomplete blocker
+    /// outside of them.
+    fn count_blocked(&self, task_ids: &[Uuid]) -> usize {
+        task_ids
+            .iter()
+            .filter(|id| self.tasks[*id].status == Status::Incomplete)
+            .filter(|id| {
+                linked_ids(&self.dependencies, id).any(|blocker_id| {
+                    !task_ids.contains(&blocker_id)
+                        && self.tasks[&blocker_id].status == Status::Incomplete
+                })
+            })
+            .count()
+    }
+
+    /// Copy every task of a story into another story, with their labels and dependencies. Parents
+    /// and blockers within the story point at their copies; blockers elsewhere are shared.
+    pub(super) fn copy_story_tasks(
+        &mut self,
+        from_story_id: &Uuid,
+        to_story_id: &Uuid,
+        reset_statuses: bool,
+        now: DateTime<Utc>,
+    ) {
+        let copies: HashMap<Uuid, Uuid> = self
+            .tasks
+            .values()
+            .filter(|t| t.story_id == *from_story_id)
+            .map(|t| (t.id, Uuid::new_v4()))
+            .collect();
+        for (old_id, new_id) in &copies {
+            let mut copy = self.tasks[old_id].clone();
+            copy.id = *new_id;
+            copy.story_id = *to_story_id;
+            copy.parent_id = copy.parent_id.and_then(|id| copies.get(&id).copied());
+            if reset_statuses {
+                copy.status = Status::Incomplete;
+            }
+            copy.created_at = now;
+            copy.updated_at = now;
+            self.tasks.insert(*new_id, copy);
+        }
+        let labels: Vec<(Uuid, Uuid)> = self
+            .task_labels
+            .iter()
+            .filter_map(|(id, label_id)| copies.get(id).map(|new_id| (*new_id, *label_id)))
+            .collect();
+        self.task_labels.extend(labels);
+        let dependencies: Vec<(Uuid, Uuid)> = self
+            .dependencies
+            .iter()
+            .filter_map(|(id, blocker_id)| {
+                let blocker_id = copies.get(blocker_id).unwrap_or(blocker_id);
+                copies.get(id).map(|new_id| (*new_id, *blocker_id))
+            })
+            .collect();
+        self.dependencies.extend(dependencies);
+    }
+}
+
+/// The rank of a priority when listing by priority, most urgent first.
+fn priority_rank(priority: Priority) -> u8 {
+    match priority {
+        Priority::Urgent => 0,
+        Priority::High => 1,
+        Priority::Medium => 2,
+        Priority::Low => 3,
+    }
+}
@@ -0,0 +1,82 @@
+use super::{MemoryStore, TemplateRecord};
+use crate::{
+    Error, Result,
+    domain::{NewTask, NewTemplate, Page, PageParams, Status, Story, Task, Template, TemplateId},
+    effect::TemplateEffects,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+#[async_trait]
+impl TemplateEffects for MemoryStore {
+    /// Create a new template
+    async fn create(&self, template: NewTemplate) -> Result<Template> {
+        self.write(|state, now| {
+            if state
+                .templates
+                .values()
+                .any(|t| t.template.name == template.name)
+            {
+                return Err(Error::already_exists(
+                    "duplicate value for story_templates_name_index",
+                ));
+            }
+            state.template_seqno += 1;
+            let template = Template {
+                id: TemplateId(Uuid::new_v4()),
+                name: template.name,
+                name_pattern: template.name_pattern,
+                task_names: template.task_names,
+                created_at: now,
+                updated_at: now,
+            };
+            let record = TemplateRecord {
+                template: template.clone(),
+                seqno: state.template_seqno,
+            };
+            state.templates.insert(template.id.0, record);
+            Ok(template)
+        })
+    }
+
+    /// Fetch a page of templates
+    async fn list(&self, PageParams(cursor, limit): PageParams) -> Result<Page<Template>> {
+        self.read(|state| {
+            let mut records: Vec<&TemplateRecord> = state
+                .templates
+                .values()
+                .filter(|t| t.seqno >= cursor)
+                .collect();
+            records.sort_by_key(|t| t.seqno);
+            records.truncate(limit.max(0) as usize);
+            let next_cursor = records.last().map(|t| t.seqno + 1).unwrap_or_default();
+            let templates = records.into_iter().map(|t| t.template.clone()).collect();
+            Ok(Page(next_cursor, templates))
+        })
+    }
+
+    /// Create a story and its tasks from a template, filling in placeholders for today (UTC)
+    async fn instantiate(&self, TemplateId(template_id): TemplateId) -> Result<(Story, Vec<Task>)> {
+        let today = Utc::now().date_naive();
+        self.write(|state, now| {
+            let template = state
+                .templates
+                .get(&template_id)
+                .map(|t| &t.template)
+                .ok_or_else(|| Error::not_found("template", template_id))?;
+            let (name, task_names) = template.render(today).map_err(Error::internal)?;
+            let story = state.insert_story(Uuid::new_v4(), name, String::new(), now);
+            let tasks = task_names
+                .into_iter()
+                .map(|name| NewTask::new(name, Status::Incomplete))
+                .collect();
+            let ids = state.insert_new_tasks(&story.id, tasks, None, now);
+            let tasks = ids
+                .iter()
+                .map(|id| state.task(&state.tasks[id], true))
+                .collect();
+            Ok((state.story(&story, true), tasks))
+        })
+    }
+}
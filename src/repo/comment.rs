@@ -0,0 +1,254 @@
+use super::Repo;
+use crate::{
+    Error, Result,
+    domain::{Comment, CommentEdit, CommentId, Page, PageParams, TaskId, View},
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// The comment entity object - used for query validation against the database.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct CommentEntity {
+    id: Uuid,
+    task_id: Uuid,
+    author: String,
+    body: String,
+    edited: bool,
+    seqno: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+// The repo should map the entity to the domain object in public functions.
+impl From<CommentEntity> for Comment {
+    fn from(entity: CommentEntity) -> Self {
+        Self {
+            id: CommentId(entity.id),
+            task_id: TaskId(entity.task_id),
+            author: entity.author,
+            body: entity.body,
+            edited: entity.edited,
+            history: Vec::new(),
+            created_at: entity.created_at,
+            updated_at: entity.updated_at,
+        }
+    }
+}
+
+/// A previous comment body, tagged with its comment id.
+struct CommentEditEntity {
+    comment_id: Uuid,
+    body: String,
+    replaced_at: DateTime<Utc>,
+}
+
+// Extend repo with queries related to task comments.
+impl Repo {
+    /// Select a comment by id, with its edit history.
+    pub async fn fetch_comment(&self, &CommentId(comment_id): &CommentId) -> Result<Comment> {
+        let query = sqlx::query_as!(
+            CommentEntity,
+            r#"SELECT id, task_id, author, body,
+              EXISTS(SELECT 1 FROM task_comment_edits e WHERE e.comment_id = c.id) AS "edited!",
+              seqno, created_at, updated_at
+            FROM task_comments c WHERE id = $1"#,
+            comment_id
+        );
+        let comment = query
+            .fetch_optional(self.db_ref())
+            .await?
+            .map(Comment::from)
+            .ok_or_else(|| Error::not_found("comment", comment_id))?;
+        let mut comments = self.with_comment_history(vec![comment]).await?;
+        Ok(comments.remove(0))
+    }
+
+    /// Select a page of comments for a task, oldest first. Edit history is only loaded in the
+    /// full view.
+    pub async fn list_comments(
+        &self,
+        &TaskId(task_id): &TaskId,
+        PageParams(cursor, limit): PageParams,
+        view: View,
+    ) -> Result<Page<Comment>> {
+        let query = sqlx::query_as!(
+            CommentEntity,
+            r#"SELECT id, task_id, author, body,
+              EXISTS(SELECT 1 FROM task_comment_edits e WHERE e.comment_id = c.id) AS "edited!",
+              seqno, created_at, updated_at
+            FROM task_comments c WHERE task_id = $1 AND seqno >= $2
+            ORDER BY seqno LIMIT $3"#,
+            task_id,
+            cursor,
+            limit,
+        );
+        let entities = query.fetch_all(self.db_ref()).await?;
+        let next_cursor = entities.last().map(|c| c.seqno + 1).unwrap_or_default();
+        let comments = entities.into_iter().map(Comment::from).collect();
+        let comments = if view.is_full() {
+            self.with_comment_history(comments).await?
+        } else {
+            comments
+        };
+        Ok(Page(next_cursor, comments))
+    }
+
+    /// Insert a new comment on a task.
+    pub async fn create_comment(
+        &self,
+        &TaskId(task_id): &TaskId,
+        author: &str,
+        body: &str,
+    ) -> Result<Comment> {
+        let query = sqlx::query_as!(
+            CommentEntity,
+            r#"INSERT INTO task_comments (task_id, author, body) VALUES ($1, $2, $3)
+            RETURNING id, task_id, author, body, false AS "edited!", seqno, created_at, updated_at"#,
+            task_id,
+            author,
+            body,
+        );
+        let entity = query.fetch_one(self.db_ref()).await?;
+        Ok(Comment::from(entity))
+    }
+
+    /// Replace the body of a comment, keeping the previous body in its history.
+    pub async fn update_comment(
+        &self,
+        &CommentId(comment_id): &CommentId,
+        body: &str,
+    ) -> Result<Comment> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(
+            r#"INSERT INTO task_comment_edits (comment_id, body)
+            SELECT id, body FROM task_comments WHERE id = $1 FOR UPDATE"#,
+            comment_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE task_comments SET body = $1 WHERE id = $2",
+            body,
+            comment_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.fetch_comment(&CommentId(comment_id)).await
+    }
+
+    /// Delete a comment and its edit history.
+    pub async fn delete_comment(&self, &CommentId(comment_id): &CommentId) -> Result<()> {
+        sqlx::query!("DELETE FROM task_comments WHERE id = $1", comment_id)
+            .execute(self.db_ref())
+            .await?;
+        Ok(())
+    }
+
+    /// Fill in edit history for a batch of comments with a single query.
+    async fn with_comment_history(&self, mut comments: Vec<Comment>) -> Result<Vec<Comment>> {
+        let ids: Vec<Uuid> = comments
+            .iter()
+            .filter(|c| c.edited)
+            .map(|c| c.id.0)
+            .collect();
+        if ids.is_empty() {
+            return Ok(comments);
+        }
+        let query = sqlx::query_as!(
+            CommentEditEntity,
+            r#"SELECT comment_id, body, replaced_at FROM task_comment_edits
+            WHERE comment_id = ANY($1) ORDER BY replaced_at, id"#,
+            &ids,
+        );
+        let mut history: HashMap<Uuid, Vec<CommentEdit>> = HashMap::new();
+        for entity in query.fetch_all(self.db_ref()).await? {
+            history
+                .entry(entity.comment_id)
+                .or_default()
+                .push(CommentEdit {
+                    body: entity.body,
+                    replaced_at: entity.replaced_at,
+                });
+        }
+        for comment in &mut comments {
+            comment.history = history.remove(&comment.id.0).unwrap_or_default();
+        }
+        Ok(comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{NewStory, NewTask, Status},
+        repo::tests,
+    };
+
+    use testcontainers::{ImageExt, runners::AsyncRunner};
+    use testcontainers_modules::postgres::Postgres;
+
+    #[ignore]
+    #[tokio::test]
+    async fn integration_test() {
+        // Set up postgres test container backed repo
+        let image = Postgres::default().with_tag("17-alpine");
+        let container = image.start().await.unwrap();
+        let pool = tests::setup_pg_pool(&container).await;
+        let repo = Repo::new(pool);
+
+        // Create a task to comment on
+        let story = repo
+            .create_story(NewStory::new("Books To Read"))
+            .await
+            .unwrap();
+        let task = repo
+            .create_task(&story.id, NewTask::new("Suttree", Status::Incomplete))
+            .await
+            .unwrap();
+
+        // Add comments, then page through them
+        let first = repo
+            .create_comment(&task.id, "alice", "Started")
+            .await
+            .unwrap();
+        repo.create_comment(&task.id, "bob", "Me too")
+            .await
+            .unwrap();
+        let Page(next_cursor, comments) = repo
+            .list_comments(&task.id, PageParams(1, 1), View::Basic)
+            .await
+            .unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].author, "alice");
+        let Page(_, comments) = repo
+            .list_comments(&task.id, PageParams(next_cursor, 10), View::Basic)
+            .await
+            .unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].author, "bob");
+
+        // Edit a comment, keeping the previous body
+        let comment = repo.update_comment(&first.id, "Finished").await.unwrap();
+        assert_eq!(comment.body, "Finished");
+        assert!(comment.edited);
+        assert_eq!(comment.history.len(), 1);
+        assert_eq!(comment.history[0].body, "Started");
+
+        // Commenting on a missing task fails with not found
+        let result = repo
+            .create_comment(&TaskId(Uuid::new_v4()), "alice", "?")
+            .await;
+        assert!(matches!(result, Err(Error::NotFound { .. })));
+
+        // Comments are removed with their task
+        repo.delete_task(&task.id).await.unwrap();
+        assert!(repo.fetch_comment(&first.id).await.is_err());
+    }
+}
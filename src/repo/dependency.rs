@@ -0,0 +1,154 @@
+use super::Repo;
+use crate::{Error, Result, domain::TaskId};
+use sqlx::PgExecutor;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Advisory lock key serializing dependency graph changes, so concurrent inserts cannot
+/// create a cycle between them.
+const DEPENDENCY_LOCK_KEY: i64 = 0x6773_6478_6465_7073;
+
+/// A dependency of a task, with whether the blocker is still incomplete.
+pub(super) struct Blocker {
+    pub id: Uuid,
+    pub incomplete: bool,
+}
+
+// Extend repo with queries related to task dependencies.
+impl Repo {
+    /// Make a task wait on a blocker task, rejecting dependencies that would form a cycle.
+    pub async fn add_dependency(
+        &self,
+        &TaskId(task_id): &TaskId,
+        &TaskId(blocker_id): &TaskId,
+    ) -> Result<()> {
+        if task_id == blocker_id {
+            return Err(Error::invalid_args(
+                "blocker_task_id",
+                "a task cannot depend on itself",
+            ));
+        }
+        let mut tx = self.db.begin().await?;
+        sqlx::query!("SELECT pg_advisory_xact_lock($1)", DEPENDENCY_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        // Walk everything the blocker waits on; finding the task means a cycle.
+        let cycle = sqlx::query_scalar!(
+            r#"WITH RECURSIVE upstream (id) AS (
+              SELECT blocker_id FROM task_dependencies WHERE task_id = $1
+              UNION
+              SELECT d.blocker_id FROM task_dependencies d JOIN upstream u ON d.task_id = u.id
+            )
+            SELECT EXISTS (SELECT 1 FROM upstream WHERE id = $2) AS "cycle!""#,
+            blocker_id,
+            task_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if cycle {
+            return Err(Error::invalid_args(
+                "blocker_task_id",
+                "would create a dependency cycle",
+            ));
+        }
+
+        sqlx::query!(
+            r#"INSERT INTO task_dependencies (task_id, blocker_id) VALUES ($1, $2)
+            ON CONFLICT DO NOTHING"#,
+            task_id,
+            blocker_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stop a task waiting on a blocker task.
+    pub async fn remove_dependency(
+        &self,
+        &TaskId(task_id): &TaskId,
+        &TaskId(blocker_id): &TaskId,
+    ) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM task_dependencies WHERE task_id = $1 AND blocker_id = $2",
+            task_id,
+            blocker_id,
+        )
+        .execute(self.db_ref())
+        .await?;
+        Ok(())
+    }
+}
+
+/// Select the blockers of a set of tasks, grouped by task id.
+pub(super) async fn select_task_blockers<'e>(
+    executor: impl PgExecutor<'e>,
+    task_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<Blocker>>> {
+    if task_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows = sqlx::query!(
+        r#"SELECT d.task_id, d.blocker_id, b.status = 'incomplete' AS "incomplete!"
+        FROM task_dependencies d JOIN tasks b ON b.id = d.blocker_id
+        WHERE d.task_id = ANY($1) ORDER BY d.blocker_id"#,
+        task_ids,
+    )
+    .fetch_all(executor)
+    .await?;
+    let mut blockers: HashMap<Uuid, Vec<Blocker>> = HashMap::new();
+    for row in rows {
+        blockers.entry(row.task_id).or_default().push(Blocker {
+            id: row.blocker_id,
+            incomplete: row.incomplete,
+        });
+    }
+    Ok(blockers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{NewStory, NewTask, Status},
+        repo::tests,
+    };
+
+    use testcontainers::{ImageExt, runners::AsyncRunner};
+    use testcontainers_modules::postgres::Postgres;
+
+    #[ignore]
+    #[tokio::test]
+    async fn integration_test() {
+        // Set up postgres test container backed repo
+        let image = Postgres::default().with_tag("17-alpine");
+        let container = image.start().await.unwrap();
+        let pool = tests::setup_pg_pool(&container).await;
+        let repo = Repo::new(pool);
+
+        // Set up tasks in different stories
+        let story = repo.create_story(NewStory::new("Books")).await.unwrap();
+        let other = repo.create_story(NewStory::new("Films")).await.unwrap();
+        let book = NewTask::new("No Country for Old Men", Status::Incomplete);
+        let book = repo.create_task(&story.id, book).await.unwrap();
+        let film = NewTask::new("No Country for Old Men", Status::Incomplete);
+        let film = repo.create_task(&other.id, film).await.unwrap();
+
+        // Read the book before watching the film
+        repo.add_dependency(&film.id, &book.id).await.unwrap();
+        let film = repo.fetch_task(&film.id).await.unwrap();
+        assert!(film.blocked);
+        assert_eq!(film.blocker_ids.first(), Some(&book.id));
+
+        // The reverse dependency would form a cycle
+        let result = repo.add_dependency(&book.id, &film.id).await;
+        assert!(matches!(result, Err(Error::InvalidArgs { .. })));
+
+        // Remove the dependency
+        repo.remove_dependency(&film.id, &book.id).await.unwrap();
+        let film = repo.fetch_task(&film.id).await.unwrap();
+        assert!(!film.blocked);
+    }
+}
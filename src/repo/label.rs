@@ -0,0 +1,305 @@
+use super::Repo;
+use crate::{
+    Error, Result,
+    domain::{Label, LabelId, LabelTarget, Story, StoryId, TaskId},
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgExecutor;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// The label entity object - used for query validation against the database.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct LabelEntity {
+    id: Uuid,
+    name: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+// The repo should map the entity to the domain object in public functions.
+impl From<LabelEntity> for Label {
+    fn from(entity: LabelEntity) -> Self {
+        Self {
+            id: LabelId(entity.id),
+            name: entity.name,
+            created_at: entity.created_at,
+            updated_at: entity.updated_at,
+        }
+    }
+}
+
+/// A label entity joined with the story or task it is attached to.
+struct LinkedLabelEntity {
+    owner_id: Uuid,
+    id: Uuid,
+    name: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// Group linked labels by owner id.
+fn group_labels(entities: Vec<LinkedLabelEntity>) -> HashMap<Uuid, Vec<Label>> {
+    let mut labels: HashMap<Uuid, Vec<Label>> = HashMap::new();
+    for entity in entities {
+        labels.entry(entity.owner_id).or_default().push(Label {
+            id: LabelId(entity.id),
+            name: entity.name,
+            created_at: entity.created_at,
+            updated_at: entity.updated_at,
+        });
+    }
+    labels
+}
+
+// Extend repo with queries related to labels.
+impl Repo {
+    /// Select a label by id
+    pub async fn fetch_label(&self, &LabelId(label_id): &LabelId) -> Result<Label> {
+        let query = sqlx::query_as!(
+            LabelEntity,
+            "SELECT id, name, created_at, updated_at FROM labels WHERE id = $1",
+            label_id
+        );
+        query
+            .fetch_optional(self.db_ref())
+            .await?
+            .map(Label::from)
+            .ok_or_else(|| Error::not_found("label", label_id))
+    }
+
+    /// Select all labels, ordered by name.
+    pub async fn list_labels(&self) -> Result<Vec<Label>> {
+        let query = sqlx::query_as!(
+            LabelEntity,
+            "SELECT id, name, created_at, updated_at FROM labels ORDER BY lower(name)"
+        );
+        let entities = query.fetch_all(self.db_ref()).await?;
+        Ok(entities.into_iter().map(Label::from).collect())
+    }
+
+    /// Insert a new label
+    pub async fn create_label(&self, name: impl Into<String>) -> Result<Label> {
+        let query = sqlx::query_as!(
+            LabelEntity,
+            r#"INSERT INTO labels (name) VALUES ($1)
+            RETURNING id, name, created_at, updated_at"#,
+            name.into()
+        );
+        let entity = query.fetch_one(self.db_ref()).await?;
+        Ok(Label::from(entity))
+    }
+
+    /// Delete a label, detaching it from all stories and tasks.
+    pub async fn delete_label(&self, &LabelId(label_id): &LabelId) -> Result<()> {
+        sqlx::query!("DELETE FROM labels WHERE id = $1", label_id)
+            .execute(self.db_ref())
+            .await?;
+        Ok(())
+    }
+
+    /// Attach labels to a story or task, ignoring labels that are already attached.
+    pub async fn add_labels(&self, target: &LabelTarget, label_ids: &[LabelId]) -> Result<()> {
+        let label_ids: Vec<Uuid> = label_ids.iter().map(|id| id.0).collect();
+        let query = match target {
+            LabelTarget::Story(StoryId(story_id)) => sqlx::query!(
+                r#"INSERT INTO story_labels (story_id, label_id)
+                SELECT $1, unnest($2::uuid[]) ON CONFLICT DO NOTHING"#,
+                story_id,
+                &label_ids,
+            ),
+            LabelTarget::Task(TaskId(task_id)) => sqlx::query!(
+                r#"INSERT INTO task_labels (task_id, label_id)
+                SELECT $1, unnest($2::uuid[]) ON CONFLICT DO NOTHING"#,
+                task_id,
+                &label_ids,
+            ),
+        };
+        query.execute(self.db_ref()).await?;
+        Ok(())
+    }
+
+    /// Detach labels from a story or task.
+    pub async fn remove_labels(&self, target: &LabelTarget, label_ids: &[LabelId]) -> Result<()> {
+        let label_ids: Vec<Uuid> = label_ids.iter().map(|id| id.0).collect();
+        let query = match target {
+            LabelTarget::Story(StoryId(story_id)) => sqlx::query!(
+                "DELETE FROM story_labels WHERE story_id = $1 AND label_id = ANY($2)",
+                story_id,
+                &label_ids,
+            ),
+            LabelTarget::Task(TaskId(task_id)) => sqlx::query!(
+                "DELETE FROM task_labels WHERE task_id = $1 AND label_id = ANY($2)",
+                task_id,
+                &label_ids,
+            ),
+        };
+        query.execute(self.db_ref()).await?;
+        Ok(())
+    }
+
+    /// Select the labels attached to a story or task, ordered by name.
+    pub async fn target_labels(&self, target: &LabelTarget) -> Result<Vec<Label>> {
+        let labels = match target {
+            LabelTarget::Story(StoryId(story_id)) => {
+                select_story_labels(self.db_ref(), &[*story_id]).await?
+            }
+            LabelTarget::Task(TaskId(task_id)) => {
+                select_task_labels(self.db_ref(), &[*task_id]).await?
+            }
+        };
+        Ok(labels.into_values().next().unwrap_or_default())
+    }
+
+    /// Fill in labels for a batch of stories with a single query.
+    pub(crate) async fn with_story_labels(&self, stories: Vec<Story>) -> Result<Vec<Story>> {
+        attach_story_labels(self.db_ref(), stories).await
+    }
+
+    /// Fill in labels for a single story.
+    pub(crate) async fn with_story_label(&self, story: Story) -> Result<Story> {
+        let mut stories = self.with_story_labels(vec![story]).await?;
+        Ok(stories.remove(0))
+    }
+}
+
+/// Fill in labels for a batch of stories, using a transaction or the pool.
+pub(super) async fn attach_story_labels<'e>(
+    executor: impl PgExecutor<'e>,
+    mut stories: Vec<Story>,
+) -> Result<Vec<Story>> {
+    let ids: Vec<Uuid> = stories.iter().map(|s| s.id.0).collect();
+    let mut labels = select_story_labels(executor, &ids).await?;
+    for story in &mut stories {
+        story.labels = labels.remove(&story.id.0).unwrap_or_default();
+    }
+    Ok(stories)
+}
+
+/// Select labels for a set of stories, grouped by story id.
+async fn select_story_labels<'e>(
+    executor: impl PgExecutor<'e>,
+    story_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<Label>>> {
+    if story_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let query = sqlx::query_as!(
+        LinkedLabelEntity,
+        r#"SELECT sl.story_id AS owner_id, l.id, l.name, l.created_at, l.updated_at
+        FROM story_labels sl JOIN labels l ON l.id = sl.label_id
+        WHERE sl.story_id = ANY($1) ORDER BY lower(l.name)"#,
+        story_ids,
+    );
+    Ok(group_labels(query.fetch_all(executor).await?))
+}
+
+/// Select labels for a set of tasks, grouped by task id.
+pub(super) async fn select_task_labels<'e>(
+    executor: impl PgExecutor<'e>,
+    task_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<Label>>> {
+    if task_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let query = sqlx::query_as!(
+        LinkedLabelEntity,
+        r#"SELECT tl.task_id AS owner_id, l.id, l.name, l.created_at, l.updated_at
+        FROM task_labels tl JOIN labels l ON l.id = tl.label_id
+        WHERE tl.task_id = ANY($1) ORDER BY lower(l.name)"#,
+        task_ids,
+    );
+    Ok(group_labels(query.fetch_all(executor).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            NewStory, NewTask, PageParams, Status, StoryFilter, StorySort, TaskFilter, TaskOrder,
+            View,
+        },
+        repo::tests,
+    };
+
+    use testcontainers::{ImageExt, runners::AsyncRunner};
+    use testcontainers_modules::postgres::Postgres;
+
+    #[ignore]
+    #[tokio::test]
+    async fn integration_test() {
+        // Set up postgres test container backed repo
+        let image = Postgres::default().with_tag("17-alpine");
+        let container = image.start().await.unwrap();
+        let pool = tests::setup_pg_pool(&container).await;
+        let repo = Repo::new(pool);
+
+        // Create labels, names are unique ignoring case
+        let fiction = repo.create_label("Fiction").await.unwrap();
+        let classic = repo.create_label("classic").await.unwrap();
+        let result = repo.create_label("FICTION").await;
+        assert!(matches!(result, Err(Error::AlreadyExists { .. })));
+        assert_eq!(
+            repo.list_labels().await.unwrap(),
+            [classic.clone(), fiction.clone()]
+        );
+
+        // Label a story and a task
+        let story = repo
+            .create_story(NewStory::new("Books To Read"))
+            .await
+            .unwrap();
+        let target = LabelTarget::Story(story.id.clone());
+        let both = [fiction.id.clone(), classic.id.clone()];
+        repo.add_labels(&target, &both).await.unwrap();
+        repo.add_labels(&target, &both).await.unwrap();
+        let story = repo.fetch_story(&story.id).await.unwrap();
+        assert_eq!(story.labels.len(), 2);
+
+        let task = repo
+            .create_task(&story.id, NewTask::new("Suttree", Status::Incomplete))
+            .await
+            .unwrap();
+        let target = LabelTarget::Task(task.id.clone());
+        repo.add_labels(&target, &both[..1]).await.unwrap();
+
+        // Filter stories and tasks by label
+        let filter = StoryFilter {
+            label_ids: both.to_vec(),
+            ..Default::default()
+        };
+        let page = repo
+            .list_stories(
+                PageParams::default(),
+                &filter,
+                StorySort::default(),
+                None,
+                View::Basic,
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.stories.len(), 1);
+        let filter = TaskFilter {
+            label_ids: both.to_vec(),
+            ..Default::default()
+        };
+        let tasks = repo
+            .list_tasks(&story.id, TaskOrder::Position, &filter, View::Basic)
+            .await
+            .unwrap();
+        assert!(tasks.is_empty());
+
+        // Attaching a missing label fails with not found
+        let missing = LabelId(Uuid::new_v4());
+        let result = repo.add_labels(&target, &[missing]).await;
+        assert!(matches!(result, Err(Error::NotFound { .. })));
+
+        // Remove a label, then delete the other
+        repo.remove_labels(&target, &both[..1]).await.unwrap();
+        assert!(repo.target_labels(&target).await.unwrap().is_empty());
+        repo.delete_label(&classic.id).await.unwrap();
+        let story = repo.fetch_story(&story.id).await.unwrap();
+        assert_eq!(story.labels, [fiction]);
+    }
+}
@@ -1,8 +1,15 @@
 use crate::Error;
-use sqlx::postgres::PgPool;
+use sqlx::postgres::{PgDatabaseError, PgPool};
 
+mod comment;
+mod dependency;
+mod label;
+mod search;
 mod story;
+mod subtask;
 mod task;
+mod template;
+mod transfer;
 
 /// Database abstraction layer.
 pub struct Repo {
@@ -21,19 +28,78 @@ impl Repo {
     }
 }
 
+// SQLSTATE codes that map to specific project errors.
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+const UNIQUE_VIOLATION: &str = "23505";
+const SERIALIZATION_FAILURE: &str = "40001";
+const DEADLOCK_DETECTED: &str = "40P01";
+const IMMUTABLE_COLUMN: &str = "GX001";
+const UNDELETABLE_TABLE: &str = "GX002";
+
 impl From<sqlx::Error> for Error {
     fn from(err: sqlx::Error) -> Self {
         let msg = err.to_string();
         match err {
-            sqlx::Error::InvalidArgument(inner) => Error::invalid_args(inner),
-            sqlx::Error::RowNotFound => Error::not_found(msg),
+            sqlx::Error::InvalidArgument(inner) => Error::invalid_args("", inner),
+            // Lookups use fetch_optional and report not found themselves; a missing row here is
+            // a bug.
+            sqlx::Error::RowNotFound => Error::internal("query unexpectedly returned no rows"),
+            sqlx::Error::Database(db_err) => match db_err.try_downcast_ref::<PgDatabaseError>() {
+                Some(pg_err) => Error::from(pg_err),
+                None => Error::internal(msg),
+            },
             _ => Error::internal(msg),
         }
     }
 }
 
+/// Classify postgres errors by SQLSTATE.
+impl From<&PgDatabaseError> for Error {
+    fn from(err: &PgDatabaseError) -> Self {
+        let constraint = err.constraint().unwrap_or_default();
+        match err.code() {
+            FOREIGN_KEY_VIOLATION => match err.detail().and_then(missing_reference) {
+                Some((table, id)) => Error::not_found(resource_name(table), id),
+                None => Error::failed_precondition(format!(
+                    "record is still referenced by {}",
+                    err.table().map(resource_name).unwrap_or("another record")
+                )),
+            },
+            UNIQUE_VIOLATION => Error::already_exists(format!("duplicate value for {constraint}")),
+            SERIALIZATION_FAILURE | DEADLOCK_DETECTED => {
+                Error::aborted("transaction conflict, please retry")
+            }
+            IMMUTABLE_COLUMN => Error::failed_precondition("cannot modify an immutable column"),
+            UNDELETABLE_TABLE => Error::failed_precondition("cannot delete from this table"),
+            _ => Error::internal(err.to_string()),
+        }
+    }
+}
+
+/// Parse the referenced table and key from a foreign key violation detail like
+/// `Key (story_id)=(...) is not present in table "stories".`
+fn missing_reference(detail: &str) -> Option<(&str, &str)> {
+    let (_, rest) = detail.split_once(")=(")?;
+    let (id, rest) = rest.split_once(") is not present in table \"")?;
+    let table = rest.strip_suffix("\".")?;
+    Some((table, id))
+}
+
+/// Map a table name to the resource name used in errors.
+fn resource_name(table: &str) -> &str {
+    match table {
+        "stories" => "story",
+        "tasks" => "task",
+        "labels" => "label",
+        "task_comments" => "comment",
+        "story_templates" => "template",
+        other => other,
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use super::*;
     use sqlx::{
         migrate::Migrator,
         postgres::{PgPool, PgPoolOptions},
@@ -43,6 +109,20 @@ mod tests {
     use testcontainers::ContainerAsync as Container;
     use testcontainers_modules::postgres::Postgres;
 
+    #[test]
+    fn parse_missing_reference() {
+        let detail = r#"Key (story_id)=(9c4f1d0e) is not present in table "stories"."#;
+        assert_eq!(missing_reference(detail), Some(("stories", "9c4f1d0e")));
+        let detail = r#"Key (id)=(9c4f1d0e) is still referenced from table "tasks"."#;
+        assert_eq!(missing_reference(detail), None);
+    }
+
+    #[test]
+    fn unexpected_row_not_found() {
+        let err = Error::from(sqlx::Error::RowNotFound);
+        assert!(matches!(err, Error::Internal { .. }));
+    }
+
     /// Given a running Postgres container, set up a connection pool and run migrations.
     pub async fn setup_pg_pool(container: &Container<Postgres>) -> PgPool {
         let connection_string = &format!(
@@ -53,7 +133,7 @@ mod tests {
         let pool = PgPoolOptions::new()
             .max_connections(2)
             .min_connections(1)
-            .connect(&connection_string)
+            .connect(connection_string)
             .await
             .unwrap();
 
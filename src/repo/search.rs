@@ -0,0 +1,136 @@
+use super::Repo;
+use crate::{
+    Result,
+    domain::{Page, PageParams, SearchHit, SearchQuery, StoryId, TaskId},
+};
+use uuid::Uuid;
+
+/// Options for highlighting search terms in snippets. Terms are marked with `**` rather than the
+/// default `<b>` tags, since the text around them is not HTML-escaped.
+const HEADLINE_OPTIONS: &str = "StartSel=**, StopSel=**, MaxFragments=2, MaxWords=24, MinWords=8";
+
+/// A story or task search hit entity.
+struct SearchHitEntity {
+    story_id: Uuid,
+    task_id: Option<Uuid>,
+    name: String,
+    snippet: String,
+    rank: f32,
+}
+
+// The repo should map the entity to the domain object in public functions.
+impl From<SearchHitEntity> for SearchHit {
+    fn from(entity: SearchHitEntity) -> Self {
+        Self {
+            story_id: StoryId(entity.story_id),
+            task_id: entity.task_id.map(TaskId),
+            name: entity.name,
+            snippet: entity.snippet,
+            rank: entity.rank,
+        }
+    }
+}
+
+// Extend repo with full-text search queries.
+impl Repo {
+    /// Select a page of stories and tasks matching a query, most relevant first. The cursor is
+    /// the one-based position of the first hit.
+    pub async fn search(
+        &self,
+        query: &SearchQuery,
+        PageParams(cursor, limit): PageParams,
+    ) -> Result<Page<SearchHit>> {
+        // Snippets are only highlighted for the hits on the page.
+        let query = sqlx::query_as!(
+            SearchHitEntity,
+            r#"WITH q AS (SELECT websearch_to_tsquery('english', $1) AS query)
+            SELECT hits.story_id AS "story_id!", hits.task_id, hits.name AS "name!",
+              ts_headline('english', hits.document, q.query, $5) AS "snippet!",
+              hits.rank AS "rank!"
+            FROM q, (
+              SELECT s.id AS story_id, NULL::uuid AS task_id, s.name,
+                s.name || ' ' || coalesce(s.description, '') AS document,
+                ts_rank(s.search_vector, q.query) AS rank
+              FROM stories s, q
+              WHERE s.search_vector @@ q.query AND ($2::uuid IS NULL OR s.id = $2)
+              UNION ALL
+              SELECT t.story_id, t.id, t.name,
+                t.name || ' ' || coalesce(t.description, ''),
+                ts_rank(t.search_vector, q.query)
+              FROM tasks t, q
+              WHERE t.search_vector @@ q.query AND ($2::uuid IS NULL OR t.story_id = $2)
+              ORDER BY rank DESC, story_id, task_id NULLS FIRST
+              OFFSET $3 LIMIT $4
+            ) hits
+            ORDER BY hits.rank DESC, hits.story_id, hits.task_id NULLS FIRST"#,
+            query.terms,
+            query.story_id.as_ref().map(|StoryId(id)| *id),
+            cursor - 1,
+            limit,
+            HEADLINE_OPTIONS,
+        );
+        let entities = query.fetch_all(self.db_ref()).await?;
+        let next_cursor = match entities.len() as i64 {
+            len if len == limit => cursor + len,
+            _ => 0,
+        };
+        Ok(Page(
+            next_cursor,
+            entities.into_iter().map(SearchHit::from).collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{NewStory, NewTask, Status},
+        repo::tests,
+    };
+
+    use testcontainers::{ImageExt, runners::AsyncRunner};
+    use testcontainers_modules::postgres::Postgres;
+
+    #[ignore]
+    #[tokio::test]
+    async fn integration_test() {
+        // Set up postgres test container backed repo
+        let image = Postgres::default().with_tag("17-alpine");
+        let container = image.start().await.unwrap();
+        let pool = tests::setup_pg_pool(&container).await;
+        let repo = Repo::new(pool);
+
+        // Create a story and tasks to search
+        let story = repo
+            .create_story(NewStory::new("Books To Read"))
+            .await
+            .unwrap();
+        let task = NewTask {
+            description: "A novel about reading in the desert".into(),
+            ..NewTask::new("Blood Meridian", Status::Incomplete)
+        };
+        let task = repo.create_task(&story.id, task).await.unwrap();
+
+        // Name matches rank above description matches
+        let query = SearchQuery {
+            terms: "read".into(),
+            story_id: None,
+        };
+        let Page(next_cursor, hits) = repo.search(&query, PageParams::default()).await.unwrap();
+        assert_eq!(next_cursor, 0);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].task_id, None);
+        assert_eq!(hits[1].task_id, Some(task.id));
+        assert!(hits[1].snippet.contains("**reading**"));
+
+        // Restrict to another story
+        let other = repo.create_story(NewStory::new("Chores")).await.unwrap();
+        let query = SearchQuery {
+            story_id: Some(other.id),
+            ..query
+        };
+        let Page(_, hits) = repo.search(&query, PageParams::default()).await.unwrap();
+        assert!(hits.is_empty());
+    }
+}
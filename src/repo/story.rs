@@ -1,16 +1,23 @@
-use super::Repo;
+use super::{Repo, task::copy_story_tasks};
 use crate::{
     Error, Result,
-    domain::{Page, PageParams, Story, StoryId},
+    domain::{
+        Comparator, Limit, NewStory, PageParams, Story, StoryExpr, StoryFilter, StoryId,
+        StoryImport, StoryKeyset, StoryPage, StorySort, StorySortField, StorySortValue, StoryStats,
+        TextMatch, View,
+    },
 };
 use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgExecutor, Postgres, QueryBuilder, Transaction};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// The story entity object - used for query validation against the database.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, FromRow)]
 struct StoryEntity {
     id: Uuid,
     name: String,
+    description: Option<String>,
     seqno: i64,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
@@ -22,76 +29,187 @@ impl From<StoryEntity> for Story {
         Self {
             id: StoryId(entity.id),
             name: entity.name,
+            description: entity.description,
+            labels: Vec::new(),
+            stats: None,
             created_at: entity.created_at,
             updated_at: entity.updated_at,
         }
     }
 }
 
+/// Aggregated task progress for a story.
+struct StoryStatsEntity {
+    story_id: Uuid,
+    incomplete_tasks: i64,
+    complete_tasks: i64,
+    last_activity_at: DateTime<Utc>,
+}
+
 // Extend repo with queries related to stories.
 impl Repo {
     /// Select a story by id
     pub async fn fetch_story(&self, &StoryId(story_id): &StoryId) -> Result<Story> {
         let query = sqlx::query_as!(
             StoryEntity,
-            "SELECT id, name, seqno, created_at, updated_at FROM stories WHERE id = $1",
+            r#"SELECT id, name, description AS "description?", seqno, created_at, updated_at
+            FROM stories WHERE id = $1"#,
             story_id
         );
-        query
+        let story = query
             .fetch_optional(self.db_ref())
             .await?
             .map(Story::from)
-            .ok_or_else(|| Error::not_found(format!("story not found: {story_id}")))
+            .ok_or_else(|| Error::not_found("story", story_id))?;
+        self.with_story_label(story).await
     }
 
-    /// Select a page of stories.
-    pub async fn list_stories(&self, PageParams(cursor, limit): PageParams) -> Result<Page<Story>> {
-        let query = sqlx::query_as!(
-            StoryEntity,
-            r#"SELECT id, name, seqno, created_at, updated_at FROM stories WHERE seqno >= $1
-            ORDER BY seqno LIMIT $2"#,
-            cursor,
-            limit,
+    /// Select a page of stories matching a filter, in sort order. Descriptions are only loaded
+    /// in the full view.
+    ///
+    /// Pages continue after a keyset position when given. Otherwise the numeric cursor applies,
+    /// for the default sort only.
+    pub async fn list_stories(
+        &self,
+        PageParams(cursor, limit): PageParams,
+        filter: &StoryFilter,
+        sort: StorySort,
+        after: Option<&StoryKeyset>,
+        view: View,
+    ) -> Result<StoryPage> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT id, name, CASE WHEN ");
+        query.push_bind(view.is_full());
+        query.push(
+            " THEN description END AS description, seqno, created_at, updated_at \
+            FROM stories WHERE true",
         );
-        let entities = query.fetch_all(self.db_ref()).await?;
-        let next_cursor = entities.last().map(|s| s.seqno + 1).unwrap_or_default();
+        if after.is_none() && sort.is_default() {
+            query.push(" AND seqno >= ").push_bind(cursor);
+        }
+        if !filter.label_ids.is_empty() {
+            let label_ids: Vec<Uuid> = filter.label_ids.iter().map(|id| id.0).collect();
+            let count = label_ids.len() as i64;
+            query
+                .push(" AND id IN (SELECT story_id FROM story_labels WHERE label_id = ANY(")
+                .push_bind(label_ids)
+                .push(") GROUP BY story_id HAVING count(*) = ")
+                .push_bind(count)
+                .push(")");
+        }
+        if let Some(expr) = &filter.expr {
+            query.push(" AND ");
+            push_story_expr(&mut query, expr);
+        }
+        if let Some(after) = after {
+            push_story_keyset(&mut query, after)?;
+        }
+        let (column, direction) = sort_sql(sort);
+        query.push(format_args!(" ORDER BY {column} {direction}"));
+        if sort.field != StorySortField::Seqno {
+            query.push(format_args!(", seqno {direction}"));
+        }
+        query.push(" LIMIT ").push_bind(limit);
+
+        let entities: Vec<StoryEntity> = query.build_query_as().fetch_all(self.db_ref()).await?;
+        let next_cursor = if sort.is_default() {
+            entities.last().map(|s| s.seqno + 1).unwrap_or_default()
+        } else {
+            0
+        };
+        let next_keyset = match entities.last() {
+            Some(last) if entities.len() as Limit == limit => Some(StoryKeyset {
+                sort,
+                value: match sort.field {
+                    StorySortField::Seqno => StorySortValue::Seqno,
+                    StorySortField::Name => StorySortValue::Name(last.name.clone()),
+                    StorySortField::CreatedAt => StorySortValue::Time(last.created_at),
+                    StorySortField::UpdatedAt => StorySortValue::Time(last.updated_at),
+                },
+                seqno: last.seqno,
+            }),
+            _ => None,
+        };
         let stories = entities.into_iter().map(Story::from).collect();
-        Ok(Page(next_cursor, stories))
+        let stories = self.with_story_labels(stories).await?;
+        Ok(StoryPage {
+            next_cursor,
+            next_keyset,
+            stories,
+        })
     }
 
     /// Insert a new story
-    pub async fn create_story(&self, name: impl Into<String>) -> Result<Story> {
+    pub async fn create_story(&self, story: NewStory) -> Result<Story> {
+        insert_story(self.db_ref(), story).await
+    }
+
+    /// Update the mutable fields of a story, keeping the description if it was not loaded.
+    pub async fn update_story(&self, story: &Story) -> Result<Story> {
+        let StoryId(story_id) = story.id;
         let query = sqlx::query_as!(
             StoryEntity,
-            r#"INSERT INTO stories (name) VALUES ($1)
-            RETURNING id, name, seqno, created_at, updated_at"#,
-            name.into()
+            r#"UPDATE stories SET name = $1, description = coalesce($2, description) WHERE id = $3
+            RETURNING id, name, description AS "description?", seqno, created_at, updated_at"#,
+            story.name,
+            story.description,
+            story_id
         );
-        let entity = query.fetch_one(self.db_ref()).await?;
-        Ok(Story::from(entity))
+        let entity = query
+            .fetch_optional(self.db_ref())
+            .await?
+            .ok_or_else(|| Error::not_found("story", story_id))?;
+        self.with_story_label(Story::from(entity)).await
     }
 
-    /// Update story name
-    pub async fn update_story(
-        &self,
-        &StoryId(story_id): &StoryId,
-        name: impl Into<String>,
-    ) -> Result<Story> {
+    /// Fill in task progress for a batch of stories with a single aggregate query.
+    pub async fn with_story_stats(&self, mut stories: Vec<Story>) -> Result<Vec<Story>> {
+        let ids: Vec<Uuid> = stories.iter().map(|s| s.id.0).collect();
+        if ids.is_empty() {
+            return Ok(stories);
+        }
         let query = sqlx::query_as!(
-            StoryEntity,
-            r#"UPDATE stories SET name = $1 WHERE id = $2
-            RETURNING id, name, seqno, created_at, updated_at"#,
-            name.into(),
-            story_id
+            StoryStatsEntity,
+            r#"SELECT s.id AS story_id,
+              count(t.id) FILTER (WHERE t.status = 'incomplete') AS "incomplete_tasks!",
+              count(t.id) FILTER (WHERE t.status = 'complete') AS "complete_tasks!",
+              greatest(s.updated_at, max(t.updated_at), (
+                SELECT max(c.updated_at) FROM task_comments c
+                JOIN tasks ct ON ct.id = c.task_id WHERE ct.story_id = s.id
+              )) AS "last_activity_at!"
+            FROM stories s LEFT JOIN tasks t ON t.story_id = s.id
+            WHERE s.id = ANY($1) GROUP BY s.id"#,
+            &ids,
         );
-        let entity = query.fetch_one(self.db_ref()).await?;
-        Ok(Story::from(entity))
+        let mut stats: HashMap<Uuid, StoryStats> = query
+            .fetch_all(self.db_ref())
+            .await?
+            .into_iter()
+            .map(|entity| {
+                let stats = StoryStats {
+                    incomplete_tasks: entity.incomplete_tasks,
+                    complete_tasks: entity.complete_tasks,
+                    last_activity_at: entity.last_activity_at,
+                };
+                (entity.story_id, stats)
+            })
+            .collect();
+        for story in &mut stories {
+            story.stats = stats.remove(&story.id.0);
+        }
+        Ok(stories)
     }
 
-    /// Delete a story and child tasks.
+    /// Delete a story, child tasks and their comments.
     pub async fn delete_story(&self, &StoryId(story_id): &StoryId) -> Result<()> {
         let mut tx = self.db.begin().await?;
 
+        sqlx::query!(
+            "DELETE FROM task_comments WHERE task_id IN (SELECT id FROM tasks WHERE story_id = $1)",
+            story_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
         sqlx::query!("DELETE FROM tasks WHERE story_id = $1", story_id)
             .execute(&mut *tx)
             .await?;
@@ -104,12 +222,227 @@ impl Repo {
 
         Ok(())
     }
+
+    /// Copy a story under a new name, with its labels and all of its tasks, in one transaction.
+    /// Task statuses are reset to incomplete when asked.
+    pub async fn duplicate_story(
+        &self,
+        &StoryId(story_id): &StoryId,
+        name: &str,
+        reset_statuses: bool,
+    ) -> Result<Story> {
+        let mut tx = self.db.begin().await?;
+        let entity = sqlx::query_as!(
+            StoryEntity,
+            r#"INSERT INTO stories (name, description)
+            SELECT $2, description FROM stories WHERE id = $1
+            RETURNING id, name, description AS "description?", seqno, created_at, updated_at"#,
+            story_id,
+            name,
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::not_found("story", story_id))?;
+        sqlx::query!(
+            "INSERT INTO story_labels (story_id, label_id)
+            SELECT $2, label_id FROM story_labels WHERE story_id = $1",
+            story_id,
+            entity.id,
+        )
+        .execute(&mut *tx)
+        .await?;
+        copy_story_tasks(&mut tx, story_id, entity.id, reset_statuses).await?;
+        tx.commit().await?;
+        self.with_story_label(Story::from(entity)).await
+    }
+}
+
+/// The name of the cursor stories are exported through.
+const EXPORT_CURSOR: &str = "export_stories";
+
+/// Open a cursor over all stories, or just one, in seqno order with descriptions. The
+/// transaction must stay open while the cursor is read.
+pub(super) async fn declare_story_cursor(
+    tx: &mut Transaction<'_, Postgres>,
+    story_id: Option<&StoryId>,
+) -> Result<()> {
+    let sql = format!(
+        "DECLARE {EXPORT_CURSOR} NO SCROLL CURSOR FOR
+        SELECT id, name, description, seqno, created_at, updated_at FROM stories
+        WHERE $1::uuid IS NULL OR id = $1 ORDER BY seqno"
+    );
+    sqlx::query(&sql)
+        .bind(story_id.map(|id| id.0))
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Read up to `limit` more stories from the export cursor, without labels.
+pub(super) async fn fetch_story_cursor(
+    tx: &mut Transaction<'_, Postgres>,
+    limit: Limit,
+) -> Result<Vec<Story>> {
+    let sql = format!("FETCH {limit} FROM {EXPORT_CURSOR}");
+    let entities = sqlx::query_as::<_, StoryEntity>(&sql)
+        .fetch_all(&mut **tx)
+        .await?;
+    Ok(entities.into_iter().map(Story::from).collect())
+}
+
+/// Insert a new story, without labels.
+pub(super) async fn insert_story<'e>(
+    executor: impl PgExecutor<'e>,
+    story: NewStory,
+) -> Result<Story> {
+    let query = sqlx::query_as!(
+        StoryEntity,
+        r#"INSERT INTO stories (name, description) VALUES ($1, $2)
+        RETURNING id, name, description AS "description?", seqno, created_at, updated_at"#,
+        story.name,
+        story.description,
+    );
+    let entity = query.fetch_one(executor).await?;
+    Ok(Story::from(entity))
+}
+
+/// Insert imported stories under the given ids in one statement, keeping any timestamps given.
+pub(super) async fn insert_stories(
+    tx: &mut Transaction<'_, Postgres>,
+    ids: &[Uuid],
+    stories: &[StoryImport],
+) -> Result<()> {
+    let names: Vec<String> = stories.iter().map(|s| s.name.clone()).collect();
+    let descriptions: Vec<String> = stories.iter().map(|s| s.description.clone()).collect();
+    let created: Vec<Option<DateTime<Utc>>> = stories.iter().map(|s| s.created_at).collect();
+    let updated: Vec<Option<DateTime<Utc>>> = stories.iter().map(|s| s.updated_at).collect();
+    sqlx::query!(
+        r#"INSERT INTO stories (id, name, description, created_at, updated_at)
+        SELECT * FROM unnest($1::uuid[], $2::text[], $3::text[], $4::timestamptz[],
+          $5::timestamptz[])"#,
+        ids,
+        &names,
+        &descriptions,
+        &created as &[Option<DateTime<Utc>>],
+        &updated as &[Option<DateTime<Utc>>],
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// The column and direction to sort stories by. Only fixed identifiers reach the SQL text.
+fn sort_sql(sort: StorySort) -> (&'static str, &'static str) {
+    let column = match sort.field {
+        StorySortField::Seqno => "seqno",
+        StorySortField::Name => "name",
+        StorySortField::CreatedAt => "created_at",
+        StorySortField::UpdatedAt => "updated_at",
+    };
+    (column, if sort.descending { "DESC" } else { "ASC" })
+}
+
+/// Append a filter expression as SQL, binding every value as a parameter.
+fn push_story_expr(query: &mut QueryBuilder<'_, Postgres>, expr: &StoryExpr) {
+    match expr {
+        StoryExpr::And(left, right) | StoryExpr::Or(left, right) => {
+            let op = if matches!(expr, StoryExpr::And(..)) {
+                " AND "
+            } else {
+                " OR "
+            };
+            query.push("(");
+            push_story_expr(query, left);
+            query.push(op);
+            push_story_expr(query, right);
+            query.push(")");
+        }
+        StoryExpr::Not(inner) => {
+            query.push("NOT (");
+            push_story_expr(query, inner);
+            query.push(")");
+        }
+        StoryExpr::Name(TextMatch::Equals(value)) => {
+            query.push("name = ").push_bind(value.clone());
+        }
+        StoryExpr::Name(TextMatch::NotEquals(value)) => {
+            query.push("name <> ").push_bind(value.clone());
+        }
+        StoryExpr::Name(TextMatch::Has(value)) => {
+            query
+                .push("name ILIKE ")
+                .push_bind(like_pattern(value))
+                .push(r" ESCAPE '\'");
+        }
+        StoryExpr::CreatedAt(op, ts) => {
+            query.push(format_args!("created_at {} ", comparator_sql(*op)));
+            query.push_bind(*ts);
+        }
+        StoryExpr::UpdatedAt(op, ts) => {
+            query.push(format_args!("updated_at {} ", comparator_sql(*op)));
+            query.push_bind(*ts);
+        }
+    }
+}
+
+/// Append the condition for rows after a keyset position, in sort order.
+fn push_story_keyset(query: &mut QueryBuilder<'_, Postgres>, after: &StoryKeyset) -> Result<()> {
+    let (column, _) = sort_sql(after.sort);
+    let op = if after.sort.descending { "<" } else { ">" };
+    match (&after.value, after.sort.field) {
+        (StorySortValue::Seqno, StorySortField::Seqno) => {
+            query.push(format_args!(" AND seqno {op} "));
+        }
+        (StorySortValue::Name(name), StorySortField::Name) => {
+            query.push(format_args!(" AND ({column}, seqno) {op} ("));
+            query.push_bind(name.clone()).push(", ");
+        }
+        (StorySortValue::Time(ts), StorySortField::CreatedAt | StorySortField::UpdatedAt) => {
+            query.push(format_args!(" AND ({column}, seqno) {op} ("));
+            query.push_bind(*ts).push(", ");
+        }
+        _ => return Err(Error::invalid_args("page_token", "does not match the sort")),
+    }
+    query.push_bind(after.seqno);
+    if after.sort.field != StorySortField::Seqno {
+        query.push(")");
+    }
+    Ok(())
+}
+
+/// The SQL operator for a comparator.
+fn comparator_sql(op: Comparator) -> &'static str {
+    match op {
+        Comparator::Eq => "=",
+        Comparator::Ne => "<>",
+        Comparator::Lt => "<",
+        Comparator::Le => "<=",
+        Comparator::Gt => ">",
+        Comparator::Ge => ">=",
+    }
+}
+
+/// Translate a `*` wildcard value into an ILIKE pattern, escaping LIKE metacharacters. Values
+/// without wildcards match anywhere.
+fn like_pattern(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    if escaped.contains('*') {
+        escaped.replace('*', "%")
+    } else {
+        format!("%{escaped}%")
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::repo::tests;
+    use crate::{
+        domain::{NewTask, Status, StoryChanges},
+        repo::tests,
+    };
 
     use testcontainers::{ImageExt, runners::AsyncRunner};
     use testcontainers_modules::postgres::Postgres;
@@ -124,18 +457,85 @@ mod tests {
         let repo = Repo::new(pool);
 
         // Create story
-        let story = repo.create_story("Books To Read").await.unwrap();
+        let story = repo
+            .create_story(NewStory::new("Books To Read"))
+            .await
+            .unwrap();
         assert_eq!(story.name, "Books To Read");
+        let story_id = story.id.clone();
 
         // Query stories page
-        let Page(next_cursor, stories) = repo.list_stories(PageParams::default()).await.unwrap();
-        assert_eq!(next_cursor, 2);
-        assert_eq!(stories.len(), 1);
+        let page = repo
+            .list_stories(
+                PageParams::default(),
+                &StoryFilter::default(),
+                StorySort::default(),
+                None,
+                View::Basic,
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.next_cursor, 2);
+        assert_eq!(page.stories.len(), 1);
+        assert_eq!(page.stories[0].description, None);
+
+        // Filter and sort by name, paging by keyset
+        repo.create_story(NewStory::new("Books To Buy"))
+            .await
+            .unwrap();
+        let filter = StoryFilter {
+            expr: Some(StoryExpr::Name(TextMatch::Has("books*".into()))),
+            ..Default::default()
+        };
+        let sort = StorySort {
+            field: StorySortField::Name,
+            descending: false,
+        };
+        let page = repo
+            .list_stories(PageParams(1, 1), &filter, sort, None, View::Basic)
+            .await
+            .unwrap();
+        assert_eq!(page.stories[0].name, "Books To Buy");
+        let after = page.next_keyset.unwrap();
+        let page = repo
+            .list_stories(PageParams(1, 1), &filter, sort, Some(&after), View::Basic)
+            .await
+            .unwrap();
+        assert_eq!(page.stories[0].name, "Books To Read");
 
-        // Update the story name
-        repo.update_story(&story.id, "Books").await.unwrap();
-        let story = repo.fetch_story(&story.id).await.unwrap();
+        // Update the story name and description
+        let changes = StoryChanges {
+            name: "Books".into(),
+            description: Some("Fiction *only*".into()),
+        };
+        repo.update_story(&changes.apply(story)).await.unwrap();
+        let story = repo.fetch_story(&story_id).await.unwrap();
         assert_eq!(story.name, "Books");
+        assert_eq!(story.description.as_deref(), Some("Fiction *only*"));
+
+        // Summarize task progress
+        let task = repo
+            .create_task(&story_id, NewTask::new("Suttree", Status::Complete))
+            .await
+            .unwrap();
+        let stories = repo.with_story_stats(vec![story]).await.unwrap();
+        let stats = stories[0].stats.clone().unwrap();
+        assert_eq!(stats.complete_tasks, 1);
+        assert_eq!(stats.percent_complete(), 100);
+        assert_eq!(stats.last_activity_at, task.updated_at);
+        let story = repo.fetch_story(&story_id).await.unwrap();
+
+        // Duplicate the story with its tasks reset
+        let copy = repo
+            .duplicate_story(&story_id, "Books Again", true)
+            .await
+            .unwrap();
+        assert_eq!(copy.name, "Books Again");
+        assert_eq!(copy.description, story.description);
+        let stories = repo.with_story_stats(vec![copy]).await.unwrap();
+        let stats = stories[0].stats.clone().unwrap();
+        assert_eq!(stats.incomplete_tasks, 1);
+        assert_eq!(stats.complete_tasks, 0);
 
         // Delete the story
         repo.delete_story(&story.id).await.unwrap();
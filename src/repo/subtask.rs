@@ -0,0 +1,154 @@
+use super::task::{count_blocked, count_recurring};
+use crate::{
+    Error, Result,
+    domain::{StoryId, TASK_DEPTH_MAX, TaskId},
+};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Ensure a task can be placed under a parent: the parent must be in the same story, must not be
+/// the task or one of its subtasks, and the result cannot be nested too deep. The story must be
+/// locked, so concurrent moves cannot create a cycle.
+pub(super) async fn check_parent(
+    tx: &mut Transaction<'_, Postgres>,
+    task_id: Option<&TaskId>,
+    &StoryId(story_id): &StoryId,
+    &TaskId(parent_id): &TaskId,
+) -> Result<()> {
+    let parent_story_id =
+        sqlx::query_scalar!("SELECT story_id FROM tasks WHERE id = $1", parent_id)
+            .fetch_optional(&mut **tx)
+            .await?
+            .ok_or_else(|| Error::not_found("task", parent_id))?;
+    if parent_story_id != story_id {
+        return Err(Error::invalid_args(
+            "parent_task_id",
+            "must be a task in the same story",
+        ));
+    }
+
+    // The parent and its ancestors, nearest first.
+    let ancestors = sqlx::query_scalar!(
+        r#"WITH RECURSIVE ancestors (id, parent_task_id) AS (
+          SELECT id, parent_task_id FROM tasks WHERE id = $1
+          UNION ALL
+          SELECT t.id, t.parent_task_id FROM tasks t JOIN ancestors a ON t.id = a.parent_task_id
+        )
+        SELECT id AS "id!" FROM ancestors"#,
+        parent_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let height = match task_id {
+        Some(TaskId(task_id)) => {
+            if ancestors.contains(task_id) {
+                return Err(Error::invalid_args(
+                    "parent_task_id",
+                    "cannot be the task itself or one of its subtasks",
+                ));
+            }
+            subtree_height(tx, task_id).await?
+        }
+        None => 1,
+    };
+    if ancestors.len() as i32 + height > TASK_DEPTH_MAX {
+        return Err(Error::invalid_args(
+            "parent_task_id",
+            format!("subtasks cannot be nested more than {TASK_DEPTH_MAX} levels deep"),
+        ));
+    }
+    Ok(())
+}
+
+/// The number of levels in a task's subtree, counting the task itself.
+async fn subtree_height(tx: &mut Transaction<'_, Postgres>, task_id: &Uuid) -> Result<i32> {
+    let height = sqlx::query_scalar!(
+        r#"WITH RECURSIVE subtree (id, depth) AS (
+          SELECT id, 1 FROM tasks WHERE id = $1
+          UNION ALL
+          SELECT t.id, s.depth + 1 FROM tasks t JOIN subtree s ON t.parent_task_id = s.id
+        )
+        SELECT max(depth) AS "height!" FROM subtree"#,
+        task_id,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(height)
+}
+
+/// Roll completion down to the subtasks of tasks being completed: fail if any outside the
+/// completed tasks are incomplete, unless forced, in which case they are completed too as long
+/// as none of them recur or wait on an incomplete blocker outside of the tasks being completed.
+pub(super) async fn complete_subtasks(
+    tx: &mut Transaction<'_, Postgres>,
+    task_ids: &[Uuid],
+    force: bool,
+) -> Result<()> {
+    let incomplete = sqlx::query_scalar!(
+        r#"WITH RECURSIVE subtree (id) AS (
+          SELECT id FROM tasks WHERE parent_task_id = ANY($1)
+          UNION
+          SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id
+        )
+        SELECT id AS "id!" FROM tasks
+        WHERE id IN (SELECT id FROM subtree) AND id <> ALL($1) AND status = 'incomplete'"#,
+        task_ids,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+    if incomplete.is_empty() {
+        return Ok(());
+    }
+    if !force {
+        let subject = if task_ids.len() == 1 {
+            "task has"
+        } else {
+            "tasks have"
+        };
+        return Err(Error::failed_precondition(format!(
+            "{subject} {} incomplete subtasks; force completion to complete them too",
+            incomplete.len()
+        )));
+    }
+    let completing: Vec<Uuid> = task_ids.iter().chain(&incomplete).copied().collect();
+    let blocked = count_blocked(tx, &completing).await?;
+    if blocked > 0 {
+        return Err(Error::failed_precondition(format!(
+            "{blocked} subtasks are blocked by incomplete tasks"
+        )));
+    }
+    let recurring = count_recurring(tx, &incomplete).await?;
+    if recurring > 0 {
+        return Err(Error::failed_precondition(format!(
+            "{recurring} subtasks recur; complete them one at a time"
+        )));
+    }
+    sqlx::query!(
+        "UPDATE tasks SET status = 'complete' WHERE id = ANY($1)",
+        &incomplete,
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Roll incomplete tasks up to their ancestors, reopening any that were complete.
+pub(super) async fn reopen_ancestors(
+    tx: &mut Transaction<'_, Postgres>,
+    task_ids: &[Uuid],
+) -> Result<()> {
+    sqlx::query!(
+        r#"WITH RECURSIVE ancestors (id, parent_task_id) AS (
+          SELECT id, parent_task_id FROM tasks WHERE id = ANY($1)
+          UNION
+          SELECT t.id, t.parent_task_id FROM tasks t JOIN ancestors a ON t.id = a.parent_task_id
+        )
+        UPDATE tasks SET status = 'incomplete'
+        WHERE id IN (SELECT id FROM ancestors WHERE id <> ALL($1)) AND status = 'complete'"#,
+        task_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
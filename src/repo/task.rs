@@ -1,10 +1,20 @@
-use super::Repo;
+use super::{
+    Repo,
+    dependency::select_task_blockers,
+    label::select_task_labels,
+    subtask::{check_parent, complete_subtasks, reopen_ancestors},
+};
 use crate::{
     Error, Result,
-    domain::{Status, StoryId, Task, TaskId},
+    domain::{
+        Keyset, KeysetPage, Limit, NewTask, Placement, Priority, Recurrence, Status, StoryId,
+        StoryImport, Task, TaskFilter, TaskId, TaskOrder, TaskResult, TaskSelection, View,
+        position_between,
+    },
 };
 use chrono::{DateTime, Utc};
-use std::str::FromStr;
+use sqlx::{PgConnection, Postgres, Transaction};
+use std::{collections::HashMap, str::FromStr};
 use uuid::Uuid;
 
 // Put some reasonable upper limit when querying tasks for a story.
@@ -15,8 +25,15 @@ const MAX_TASKS: i64 = 100;
 struct TaskEntity {
     id: Uuid,
     story_id: Uuid,
+    parent_task_id: Option<Uuid>,
     name: String,
+    description: Option<String>,
     status: String,
+    priority: String,
+    position: String,
+    due_at: Option<DateTime<Utc>>,
+    assignee: Option<String>,
+    recurrence: Option<String>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -27,8 +44,20 @@ impl From<TaskEntity> for Task {
         Self {
             id: TaskId(entity.id),
             story_id: StoryId(entity.story_id),
+            parent_id: entity.parent_task_id.map(TaskId),
             name: entity.name,
+            description: entity.description,
             status: Status::from_str(&entity.status).unwrap_or_default(),
+            priority: Priority::from_str(&entity.priority).unwrap_or_default(),
+            position: entity.position,
+            due_at: entity.due_at,
+            assignee: entity.assignee,
+            recurrence: entity
+                .recurrence
+                .and_then(|rule| Recurrence::from_str(&rule).ok()),
+            labels: Vec::new(),
+            blocker_ids: Vec::new(),
+            blocked: false,
             created_at: entity.created_at,
             updated_at: entity.updated_at,
         }
@@ -41,82 +70,851 @@ impl Repo {
     pub async fn fetch_task(&self, &TaskId(task_id): &TaskId) -> Result<Task> {
         let query = sqlx::query_as!(
             TaskEntity,
-            "SELECT id, story_id, name, status, created_at, updated_at FROM tasks WHERE id = $1",
+            r#"SELECT id, story_id, parent_task_id, name, description AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at
+            FROM tasks WHERE id = $1"#,
             task_id,
         );
-        query
+        let task = query
             .fetch_optional(self.db_ref())
             .await?
             .map(Task::from)
-            .ok_or_else(|| Error::not_found(format!("task not found: {task_id}")))
+            .ok_or_else(|| Error::not_found("task", task_id))?;
+        self.hydrate_task(task).await
     }
 
-    /// Select tasks for a story
-    pub async fn list_tasks(&self, &StoryId(story_id): &StoryId) -> Result<Vec<Task>> {
+    /// Select tasks for a story matching a filter, falling back to position order for ties.
+    /// Descriptions are only loaded in the full view.
+    pub async fn list_tasks(
+        &self,
+        &StoryId(story_id): &StoryId,
+        sort: TaskOrder,
+        filter: &TaskFilter,
+        view: View,
+    ) -> Result<Vec<Task>> {
+        let label_ids: Vec<Uuid> = filter.label_ids.iter().map(|id| id.0).collect();
+        let statuses: Vec<String> = filter.statuses.iter().map(ToString::to_string).collect();
         let query = sqlx::query_as!(
             TaskEntity,
-            r#"SELECT id, story_id, name, status, created_at, updated_at FROM tasks
-            WHERE story_id = $1 ORDER BY created_at LIMIT $2"#,
+            r#"SELECT id, story_id, parent_task_id, name,
+              CASE WHEN $5 THEN description END AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at
+            FROM tasks WHERE story_id = $1
+            AND (cardinality($4::uuid[]) = 0 OR id IN (
+              SELECT task_id FROM task_labels WHERE label_id = ANY($4)
+              GROUP BY task_id HAVING count(*) = cardinality($4)))
+            AND (cardinality($6::text[]) = 0 OR status = ANY($6))
+            AND ($7::timestamptz IS NULL OR created_at >= $7)
+            AND ($8::timestamptz IS NULL OR created_at < $8)
+            AND ($9::timestamptz IS NULL OR updated_at >= $9)
+            AND ($10::timestamptz IS NULL OR updated_at < $10)
+            ORDER BY
+              CASE WHEN $2 = 'priority'
+                THEN array_position(ARRAY['urgent', 'high', 'medium', 'low'], priority) END,
+              CASE WHEN $2 = 'created_at' THEN created_at END,
+              position
+            LIMIT $3"#,
             story_id,
+            sort.to_string(),
             MAX_TASKS,
+            &label_ids,
+            view.is_full(),
+            &statuses,
+            filter.created.after,
+            filter.created.before,
+            filter.updated.after,
+            filter.updated.before,
         );
         let entities = query.fetch_all(self.db_ref()).await?;
-        Ok(entities.into_iter().map(Task::from).collect())
+        self.hydrate_tasks(entities.into_iter().map(Task::from).collect())
+            .await
     }
 
-    /// Insert a new task
-    pub async fn create_task(
+    /// Select a page of incomplete tasks due before a time, across all stories.
+    pub async fn list_overdue_tasks(
         &self,
-        &StoryId(story_id): &StoryId,
-        name: impl Into<String>,
-        status: Status,
-    ) -> Result<Task> {
+        due_before: DateTime<Utc>,
+        after: Option<Keyset>,
+        limit: Limit,
+    ) -> Result<KeysetPage<Task>> {
+        let (after_due_at, after_id) = after.map(|Keyset(ts, id)| (ts, id)).unzip();
         let query = sqlx::query_as!(
             TaskEntity,
-            r#"INSERT INTO tasks (story_id, name, status) VALUES ($1, $2, $3)
-            RETURNING id, story_id, name, status, created_at, updated_at"#,
-            story_id,
-            name.into(),
-            status.to_string(),
+            r#"SELECT id, story_id, parent_task_id, name, NULL::text AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at
+            FROM tasks WHERE status = 'incomplete' AND due_at IS NOT NULL AND due_at < $1
+            AND ($2::timestamptz IS NULL OR (due_at, id) > ($2, $3))
+            ORDER BY due_at, id LIMIT $4"#,
+            due_before,
+            after_due_at,
+            after_id,
+            limit,
+        );
+        let entities = query.fetch_all(self.db_ref()).await?;
+        let next = match entities.last() {
+            Some(last) if entities.len() as Limit == limit => {
+                last.due_at.map(|due_at| Keyset(due_at, last.id))
+            }
+            _ => None,
+        };
+        let tasks = entities.into_iter().map(Task::from).collect();
+        let tasks = self.hydrate_tasks(tasks).await?;
+        Ok(KeysetPage(next, tasks))
+    }
+
+    /// Select a page of tasks assigned to a user across all stories, optionally with a status,
+    /// oldest first.
+    pub async fn list_assigned_tasks(
+        &self,
+        assignee: &str,
+        status: Option<Status>,
+        after: Option<Keyset>,
+        limit: Limit,
+    ) -> Result<KeysetPage<Task>> {
+        let (after_created_at, after_id) = after.map(|Keyset(ts, id)| (ts, id)).unzip();
+        let query = sqlx::query_as!(
+            TaskEntity,
+            r#"SELECT id, story_id, parent_task_id, name, NULL::text AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at
+            FROM tasks WHERE assignee = $1 AND ($2::text IS NULL OR status = $2)
+            AND ($3::timestamptz IS NULL OR (created_at, id) > ($3, $4))
+            ORDER BY created_at, id LIMIT $5"#,
+            assignee,
+            status.map(|status| status.to_string()),
+            after_created_at,
+            after_id,
+            limit,
+        );
+        let entities = query.fetch_all(self.db_ref()).await?;
+        let next = match entities.last() {
+            Some(last) if entities.len() as Limit == limit => {
+                Some(Keyset(last.created_at, last.id))
+            }
+            _ => None,
+        };
+        let tasks = entities.into_iter().map(Task::from).collect();
+        let tasks = self.hydrate_tasks(tasks).await?;
+        Ok(KeysetPage(next, tasks))
+    }
+
+    /// Insert a new task at the end of a story, reopening its ancestors if it is incomplete.
+    pub async fn create_task(&self, story_id: &StoryId, task: NewTask) -> Result<Task> {
+        let mut tx = self.db.begin().await?;
+        let last = lock_story_positions(&mut tx, story_id).await?;
+        if let Some(parent_id) = &task.parent_id {
+            check_parent(&mut tx, None, story_id, parent_id).await?;
+        }
+        let position = position_between(last.as_deref(), None);
+        let query = sqlx::query_as!(
+            TaskEntity,
+            r#"INSERT INTO tasks
+              (story_id, parent_task_id, name, description, status, priority, position, due_at,
+              recurrence)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, story_id, parent_task_id, name, description AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at"#,
+            story_id.0,
+            task.parent_id.map(|TaskId(id)| id),
+            task.name,
+            task.description,
+            task.status.to_string(),
+            task.priority.to_string(),
+            position,
+            task.due_at,
+            task.recurrence.map(|rule| rule.to_string()),
         );
-        let entity = query.fetch_one(self.db_ref()).await?;
+        let entity = query.fetch_one(&mut *tx).await?;
+        if task.status == Status::Incomplete {
+            reopen_ancestors(&mut tx, &[entity.id]).await?;
+        }
+        tx.commit().await?;
         Ok(Task::from(entity))
     }
 
-    /// Update task name and status.
-    pub async fn update_task(
+    /// Create several tasks at the end of a story with a single insert, in input order. Either
+    /// every task is created, or none are.
+    pub async fn create_tasks(&self, story_id: &StoryId, tasks: Vec<NewTask>) -> Result<Vec<Task>> {
+        if tasks.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut tx = self.db.begin().await?;
+        let last = lock_story_positions(&mut tx, story_id).await?;
+        let mut parent_ids: Vec<&TaskId> =
+            tasks.iter().filter_map(|t| t.parent_id.as_ref()).collect();
+        parent_ids.sort();
+        parent_ids.dedup();
+        for parent_id in parent_ids {
+            check_parent(&mut tx, None, story_id, parent_id).await?;
+        }
+
+        let tasks = insert_new_tasks(&mut tx, story_id, &tasks, last).await?;
+        tx.commit().await?;
+        Ok(tasks)
+    }
+
+    /// Move a task before or after a sibling, by updating only the moved task's position.
+    pub async fn reorder_task(&self, task_id: &TaskId, placement: &Placement) -> Result<Task> {
+        let task = self.fetch_task(task_id).await?;
+        let (field, anchor_id) = match placement {
+            Placement::Before(id) => ("before_task_id", id),
+            Placement::After(id) => ("after_task_id", id),
+        };
+        if anchor_id == task_id {
+            return Err(Error::invalid_args(
+                field,
+                "cannot place a task relative to itself",
+            ));
+        }
+
+        let mut tx = self.db.begin().await?;
+        lock_story_positions(&mut tx, &task.story_id).await?;
+        let anchor = sqlx::query_scalar!(
+            "SELECT position FROM tasks WHERE id = $1 AND story_id = $2",
+            anchor_id.0,
+            task.story_id.0,
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::invalid_args(field, "must be a task in the same story"))?;
+
+        let position = match placement {
+            Placement::Before(_) => {
+                let prev = sqlx::query_scalar!(
+                    r#"SELECT max(position) FROM tasks
+                    WHERE story_id = $1 AND position < $2 AND id <> $3"#,
+                    task.story_id.0,
+                    anchor,
+                    task_id.0,
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                position_between(prev.as_deref(), Some(&anchor))
+            }
+            Placement::After(_) => {
+                let next = sqlx::query_scalar!(
+                    r#"SELECT min(position) FROM tasks
+                    WHERE story_id = $1 AND position > $2 AND id <> $3"#,
+                    task.story_id.0,
+                    anchor,
+                    task_id.0,
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                position_between(Some(&anchor), next.as_deref())
+            }
+        };
+
+        let query = sqlx::query_as!(
+            TaskEntity,
+            r#"UPDATE tasks SET position = $1 WHERE id = $2
+            RETURNING id, story_id, parent_task_id, name, description AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at"#,
+            position,
+            task_id.0,
+        );
+        let entity = query
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| Error::not_found("task", task_id.0))?;
+        tx.commit().await?;
+        self.hydrate_task(Task::from(entity)).await
+    }
+
+    /// Update the mutable fields of a task, keeping the description if it was not loaded.
+    ///
+    /// A task cannot be completed while it waits on an incomplete blocker. Completion rolls down
+    /// to subtasks, which must already be complete unless forced. An incomplete task rolls up,
+    /// reopening its ancestors.
+    ///
+    /// Completing a recurring task hands its rule on to the next occurrence, which is created
+    /// at the end of the story and returned too, unless the recurrence is over.
+    pub async fn update_task(&self, task: &Task, force: bool) -> Result<(Task, Option<Task>)> {
+        let TaskId(task_id) = task.id;
+        let mut tx = self.db.begin().await?;
+        let last = lock_story_positions(&mut tx, &task.story_id).await?;
+        if let Some(parent_id) = &task.parent_id {
+            check_parent(&mut tx, Some(&task.id), &task.story_id, parent_id).await?;
+        }
+        let previous = sqlx::query_scalar!("SELECT status FROM tasks WHERE id = $1", task_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| Error::not_found("task", task_id))?;
+        let completed = task.status == Status::Complete && previous != task.status.to_string();
+        if completed && count_blocked(&mut tx, &[task_id]).await? > 0 {
+            return Err(Error::failed_precondition(
+                "task is blocked by incomplete tasks",
+            ));
+        }
+        if task.status == Status::Complete {
+            complete_subtasks(&mut tx, &[task_id], force).await?;
+        }
+        let recurrence = task.recurrence.filter(|_| !completed);
+        let query = sqlx::query_as!(
+            TaskEntity,
+            r#"UPDATE tasks SET parent_task_id = $1, name = $2,
+              description = coalesce($3, description), status = $4, priority = $5, due_at = $6,
+              recurrence = $7
+            WHERE id = $8
+            RETURNING id, story_id, parent_task_id, name, description AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at"#,
+            task.parent_id.as_ref().map(|TaskId(id)| *id),
+            task.name,
+            task.description,
+            task.status.to_string(),
+            task.priority.to_string(),
+            task.due_at,
+            recurrence.map(|rule| rule.to_string()),
+            task_id,
+        );
+        let entity = query
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| Error::not_found("task", task_id))?;
+        if task.status == Status::Incomplete {
+            reopen_ancestors(&mut tx, &[task_id]).await?;
+        }
+        let next_id = match task.recurrence.filter(|_| completed) {
+            Some(rule) => {
+                let due_at = entity.due_at.unwrap_or(entity.updated_at);
+                insert_next_occurrence(&mut tx, task_id, rule, due_at, last.as_deref()).await?
+            }
+            None => None,
+        };
+        tx.commit().await?;
+        let task = self.hydrate_task(Task::from(entity)).await?;
+        let next = match next_id {
+            Some(next_id) => Some(self.fetch_task(&TaskId(next_id)).await?),
+            None => None,
+        };
+        Ok((task, next))
+    }
+
+    /// Set the status of several tasks in one transaction, with the same subtask rules as a single
+    /// update. Recurring tasks must be completed one at a time, so their next occurrence is
+    /// created. Each selected task id gets a result, with no task if it was not found.
+    pub async fn update_task_statuses(
         &self,
-        &TaskId(task_id): &TaskId,
-        name: impl Into<String>,
+        selection: &TaskSelection,
         status: Status,
+        force: bool,
+    ) -> Result<Vec<TaskResult>> {
+        let mut tx = self.db.begin().await?;
+        let selected: Vec<Uuid> = match selection {
+            TaskSelection::Ids(ids) => ids.iter().map(|id| id.0).collect(),
+            TaskSelection::Story(story_id, filter) => {
+                select_task_ids(&mut tx, story_id, filter).await?
+            }
+        };
+        let found = sqlx::query_scalar!(
+            "SELECT id FROM tasks WHERE id = ANY($1) ORDER BY id FOR UPDATE",
+            &selected,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if status == Status::Complete {
+            let blocked = count_blocked(&mut tx, &found).await?;
+            if blocked > 0 {
+                return Err(Error::failed_precondition(format!(
+                    "{blocked} tasks are blocked by incomplete tasks"
+                )));
+            }
+            let recurring = count_recurring(&mut tx, &found).await?;
+            if recurring > 0 {
+                return Err(Error::failed_precondition(format!(
+                    "{recurring} tasks recur; complete them one at a time"
+                )));
+            }
+            complete_subtasks(&mut tx, &found, force).await?;
+        }
+        sqlx::query!(
+            "UPDATE tasks SET status = $2 WHERE id = ANY($1) AND status <> $2",
+            &found,
+            status.to_string(),
+        )
+        .execute(&mut *tx)
+        .await?;
+        if status == Status::Incomplete {
+            reopen_ancestors(&mut tx, &found).await?;
+        }
+        tx.commit().await?;
+
+        let mut tasks: HashMap<Uuid, Task> = self
+            .fetch_tasks(&found)
+            .await?
+            .into_iter()
+            .map(|t| (t.id.0, t))
+            .collect();
+        let results = selected
+            .into_iter()
+            .map(|id| TaskResult {
+                task_id: TaskId(id),
+                task: tasks.remove(&id),
+            })
+            .collect();
+        Ok(results)
+    }
+
+    /// Fetch several tasks by id, in no particular order.
+    async fn fetch_tasks(&self, task_ids: &[Uuid]) -> Result<Vec<Task>> {
+        let query = sqlx::query_as!(
+            TaskEntity,
+            r#"SELECT id, story_id, parent_task_id, name, description AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at
+            FROM tasks WHERE id = ANY($1)"#,
+            task_ids,
+        );
+        let entities = query.fetch_all(self.db_ref()).await?;
+        self.hydrate_tasks(entities.into_iter().map(Task::from).collect())
+            .await
+    }
+
+    /// Set or clear the assignee of a task.
+    pub async fn assign_task(
+        &self,
+        &TaskId(task_id): &TaskId,
+        assignee: Option<&str>,
     ) -> Result<Task> {
         let query = sqlx::query_as!(
             TaskEntity,
-            r#"UPDATE tasks SET name = $1, status = $2 WHERE id = $3
-            RETURNING id, story_id, name, status, created_at, updated_at"#,
-            name.into(),
-            status.to_string(),
+            r#"UPDATE tasks SET assignee = $1 WHERE id = $2
+            RETURNING id, story_id, parent_task_id, name, description AS "description?",
+              status, priority, position, due_at, assignee, recurrence, created_at, updated_at"#,
+            assignee,
             task_id,
         );
-        let entity = query.fetch_one(self.db_ref()).await?;
-        Ok(Task::from(entity))
+        let entity = query
+            .fetch_optional(self.db_ref())
+            .await?
+            .ok_or_else(|| Error::not_found("task", task_id))?;
+        self.hydrate_task(Task::from(entity)).await
     }
 
-    /// Delete a task.
+    /// Delete a task, its subtasks and their comments.
     pub async fn delete_task(&self, &TaskId(task_id): &TaskId) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        // Subtasks cascade, so remove comments for the whole subtree first.
+        sqlx::query!(
+            r#"WITH RECURSIVE subtree AS (
+              SELECT id FROM tasks WHERE id = $1
+              UNION ALL
+              SELECT t.id FROM tasks t JOIN subtree s ON t.parent_task_id = s.id
+            )
+            DELETE FROM task_comments WHERE task_id IN (SELECT id FROM subtree)"#,
+            task_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
         sqlx::query!("DELETE FROM tasks WHERE id = $1", task_id)
-            .execute(self.db_ref())
+            .execute(&mut *tx)
             .await?;
+
+        tx.commit().await?;
+
         Ok(())
     }
 }
 
+// Fill in the details of tasks that live in other tables.
+impl Repo {
+    /// Fill in labels and blockers for a batch of tasks, with one query each.
+    pub(crate) async fn hydrate_tasks(&self, tasks: Vec<Task>) -> Result<Vec<Task>> {
+        let mut conn = self.db_ref().acquire().await?;
+        hydrate_tasks_in(&mut conn, tasks).await
+    }
+
+    /// Fill in labels and blockers for a single task.
+    pub(crate) async fn hydrate_task(&self, task: Task) -> Result<Task> {
+        let mut tasks = self.hydrate_tasks(vec![task]).await?;
+        Ok(tasks.remove(0))
+    }
+}
+
+/// Fill in labels and blockers for tasks on one connection, which may be in a transaction.
+pub(super) async fn hydrate_tasks_in(
+    conn: &mut PgConnection,
+    mut tasks: Vec<Task>,
+) -> Result<Vec<Task>> {
+    let ids: Vec<Uuid> = tasks.iter().map(|t| t.id.0).collect();
+    let mut labels = select_task_labels(&mut *conn, &ids).await?;
+    let mut blockers = select_task_blockers(&mut *conn, &ids).await?;
+    for task in &mut tasks {
+        task.labels = labels.remove(&task.id.0).unwrap_or_default();
+        let task_blockers = blockers.remove(&task.id.0).unwrap_or_default();
+        task.blocked = task_blockers.iter().any(|b| b.incomplete);
+        task.blocker_ids = task_blockers.into_iter().map(|b| TaskId(b.id)).collect();
+    }
+    Ok(tasks)
+}
+
+/// New task fields split into columns, for a multi-row insert using unnest.
+struct NewTaskColumns {
+    parent_ids: Vec<Option<Uuid>>,
+    names: Vec<String>,
+    descriptions: Vec<String>,
+    statuses: Vec<String>,
+    priorities: Vec<String>,
+    positions: Vec<String>,
+    due_ats: Vec<Option<DateTime<Utc>>>,
+    recurrences: Vec<Option<String>>,
+}
+
+impl NewTaskColumns {
+    /// Create empty columns with room for a number of tasks.
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            parent_ids: Vec::with_capacity(capacity),
+            names: Vec::with_capacity(capacity),
+            descriptions: Vec::with_capacity(capacity),
+            statuses: Vec::with_capacity(capacity),
+            priorities: Vec::with_capacity(capacity),
+            positions: Vec::with_capacity(capacity),
+            due_ats: Vec::with_capacity(capacity),
+            recurrences: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Append a row for a task at a position.
+    fn push(&mut self, task: &NewTask, position: &str) {
+        self.parent_ids.push(task.parent_id.as_ref().map(|id| id.0));
+        self.names.push(task.name.clone());
+        self.descriptions.push(task.description.clone());
+        self.statuses.push(task.status.to_string());
+        self.priorities.push(task.priority.to_string());
+        self.positions.push(position.to_string());
+        self.due_ats.push(task.due_at);
+        self.recurrences
+            .push(task.recurrence.map(|rule| rule.to_string()));
+    }
+}
+
+/// Insert new tasks after the given last position of a story with a single insert, reopening the
+/// ancestors of incomplete subtasks. Tasks are returned in input order.
+pub(super) async fn insert_new_tasks(
+    tx: &mut Transaction<'_, Postgres>,
+    story_id: &StoryId,
+    tasks: &[NewTask],
+    mut last: Option<String>,
+) -> Result<Vec<Task>> {
+    let mut columns = NewTaskColumns::with_capacity(tasks.len());
+    for task in tasks {
+        let position = position_between(last.as_deref(), None);
+        columns.push(task, &position);
+        last = Some(position);
+    }
+    let query = sqlx::query_as!(
+        TaskEntity,
+        r#"INSERT INTO tasks
+          (story_id, parent_task_id, name, description, status, priority, position, due_at,
+          recurrence)
+        SELECT $1, t.parent_task_id, t.name, t.description, t.status, t.priority, t.position,
+          t.due_at, t.recurrence
+        FROM unnest($2::uuid[], $3::text[], $4::text[], $5::text[], $6::text[], $7::text[],
+          $8::timestamptz[], $9::text[])
+          AS t (parent_task_id, name, description, status, priority, position, due_at, recurrence)
+        RETURNING id, story_id, parent_task_id, name, description AS "description?",
+          status, priority, position, due_at, assignee, recurrence, created_at, updated_at"#,
+        story_id.0,
+        &columns.parent_ids as &[Option<Uuid>],
+        &columns.names,
+        &columns.descriptions,
+        &columns.statuses,
+        &columns.priorities,
+        &columns.positions,
+        &columns.due_ats as &[Option<DateTime<Utc>>],
+        &columns.recurrences as &[Option<String>],
+    );
+    let mut entities = query.fetch_all(&mut **tx).await?;
+    let incomplete: Vec<Uuid> = entities
+        .iter()
+        .filter(|e| e.parent_task_id.is_some() && e.status == Status::Incomplete.to_string())
+        .map(|e| e.id)
+        .collect();
+    if !incomplete.is_empty() {
+        reopen_ancestors(tx, &incomplete).await?;
+    }
+
+    // Positions were handed out in input order, and compare bytewise.
+    entities.sort_by(|a, b| a.position.cmp(&b.position));
+    Ok(entities.into_iter().map(Task::from).collect())
+}
+
+/// Insert the next occurrence of a completed recurring task after the last position in its
+/// story, as an incomplete copy with the same labels. Nothing is inserted, and no id returned,
+/// once the recurrence is over.
+async fn insert_next_occurrence(
+    tx: &mut Transaction<'_, Postgres>,
+    task_id: Uuid,
+    rule: Recurrence,
+    due_at: DateTime<Utc>,
+    last: Option<&str>,
+) -> Result<Option<Uuid>> {
+    let Some((next_due_at, next_rule)) = rule.next(due_at) else {
+        return Ok(None);
+    };
+    let position = position_between(last, None);
+    let next_id = sqlx::query_scalar!(
+        r#"INSERT INTO tasks (story_id, parent_task_id, name, description, status, priority,
+          position, due_at, assignee, recurrence)
+        SELECT story_id, parent_task_id, name, description, 'incomplete', priority, $2, $3,
+          assignee, $4
+        FROM tasks WHERE id = $1
+        RETURNING id"#,
+        task_id,
+        position,
+        next_due_at,
+        next_rule.to_string(),
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| Error::not_found("task", task_id))?;
+    sqlx::query!(
+        "INSERT INTO task_labels (task_id, label_id)
+        SELECT $2, label_id FROM task_labels WHERE task_id = $1",
+        task_id,
+        next_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+    reopen_ancestors(tx, &[next_id]).await?;
+    Ok(Some(next_id))
+}
+
+/// Select the ids of all tasks in a story matching a filter, in position order.
+async fn select_task_ids(
+    tx: &mut Transaction<'_, Postgres>,
+    &StoryId(story_id): &StoryId,
+    filter: &TaskFilter,
+) -> Result<Vec<Uuid>> {
+    sqlx::query!("SELECT id FROM stories WHERE id = $1", story_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| Error::not_found("story", story_id))?;
+    let label_ids: Vec<Uuid> = filter.label_ids.iter().map(|id| id.0).collect();
+    let statuses: Vec<String> = filter.statuses.iter().map(ToString::to_string).collect();
+    let ids = sqlx::query_scalar!(
+        r#"SELECT id FROM tasks WHERE story_id = $1
+        AND (cardinality($2::uuid[]) = 0 OR id IN (
+          SELECT task_id FROM task_labels WHERE label_id = ANY($2)
+          GROUP BY task_id HAVING count(*) = cardinality($2)))
+        AND (cardinality($3::text[]) = 0 OR status = ANY($3))
+        AND ($4::timestamptz IS NULL OR created_at >= $4)
+        AND ($5::timestamptz IS NULL OR created_at < $5)
+        AND ($6::timestamptz IS NULL OR updated_at >= $6)
+        AND ($7::timestamptz IS NULL OR updated_at < $7)
+        ORDER BY position"#,
+        story_id,
+        &label_ids,
+        &statuses,
+        filter.created.after,
+        filter.created.before,
+        filter.updated.after,
+        filter.updated.before,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(ids)
+}
+
+/// Count the incomplete tasks among those being completed that wait on an incomplete blocker
+/// outside of them.
+pub(super) async fn count_blocked(
+    tx: &mut Transaction<'_, Postgres>,
+    task_ids: &[Uuid],
+) -> Result<i64> {
+    let blocked = sqlx::query_scalar!(
+        r#"SELECT count(DISTINCT t.id) AS "count!" FROM tasks t
+        JOIN task_dependencies d ON d.task_id = t.id
+        JOIN tasks b ON b.id = d.blocker_id
+        WHERE t.id = ANY($1) AND t.status = 'incomplete'
+          AND b.status = 'incomplete' AND b.id <> ALL($1)"#,
+        task_ids,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(blocked)
+}
+
+/// Count the incomplete tasks among those being completed that have a recurrence rule.
+pub(super) async fn count_recurring(
+    tx: &mut Transaction<'_, Postgres>,
+    task_ids: &[Uuid],
+) -> Result<i64> {
+    let recurring = sqlx::query_scalar!(
+        r#"SELECT count(*) AS "count!" FROM tasks
+        WHERE id = ANY($1) AND status = 'incomplete' AND recurrence IS NOT NULL"#,
+        task_ids,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(recurring)
+}
+
+/// Select all tasks of some stories with descriptions, in position order within each story.
+pub(super) async fn select_story_tasks(
+    tx: &mut Transaction<'_, Postgres>,
+    story_ids: &[Uuid],
+) -> Result<Vec<Task>> {
+    let query = sqlx::query_as!(
+        TaskEntity,
+        r#"SELECT id, story_id, parent_task_id, name, description AS "description?",
+          status, priority, position, due_at, assignee, recurrence, created_at, updated_at
+        FROM tasks WHERE story_id = ANY($1) ORDER BY story_id, position"#,
+        story_ids,
+    );
+    let entities = query.fetch_all(&mut **tx).await?;
+    Ok(entities.into_iter().map(Task::from).collect())
+}
+
+/// Copy every task of a story into another story, with their labels and dependencies. Parents
+/// and blockers within the story point at their copies; blockers elsewhere are shared.
+pub(super) async fn copy_story_tasks(
+    tx: &mut Transaction<'_, Postgres>,
+    from_story_id: Uuid,
+    to_story_id: Uuid,
+    reset_statuses: bool,
+) -> Result<u64> {
+    let old_ids = sqlx::query_scalar!("SELECT id FROM tasks WHERE story_id = $1", from_story_id)
+        .fetch_all(&mut **tx)
+        .await?;
+    if old_ids.is_empty() {
+        return Ok(0);
+    }
+    let new_ids: Vec<Uuid> = old_ids.iter().map(|_| Uuid::new_v4()).collect();
+    let result = sqlx::query!(
+        r#"WITH copies AS (SELECT * FROM unnest($1::uuid[], $2::uuid[]) AS c(old_id, new_id))
+        INSERT INTO tasks (id, story_id, parent_task_id, name, description, status, priority,
+          position, due_at, assignee, recurrence)
+        SELECT c.new_id, $3, p.new_id, t.name, t.description,
+          CASE WHEN $4 THEN 'incomplete' ELSE t.status END, t.priority, t.position, t.due_at,
+          t.assignee, t.recurrence
+        FROM tasks t JOIN copies c ON c.old_id = t.id
+        LEFT JOIN copies p ON p.old_id = t.parent_task_id"#,
+        &old_ids,
+        &new_ids,
+        to_story_id,
+        reset_statuses,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query!(
+        r#"WITH copies AS (SELECT * FROM unnest($1::uuid[], $2::uuid[]) AS c(old_id, new_id))
+        INSERT INTO task_labels (task_id, label_id)
+        SELECT c.new_id, tl.label_id FROM task_labels tl JOIN copies c ON c.old_id = tl.task_id"#,
+        &old_ids,
+        &new_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query!(
+        r#"WITH copies AS (SELECT * FROM unnest($1::uuid[], $2::uuid[]) AS c(old_id, new_id))
+        INSERT INTO task_dependencies (task_id, blocker_id)
+        SELECT c.new_id, coalesce(b.new_id, d.blocker_id)
+        FROM task_dependencies d JOIN copies c ON c.old_id = d.task_id
+        LEFT JOIN copies b ON b.old_id = d.blocker_id"#,
+        &old_ids,
+        &new_ids,
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Insert the imported tasks of new stories in one statement, keeping any ids and timestamps
+/// given, and reopening the ancestors of incomplete subtasks. Each story's tasks are positioned
+/// in the order given. Returns the number inserted.
+pub(super) async fn insert_task_imports(
+    tx: &mut Transaction<'_, Postgres>,
+    story_ids: &[Uuid],
+    stories: &[StoryImport],
+) -> Result<u64> {
+    let capacity = stories.iter().map(|s| s.tasks.len()).sum();
+    let mut columns = NewTaskColumns::with_capacity(capacity);
+    let mut ids = Vec::with_capacity(capacity);
+    let mut task_story_ids = Vec::with_capacity(capacity);
+    let mut assignees = Vec::with_capacity(capacity);
+    let mut created = Vec::with_capacity(capacity);
+    let mut updated = Vec::with_capacity(capacity);
+    let mut incomplete = Vec::new();
+    for (story_id, story) in story_ids.iter().zip(stories) {
+        let mut last = None;
+        for import in &story.tasks {
+            let position = position_between(last.as_deref(), None);
+            columns.push(&import.task, &position);
+            last = Some(position);
+            let id = import.id.as_ref().map_or_else(Uuid::new_v4, |id| id.0);
+            if import.task.parent_id.is_some() && import.task.status == Status::Incomplete {
+                incomplete.push(id);
+            }
+            ids.push(id);
+            task_story_ids.push(*story_id);
+            assignees.push(import.assignee.clone());
+            created.push(import.created_at);
+            updated.push(import.updated_at);
+        }
+    }
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let result = sqlx::query!(
+        r#"INSERT INTO tasks (id, story_id, parent_task_id, name, description, status, priority,
+          position, due_at, assignee, recurrence, created_at, updated_at)
+        SELECT * FROM unnest($1::uuid[], $2::uuid[], $3::uuid[], $4::text[], $5::text[],
+          $6::text[], $7::text[], $8::text[], $9::timestamptz[], $10::text[], $11::text[],
+          $12::timestamptz[], $13::timestamptz[])"#,
+        &ids,
+        &task_story_ids,
+        &columns.parent_ids as &[Option<Uuid>],
+        &columns.names,
+        &columns.descriptions,
+        &columns.statuses,
+        &columns.priorities,
+        &columns.positions,
+        &columns.due_ats as &[Option<DateTime<Utc>>],
+        &assignees as &[Option<String>],
+        &columns.recurrences as &[Option<String>],
+        &created as &[Option<DateTime<Utc>>],
+        &updated as &[Option<DateTime<Utc>>],
+    )
+    .execute(&mut **tx)
+    .await?;
+    if !incomplete.is_empty() {
+        reopen_ancestors(tx, &incomplete).await?;
+    }
+    Ok(result.rows_affected())
+}
+
+/// Lock a story against concurrent task position changes, returning the last task position.
+async fn lock_story_positions(
+    tx: &mut Transaction<'_, Postgres>,
+    &StoryId(story_id): &StoryId,
+) -> Result<Option<String>> {
+    sqlx::query!("SELECT id FROM stories WHERE id = $1 FOR UPDATE", story_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| Error::not_found("story", story_id))?;
+    let last = sqlx::query_scalar!(
+        "SELECT max(position) FROM tasks WHERE story_id = $1",
+        story_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(last)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        domain::Status,
+        Error,
+        domain::{
+            KeysetPage, NewStory, NewTask, Placement, Status, StoryId, Task, TaskFilter, TaskId,
+            TaskOrder, TaskSelection, TimeRange, View,
+        },
         repo::{Repo, tests},
     };
+    use chrono::{Duration, Utc};
     use testcontainers::{ImageExt, runners::AsyncRunner};
     use testcontainers_modules::postgres::Postgres;
 
@@ -130,31 +928,175 @@ mod tests {
         let repo = Repo::new(pool);
 
         // Set up a story to put tasks under
-        let story = repo.create_story("Books To Read").await.unwrap();
+        let story = repo
+            .create_story(NewStory::new("Books To Read"))
+            .await
+            .unwrap();
         let story_id = story.id;
 
         // Create a task
         let task = repo
-            .create_task(&story_id, "Suttree", Status::Incomplete)
+            .create_task(&story_id, NewTask::new("Suttree", Status::Incomplete))
             .await
             .unwrap();
         assert_eq!(task.name, "Suttree");
 
+        // Creating a task under a missing story fails with not found.
+        let missing = StoryId(uuid::Uuid::new_v4());
+        let result = repo.create_task(&missing, NewTask::new("Blood Meridian", Status::Incomplete));
+        assert!(matches!(result.await, Err(Error::NotFound { .. })));
+
         // Query tasks for story.
-        let tasks = repo.list_tasks(&story_id).await.unwrap();
+        let tasks = repo
+            .list_tasks(
+                &story_id,
+                TaskOrder::Position,
+                &TaskFilter::default(),
+                View::Basic,
+            )
+            .await
+            .unwrap();
         assert_eq!(tasks.len(), 1);
 
-        // Set task status to complete
-        repo.update_task(&task.id, task.name, Status::Complete)
+        // Move a new task ahead of the first one
+        let first = repo
+            .create_task(&story_id, NewTask::new("Child of God", Status::Incomplete))
             .await
             .unwrap();
+        let placement = Placement::Before(task.id.clone());
+        repo.reorder_task(&first.id, &placement).await.unwrap();
+        let tasks = repo
+            .list_tasks(
+                &story_id,
+                TaskOrder::Position,
+                &TaskFilter::default(),
+                View::Basic,
+            )
+            .await
+            .unwrap();
+        assert_eq!(tasks[0].id, first.id);
+
+        // A parent cannot be completed while a subtask is incomplete, unless forced
+        let subtask = NewTask {
+            parent_id: Some(first.id.clone()),
+            ..NewTask::new("Outer Dark", Status::Incomplete)
+        };
+        let subtask = repo.create_task(&story_id, subtask).await.unwrap();
+        let first = repo.fetch_task(&first.id).await.unwrap();
+        let first = Task {
+            status: Status::Complete,
+            ..first
+        };
+        let result = repo.update_task(&first, false).await;
+        assert!(matches!(result, Err(Error::FailedPrecondition { .. })));
+        repo.update_task(&first, true).await.unwrap();
+        let subtask = repo.fetch_task(&subtask.id).await.unwrap();
+        assert_eq!(subtask.status, Status::Complete);
+
+        // Give the task a past due date, so it shows up as overdue
+        let due_at = Some(Utc::now() - Duration::days(1));
+        let (task, _) = repo
+            .update_task(&Task { due_at, ..task }, false)
+            .await
+            .unwrap();
+        let KeysetPage(_, overdue) = repo.list_overdue_tasks(Utc::now(), None, 10).await.unwrap();
+        assert_eq!(overdue.len(), 1);
+
+        // Assign the task, then find it by assignee
+        let task = repo.assign_task(&task.id, Some("cormac")).await.unwrap();
+        assert_eq!(task.assignee.as_deref(), Some("cormac"));
+        let KeysetPage(next, assigned) = repo
+            .list_assigned_tasks("cormac", Some(Status::Incomplete), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(assigned.len(), 1);
+        assert!(next.is_none());
+
+        // Set task status to complete
+        let task = Task {
+            status: Status::Complete,
+            ..task
+        };
+        repo.update_task(&task, false).await.unwrap();
         assert_eq!(
             repo.fetch_task(&task.id).await.unwrap().status,
             Status::Complete
         );
 
+        // Filter tasks by status and update window
+        let filter = TaskFilter {
+            statuses: vec![Status::Complete],
+            updated: TimeRange {
+                after: Some(Utc::now() - Duration::hours(1)),
+                before: None,
+            },
+            ..Default::default()
+        };
+        let tasks = repo
+            .list_tasks(&story_id, TaskOrder::Position, &filter, View::Basic)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 3);
+        let filter = TaskFilter {
+            statuses: vec![Status::Incomplete],
+            ..Default::default()
+        };
+        let tasks = repo
+            .list_tasks(&story_id, TaskOrder::Position, &filter, View::Basic)
+            .await
+            .unwrap();
+        assert!(tasks.is_empty());
+
         // Delete the task
         repo.delete_task(&task.id).await.unwrap();
         assert!(repo.fetch_task(&task.id).await.is_err());
+
+        // Create several tasks at once, returned in input order
+        let batch = vec![
+            NewTask::new("The Road", Status::Incomplete),
+            NewTask::new("No Country for Old Men", Status::Complete),
+        ];
+        let tasks = repo.create_tasks(&story_id, batch).await.unwrap();
+        assert_eq!(tasks[0].name, "The Road");
+        assert!(tasks[0].position < tasks[1].position);
+
+        // Complete several tasks at once, reporting missing ids
+        let missing = TaskId(uuid::Uuid::new_v4());
+        let ids = vec![tasks[0].id.clone(), missing.clone()];
+        let results = repo
+            .update_task_statuses(&TaskSelection::Ids(ids), Status::Complete, false)
+            .await
+            .unwrap();
+        assert_eq!(results[0].task.as_ref().unwrap().status, Status::Complete);
+        assert_eq!(results[1].task_id, missing);
+        assert!(results[1].task.is_none());
+
+        // Completing a recurring task creates its next occurrence, until the count runs out
+        let due_at = Utc::now();
+        let chore = NewTask {
+            due_at: Some(due_at),
+            recurrence: Some("FREQ=WEEKLY;COUNT=2".parse().unwrap()),
+            ..NewTask::new("Water the plants", Status::Incomplete)
+        };
+        let chore = repo.create_task(&story_id, chore).await.unwrap();
+        let done = Task {
+            status: Status::Complete,
+            ..chore
+        };
+        let (done, next) = repo.update_task(&done, false).await.unwrap();
+        assert_eq!(done.recurrence, None);
+        let next = next.unwrap();
+        assert_eq!(next.status, Status::Incomplete);
+        assert_eq!(
+            next.due_at,
+            done.due_at.map(|due_at| due_at + Duration::weeks(1))
+        );
+        assert_eq!(next.recurrence.unwrap().to_string(), "FREQ=WEEKLY;COUNT=1");
+        let last = Task {
+            status: Status::Complete,
+            ..next
+        };
+        let (_, next) = repo.update_task(&last, false).await.unwrap();
+        assert!(next.is_none());
     }
 }
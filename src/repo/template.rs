@@ -0,0 +1,174 @@
+use super::{Repo, story::insert_story, task::insert_new_tasks};
+use crate::{
+    Error, Result,
+    domain::{
+        NewStory, NewTask, NewTemplate, Page, PageParams, Status, Story, Task, Template, TemplateId,
+    },
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+/// The template entity object - used for query validation against the database.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct TemplateEntity {
+    id: Uuid,
+    name: String,
+    name_pattern: String,
+    task_names: Vec<String>,
+    seqno: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+// The repo should map the entity to the domain object in public functions.
+impl From<TemplateEntity> for Template {
+    fn from(entity: TemplateEntity) -> Self {
+        Self {
+            id: TemplateId(entity.id),
+            name: entity.name,
+            name_pattern: entity.name_pattern,
+            task_names: entity.task_names,
+            created_at: entity.created_at,
+            updated_at: entity.updated_at,
+        }
+    }
+}
+
+// Extend repo with queries related to story templates.
+impl Repo {
+    /// Select a page of templates, oldest first.
+    pub async fn list_templates(
+        &self,
+        PageParams(cursor, limit): PageParams,
+    ) -> Result<Page<Template>> {
+        let query = sqlx::query_as!(
+            TemplateEntity,
+            r#"SELECT id, name, name_pattern, task_names, seqno, created_at, updated_at
+            FROM story_templates WHERE seqno >= $1 ORDER BY seqno LIMIT $2"#,
+            cursor,
+            limit,
+        );
+        let entities = query.fetch_all(self.db_ref()).await?;
+        let next_cursor = entities.last().map(|t| t.seqno + 1).unwrap_or_default();
+        Ok(Page(
+            next_cursor,
+            entities.into_iter().map(Template::from).collect(),
+        ))
+    }
+
+    /// Insert a new template.
+    pub async fn create_template(&self, template: NewTemplate) -> Result<Template> {
+        let query = sqlx::query_as!(
+            TemplateEntity,
+            r#"INSERT INTO story_templates (name, name_pattern, task_names) VALUES ($1, $2, $3)
+            RETURNING id, name, name_pattern, task_names, seqno, created_at, updated_at"#,
+            template.name,
+            template.name_pattern,
+            &template.task_names,
+        );
+        let entity = query.fetch_one(self.db_ref()).await?;
+        Ok(Template::from(entity))
+    }
+
+    /// Create a story and its incomplete tasks from a template in one transaction, with
+    /// placeholders filled in for the given date.
+    pub async fn instantiate_template(
+        &self,
+        &TemplateId(template_id): &TemplateId,
+        date: NaiveDate,
+    ) -> Result<(Story, Vec<Task>)> {
+        let mut tx = self.db.begin().await?;
+        let template = sqlx::query_as!(
+            TemplateEntity,
+            r#"SELECT id, name, name_pattern, task_names, seqno, created_at, updated_at
+            FROM story_templates WHERE id = $1 FOR SHARE"#,
+            template_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(Template::from)
+        .ok_or_else(|| Error::not_found("template", template_id))?;
+        let (name, task_names) = template.render(date).map_err(Error::internal)?;
+
+        let story = insert_story(&mut *tx, NewStory::new(name)).await?;
+        let tasks: Vec<NewTask> = task_names
+            .into_iter()
+            .map(|name| NewTask::new(name, Status::Incomplete))
+            .collect();
+        let tasks = insert_new_tasks(&mut tx, &story.id, &tasks, None).await?;
+        tx.commit().await?;
+        Ok((story, tasks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{TaskFilter, TaskOrder, View},
+        repo::tests,
+    };
+
+    use testcontainers::{ImageExt, runners::AsyncRunner};
+    use testcontainers_modules::postgres::Postgres;
+
+    #[ignore]
+    #[tokio::test]
+    async fn integration_test() {
+        // Set up postgres test container backed repo
+        let image = Postgres::default().with_tag("17-alpine");
+        let container = image.start().await.unwrap();
+        let pool = tests::setup_pg_pool(&container).await;
+        let repo = Repo::new(pool);
+
+        // Create a template
+        let template = repo
+            .create_template(NewTemplate {
+                name: "Weekly review".into(),
+                name_pattern: "Review {week}".into(),
+                task_names: vec!["Inbox zero".into(), "Plan {date}".into()],
+            })
+            .await
+            .unwrap();
+        assert_eq!(template.task_names.len(), 2);
+
+        // Template names are unique
+        let err = repo
+            .create_template(NewTemplate {
+                name: "Weekly review".into(),
+                name_pattern: "Again".into(),
+                task_names: Vec::new(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::AlreadyExists { .. }));
+
+        // Query templates page
+        let Page(next_cursor, templates) = repo.list_templates(PageParams(1, 10)).await.unwrap();
+        assert_eq!(next_cursor, 2);
+        assert_eq!(templates, vec![template.clone()]);
+
+        // Instantiate the template into a story with tasks in order
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let (story, tasks) = repo.instantiate_template(&template.id, date).await.unwrap();
+        assert_eq!(story.name, "Review 2026-W43");
+        let names: Vec<&str> = tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["Inbox zero", "Plan 2026-10-19"]);
+        let listed = repo
+            .list_tasks(
+                &story.id,
+                TaskOrder::default(),
+                &TaskFilter::default(),
+                View::Basic,
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|t| t.status == Status::Incomplete));
+
+        // Unknown templates are not found
+        let missing = TemplateId(Uuid::new_v4());
+        let err = repo.instantiate_template(&missing, date).await.unwrap_err();
+        assert!(matches!(err, Error::NotFound { .. }));
+    }
+}
@@ -0,0 +1,187 @@
+use super::{
+    Repo,
+    label::attach_story_labels,
+    story::{declare_story_cursor, fetch_story_cursor, insert_stories},
+    task::{hydrate_tasks_in, insert_task_imports, select_story_tasks},
+};
+use crate::{
+    Result,
+    domain::{ImportSummary, Limit, Story, StoryExport, StoryId, StoryImport, Task},
+};
+use futures_util::{StreamExt, TryStreamExt, stream, stream::BoxStream};
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How many stories to read from the export cursor at a time.
+const EXPORT_BATCH_SIZE: Limit = 100;
+
+/// The repo and open export transaction, between batches.
+type ExportState = (Repo, Option<Transaction<'static, Postgres>>);
+
+// Extend repo with bulk import and export of stories and tasks.
+impl Repo {
+    /// Stream all stories, or just one, with their tasks in seqno order. Stories are read through
+    /// a cursor in a repeatable read snapshot a batch at a time, so the export is consistent
+    /// without being held in memory.
+    pub fn export_stories(
+        &self,
+        story_id: Option<StoryId>,
+    ) -> BoxStream<'static, Result<StoryExport>> {
+        let state = (Repo::new(self.db.clone()), None);
+        stream::try_unfold(state, move |(repo, tx)| {
+            next_export_batch(repo, tx, story_id.clone())
+        })
+        .map_ok(|exports| stream::iter(exports.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+    }
+
+    /// Insert stories with their tasks in one transaction, keeping any ids and timestamps given.
+    pub async fn import_stories(&self, stories: &[StoryImport]) -> Result<ImportSummary> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query("SET LOCAL gsdx.keep_timestamps = 'on'")
+            .execute(&mut *tx)
+            .await?;
+        let ids: Vec<Uuid> = stories
+            .iter()
+            .map(|s| s.id.as_ref().map_or_else(Uuid::new_v4, |id| id.0))
+            .collect();
+        insert_stories(&mut tx, &ids, stories).await?;
+        let tasks_imported = insert_task_imports(&mut tx, &ids, stories).await?;
+        tx.commit().await?;
+        Ok(ImportSummary {
+            story_ids: ids.into_iter().map(StoryId).collect(),
+            tasks_imported,
+        })
+    }
+
+    /// Start a read only snapshot with a cursor open over the stories to export.
+    async fn begin_export(
+        &self,
+        story_id: Option<&StoryId>,
+    ) -> Result<Transaction<'static, Postgres>> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+        declare_story_cursor(&mut tx, story_id).await?;
+        Ok(tx)
+    }
+
+    /// Pair a batch of stories with their tasks, loading labels for both.
+    async fn with_export_tasks(
+        &self,
+        tx: &mut Transaction<'static, Postgres>,
+        stories: Vec<Story>,
+    ) -> Result<Vec<StoryExport>> {
+        let ids: Vec<Uuid> = stories.iter().map(|s| s.id.0).collect();
+        let tasks = select_story_tasks(tx, &ids).await?;
+        let tasks = hydrate_tasks_in(tx, tasks).await?;
+        let mut tasks_by_story: HashMap<Uuid, Vec<Task>> = HashMap::new();
+        for task in tasks {
+            tasks_by_story
+                .entry(task.story_id.0)
+                .or_default()
+                .push(task);
+        }
+        let stories = attach_story_labels(&mut **tx, stories).await?;
+        let exports = stories
+            .into_iter()
+            .map(|story| StoryExport {
+                tasks: tasks_by_story.remove(&story.id.0).unwrap_or_default(),
+                story,
+            })
+            .collect();
+        Ok(exports)
+    }
+}
+
+/// Read the next batch of stories to export, opening the cursor on the first call and closing
+/// the snapshot once the cursor is exhausted.
+async fn next_export_batch(
+    repo: Repo,
+    tx: Option<Transaction<'static, Postgres>>,
+    story_id: Option<StoryId>,
+) -> Result<Option<(Vec<StoryExport>, ExportState)>> {
+    let mut tx = match tx {
+        Some(tx) => tx,
+        None => repo.begin_export(story_id.as_ref()).await?,
+    };
+    let stories = fetch_story_cursor(&mut tx, EXPORT_BATCH_SIZE).await?;
+    if stories.is_empty() {
+        tx.commit().await?;
+        return Ok(None);
+    }
+    let exports = repo.with_export_tasks(&mut tx, stories).await?;
+    Ok(Some((exports, (repo, Some(tx)))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{NewTask, Status, TaskId, TaskImport},
+        repo::tests,
+    };
+    use chrono::{TimeZone, Utc};
+    use testcontainers::{ImageExt, runners::AsyncRunner};
+    use testcontainers_modules::postgres::Postgres;
+
+    #[ignore]
+    #[tokio::test]
+    async fn integration_test() {
+        // Set up postgres test container backed repo
+        let image = Postgres::default().with_tag("17-alpine");
+        let container = image.start().await.unwrap();
+        let pool = tests::setup_pg_pool(&container).await;
+        let repo = Repo::new(pool);
+
+        // Import a story with a subtask, keeping the original ids and timestamps
+        let created_at = Utc.with_ymd_and_hms(2020, 1, 2, 3, 4, 5).unwrap();
+        let story_id = StoryId(Uuid::new_v4());
+        let parent_id = TaskId(Uuid::new_v4());
+        let subtask = NewTask {
+            parent_id: Some(parent_id.clone()),
+            ..NewTask::new("Read chapter one", Status::Complete)
+        };
+        let story = StoryImport {
+            id: Some(story_id.clone()),
+            name: "Books To Read".into(),
+            created_at: Some(created_at),
+            tasks: vec![
+                TaskImport {
+                    id: Some(parent_id.clone()),
+                    ..TaskImport::new(NewTask::new("Suttree", Status::Complete))
+                },
+                TaskImport::new(subtask),
+            ],
+            ..Default::default()
+        };
+        let summary = repo.import_stories(&[story]).await.unwrap();
+        assert_eq!(summary.story_ids[0], story_id);
+        assert_eq!(summary.tasks_imported, 2);
+
+        // Export the story with its tasks in order
+        let exports: Vec<StoryExport> = repo
+            .export_stories(Some(story_id.clone()))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].story.created_at, created_at);
+        assert_eq!(exports[0].tasks[0].id, parent_id);
+        assert_eq!(exports[0].tasks[1].parent_id, Some(parent_id));
+
+        // Importing the same ids again fails, and imports nothing
+        let story = StoryImport {
+            id: Some(story_id),
+            name: "Books To Read".into(),
+            ..Default::default()
+        };
+        let result = repo.import_stories(&[story]).await;
+        assert!(matches!(result, Err(crate::Error::AlreadyExists { .. })));
+        let exports: Vec<StoryExport> = repo.export_stories(None).try_collect().await.unwrap();
+        assert_eq!(exports.len(), 1);
+    }
+}
@@ -6,10 +6,10 @@ use tonic_health::{
 };
 
 /// Health check for the gRPC transport server. Makes sure the database is accessible.
-pub async fn health_check(reporter: HealthReporter, db: PgPool) {
+pub async fn health_check(reporter: HealthReporter, db: PgPool, interval: Duration) {
     log::info!("Starting health check loop");
     loop {
-        time::sleep(Duration::from_secs(2)).await;
+        time::sleep(interval).await;
         log::debug!("Running health check query");
         let query_fut = sqlx::query("SELECT 1").fetch_one(&db);
         let status = match query_fut.await {
@@ -1,61 +1,117 @@
 use crate::{
+    config::Config,
+    effect::{
+        CommentEffects, LabelEffects, SearchEffects, StoryEffects, TaskEffects, TemplateEffects,
+    },
     grpc::Gsdx,
+    memory::MemoryStore,
     proto::{GSDX_V1_FILE_DESCRIPTOR_SET, gsdx_service_server::GsdxServiceServer},
     repo::Repo,
-    service::{StoryService, TaskService},
+    service::{
+        CommentService, LabelService, SearchService, StoryService, TaskService, TemplateService,
+    },
 };
 
 use sqlx::postgres::PgPool;
-use std::{net::SocketAddr, sync::Arc};
+use std::sync::Arc;
 use tonic::{codec::CompressionEncoding::Gzip, transport::Server as TransportServer};
+use tonic_health::ServingStatus::Serving;
 
 mod health;
 use health::health_check;
 
 // The GSDX gRPC server
 pub struct Server {
-    pool: PgPool,
+    backend: Backend,
+    config: Config,
+}
+
+/// Where the server keeps stories and tasks.
+enum Backend {
+    Postgres(PgPool),
+    Memory(MemoryStore),
 }
 
 impl Server {
     /// Create a new server
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, config: Config) -> Self {
+        let backend = Backend::Postgres(pool);
+        Self { backend, config }
+    }
+
+    /// Create a new server that keeps everything in memory, without a database
+    pub fn in_memory(config: Config) -> Self {
+        let backend = Backend::Memory(MemoryStore::new());
+        Self { backend, config }
     }
 }
 
 impl Server {
-    /// Start the GSDX gRPC server on the given socket address.
-    pub async fn listen(
-        &self,
-        grpc_listen_addr: SocketAddr,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Start health check
+    /// Start the GSDX gRPC server on the configured socket address.
+    pub async fn listen(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // Set up gRPC health checks and reflection
         let (reporter, health_service) = tonic_health::server::health_reporter();
-        tokio::spawn(health_check(reporter, self.pool.clone()));
-
-        // Set up gRPC reflection
         let reflection_service = tonic_reflection::server::Builder::configure()
             .register_encoded_file_descriptor_set(GSDX_V1_FILE_DESCRIPTOR_SET)
             .build_v1()?;
+        let router = TransportServer::builder()
+            .add_service(health_service)
+            .add_service(reflection_service);
 
-        // Setup the GSDX service with gzip compression.
-        let repo = Arc::new(Repo::new(self.pool.clone()));
-        let story_service = StoryService::new(repo.clone());
-        let task_service = TaskService::new(repo);
-        let gsdx_grpc_service = GsdxServiceServer::new(Gsdx::new(story_service, task_service))
-            .send_compressed(Gzip)
-            .accept_compressed(Gzip);
+        // Setup the GSDX service on the configured backend.
+        let router = match &self.backend {
+            Backend::Postgres(pool) => {
+                let interval = self.config.health_interval();
+                tokio::spawn(health_check(reporter, pool.clone(), interval));
+                let repo = Arc::new(Repo::new(pool.clone()));
+                router.add_service(compressed(Gsdx::new(
+                    StoryService::new(repo.clone()),
+                    TaskService::new(repo.clone()),
+                    LabelService::new(repo.clone()),
+                    CommentService::new(repo.clone()),
+                    SearchService::new(repo.clone()),
+                    TemplateService::new(repo),
+                    self.config.limits,
+                )))
+            }
+            Backend::Memory(store) => {
+                // Memory is always available, so there is nothing to check.
+                log::warn!("Keeping data in memory; it will be lost when the server stops");
+                reporter.set_service_status("gsdx", Serving).await;
+                router.add_service(compressed(Gsdx::new(
+                    store.clone(),
+                    store.clone(),
+                    store.clone(),
+                    store.clone(),
+                    store.clone(),
+                    store.clone(),
+                    self.config.limits,
+                )))
+            }
+        };
 
         // Serve gRPC services
+        let grpc_listen_addr = self.config.listen_addr();
         log::info!("Server listening on {}", grpc_listen_addr);
-        TransportServer::builder()
-            .add_service(health_service)
-            .add_service(reflection_service)
-            .add_service(gsdx_grpc_service)
-            .serve(grpc_listen_addr)
-            .await?;
+        router.serve(grpc_listen_addr).await?;
 
         Ok(())
     }
 }
+
+/// Wrap the GSDX service with gzip compression.
+fn compressed<S, T, L, C, F, P>(
+    gsdx: Gsdx<S, T, L, C, F, P>,
+) -> GsdxServiceServer<Gsdx<S, T, L, C, F, P>>
+where
+    S: StoryEffects + 'static,
+    T: TaskEffects + 'static,
+    L: LabelEffects + 'static,
+    C: CommentEffects + 'static,
+    F: SearchEffects + 'static,
+    P: TemplateEffects + 'static,
+{
+    GsdxServiceServer::new(gsdx)
+        .send_compressed(Gzip)
+        .accept_compressed(Gzip)
+}
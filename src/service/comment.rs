@@ -0,0 +1,72 @@
+use crate::{
+    Error, Result,
+    domain::{Comment, CommentId, Page, PageParams, TaskId, View},
+    effect::CommentEffects,
+    repo::Repo,
+};
+use async_trait::async_trait;
+use futures_util::TryFutureExt;
+use std::sync::Arc;
+
+/// Comment service
+pub struct CommentService {
+    repo: Arc<Repo>,
+}
+
+impl CommentService {
+    /// Constructor
+    pub fn new(repo: Arc<Repo>) -> Self {
+        Self { repo }
+    }
+
+    /// Fetch a comment, ensuring it was written by the author.
+    async fn fetch_own(&self, comment_id: &CommentId, author: &str) -> Result<Comment> {
+        let comment = self.repo.fetch_comment(comment_id).await?;
+        if comment.author != author {
+            return Err(Error::permission_denied(
+                "only the author can change a comment",
+            ));
+        }
+        Ok(comment)
+    }
+}
+
+#[async_trait]
+impl CommentEffects for CommentService {
+    /// Add a comment to a task
+    async fn add(&self, task_id: TaskId, author: String, body: String) -> Result<Comment> {
+        self.repo
+            .fetch_task(&task_id)
+            .and_then(|_| self.repo.create_comment(&task_id, &author, &body))
+            .await
+    }
+
+    /// Fetch a page of comments for a task
+    async fn list(
+        &self,
+        task_id: TaskId,
+        page_params: PageParams,
+        view: View,
+    ) -> Result<Page<Comment>> {
+        self.repo
+            .fetch_task(&task_id)
+            .and_then(|_| self.repo.list_comments(&task_id, page_params, view))
+            .await
+    }
+
+    /// Replace the body of a comment, if written by the author
+    async fn edit(&self, comment_id: CommentId, author: String, body: String) -> Result<Comment> {
+        let comment = self.fetch_own(&comment_id, &author).await?;
+        if comment.body == body {
+            return Ok(comment);
+        }
+        self.repo.update_comment(&comment_id, &body).await
+    }
+
+    /// Delete a comment, if written by the author
+    async fn delete(&self, comment_id: CommentId, author: String) -> Result<()> {
+        self.fetch_own(&comment_id, &author)
+            .and_then(|_| self.repo.delete_comment(&comment_id))
+            .await
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{
+    Result,
+    domain::{Label, LabelId, LabelTarget},
+    effect::LabelEffects,
+    repo::Repo,
+};
+use async_trait::async_trait;
+use futures_util::TryFutureExt;
+use std::sync::Arc;
+
+/// Label service
+pub struct LabelService {
+    repo: Arc<Repo>,
+}
+
+impl LabelService {
+    /// Constructor
+    pub fn new(repo: Arc<Repo>) -> Self {
+        Self { repo }
+    }
+
+    /// Ensure a label target exists, so a missing story or task is reported as such.
+    async fn check_target(&self, target: &LabelTarget) -> Result<()> {
+        match target {
+            LabelTarget::Story(story_id) => self.repo.fetch_story(story_id).await.map(|_| ()),
+            LabelTarget::Task(task_id) => self.repo.fetch_task(task_id).await.map(|_| ()),
+        }
+    }
+}
+
+#[async_trait]
+impl LabelEffects for LabelService {
+    /// Create a new label
+    async fn create(&self, name: String) -> Result<Label> {
+        self.repo.create_label(name).await
+    }
+
+    /// Fetch all labels
+    async fn list(&self) -> Result<Vec<Label>> {
+        self.repo.list_labels().await
+    }
+
+    /// Delete an existing label
+    async fn delete(&self, label_id: LabelId) -> Result<()> {
+        self.repo
+            .fetch_label(&label_id)
+            .and_then(|_| self.repo.delete_label(&label_id))
+            .await
+    }
+
+    /// Attach labels to a story or task, returning all of its labels
+    async fn add(&self, target: LabelTarget, label_ids: Vec<LabelId>) -> Result<Vec<Label>> {
+        self.check_target(&target)
+            .and_then(|_| self.repo.add_labels(&target, &label_ids))
+            .and_then(|_| self.repo.target_labels(&target))
+            .await
+    }
+
+    /// Detach labels from a story or task, returning its remaining labels
+    async fn remove(&self, target: LabelTarget, label_ids: Vec<LabelId>) -> Result<Vec<Label>> {
+        self.check_target(&target)
+            .and_then(|_| self.repo.remove_labels(&target, &label_ids))
+            .and_then(|_| self.repo.target_labels(&target))
+            .await
+    }
+}
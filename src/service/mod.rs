@@ -5,3 +5,49 @@ pub use story::StoryService;
 // Expose the task effects
 mod task;
 pub use task::TaskService;
+
+// Expose the label effects
+mod label;
+pub use label::LabelService;
+
+// Expose the comment effects
+mod comment;
+pub use comment::CommentService;
+
+// Expose the search effects
+mod search;
+pub use search::SearchService;
+
+// Expose the template effects
+mod template;
+pub use template::TemplateService;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        effect::conformance,
+        repo::{Repo, tests},
+    };
+    use std::sync::Arc;
+
+    use testcontainers::{ImageExt, runners::AsyncRunner};
+    use testcontainers_modules::postgres::Postgres;
+
+    #[ignore]
+    #[tokio::test]
+    async fn conformance_test() {
+        // Set up postgres test container backed services
+        let image = Postgres::default().with_tag("17-alpine");
+        let container = image.start().await.unwrap();
+        let pool = tests::setup_pg_pool(&container).await;
+        let repo = Arc::new(Repo::new(pool));
+        let stories = StoryService::new(repo.clone());
+        let tasks = TaskService::new(repo.clone());
+        let comments = CommentService::new(repo);
+
+        // Run the same checks as the in-memory effects
+        conformance::stories(&stories, &tasks, &comments).await;
+        conformance::tasks(&stories, &tasks).await;
+    }
+}
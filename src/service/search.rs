@@ -0,0 +1,31 @@
+use crate::{
+    Result,
+    domain::{Page, PageParams, SearchHit, SearchQuery},
+    effect::SearchEffects,
+    repo::Repo,
+};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Search service
+pub struct SearchService {
+    repo: Arc<Repo>,
+}
+
+impl SearchService {
+    /// Constructor
+    pub fn new(repo: Arc<Repo>) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl SearchEffects for SearchService {
+    /// Fetch a page of stories and tasks matching a query, most relevant first
+    async fn search(&self, query: SearchQuery, page_params: PageParams) -> Result<Page<SearchHit>> {
+        if let Some(story_id) = &query.story_id {
+            self.repo.fetch_story(story_id).await?;
+        }
+        self.repo.search(&query, page_params).await
+    }
+}
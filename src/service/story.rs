@@ -1,11 +1,14 @@
 use crate::{
     Result,
-    domain::{Page, PageParams, Story, StoryId},
+    domain::{
+        ImportSummary, NewStory, PageParams, Story, StoryChanges, StoryExport, StoryFilter,
+        StoryId, StoryImport, StoryKeyset, StoryPage, StorySort, View,
+    },
     effect::StoryEffects,
     repo::Repo,
 };
 use async_trait::async_trait;
-use futures_util::TryFutureExt;
+use futures_util::{TryFutureExt, stream::BoxStream};
 use std::sync::Arc;
 
 /// Story service
@@ -22,27 +25,56 @@ impl StoryService {
 
 #[async_trait]
 impl StoryEffects for StoryService {
-    /// Fetch a page of stories
-    async fn list(&self, page_params: PageParams) -> Result<Page<Story>> {
-        self.repo.list_stories(page_params).await
+    /// Fetch a story, with task progress if requested
+    async fn get(&self, story_id: StoryId, view: View, with_stats: bool) -> Result<Story> {
+        let mut story = self.repo.fetch_story(&story_id).await?;
+        if !view.is_full() {
+            story.description = None;
+        }
+        if !with_stats {
+            return Ok(story);
+        }
+        let mut stories = self.repo.with_story_stats(vec![story]).await?;
+        Ok(stories.remove(0))
+    }
+
+    /// Fetch a page of stories in sort order, with task progress if requested
+    async fn list(
+        &self,
+        page_params: PageParams,
+        filter: StoryFilter,
+        sort: StorySort,
+        after: Option<StoryKeyset>,
+        view: View,
+        with_stats: bool,
+    ) -> Result<StoryPage> {
+        let page = self
+            .repo
+            .list_stories(page_params, &filter, sort, after.as_ref(), view)
+            .await?;
+        if !with_stats {
+            return Ok(page);
+        }
+        let stories = self.repo.with_story_stats(page.stories).await?;
+        Ok(StoryPage { stories, ..page })
     }
 
     /// Create a new story
-    async fn create(&self, name: String) -> Result<Story> {
-        self.repo.create_story(name).await
+    async fn create(&self, story: NewStory) -> Result<Story> {
+        self.repo.create_story(story).await
     }
 
     /// Update an existing story
-    async fn update(&self, story_id: StoryId, name: String) -> Result<Story> {
+    async fn update(&self, story_id: StoryId, changes: StoryChanges) -> Result<Story> {
         self.repo
             .fetch_story(&story_id)
             .and_then(async |s| {
-                if s.name == name {
-                    log::debug!("Story name is the same, skipping update");
+                if changes.is_noop(&s) {
+                    log::debug!("Story is the same, skipping update");
                     Ok(s)
                 } else {
-                    log::debug!("Updating story name");
-                    self.repo.update_story(&story_id, name).await
+                    log::debug!("Updating story");
+                    self.repo.update_story(&changes.apply(s)).await
                 }
             })
             .await
@@ -55,4 +87,33 @@ impl StoryEffects for StoryService {
             .and_then(|_| self.repo.delete_story(&story_id))
             .await
     }
+
+    /// Copy a story with all of its tasks under a new name, optionally making every task
+    /// incomplete
+    async fn duplicate(
+        &self,
+        story_id: StoryId,
+        name: String,
+        reset_statuses: bool,
+    ) -> Result<Story> {
+        self.repo
+            .duplicate_story(&story_id, &name, reset_statuses)
+            .await
+    }
+
+    /// Stream all stories, or just one, with their tasks
+    async fn export(
+        &self,
+        story_id: Option<StoryId>,
+    ) -> Result<BoxStream<'static, Result<StoryExport>>> {
+        if let Some(story_id) = &story_id {
+            self.repo.fetch_story(story_id).await?;
+        }
+        Ok(self.repo.export_stories(story_id))
+    }
+
+    /// Import a batch of stories with their tasks, all or nothing
+    async fn import(&self, stories: Vec<StoryImport>) -> Result<ImportSummary> {
+        self.repo.import_stories(&stories).await
+    }
 }
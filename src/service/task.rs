@@ -1,10 +1,14 @@
 use crate::{
     Result,
-    domain::{Status, StoryId, Task, TaskId},
+    domain::{
+        Keyset, KeysetPage, Limit, NewTask, Placement, Status, StoryId, Task, TaskChanges,
+        TaskFilter, TaskId, TaskOrder, TaskResult, TaskSelection, View,
+    },
     effect::TaskEffects,
     repo::Repo,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures_util::TryFutureExt;
 use std::sync::Arc;
 
@@ -23,31 +27,99 @@ impl TaskService {
 #[async_trait]
 impl TaskEffects for TaskService {
     /// Fetch all tasks for a story
-    async fn list(&self, story_id: StoryId) -> Result<Vec<Task>> {
+    async fn list(
+        &self,
+        story_id: StoryId,
+        sort: TaskOrder,
+        filter: TaskFilter,
+        view: View,
+    ) -> Result<Vec<Task>> {
         self.repo
             .fetch_story(&story_id)
-            .and_then(|_| self.repo.list_tasks(&story_id))
+            .and_then(|_| self.repo.list_tasks(&story_id, sort, &filter, view))
             .await
     }
 
+    /// Fetch a page of incomplete tasks due before a time, across all stories
+    async fn list_overdue(
+        &self,
+        due_before: DateTime<Utc>,
+        after: Option<Keyset>,
+        limit: Limit,
+    ) -> Result<KeysetPage<Task>> {
+        self.repo.list_overdue_tasks(due_before, after, limit).await
+    }
+
+    /// Fetch a page of tasks assigned to a user across all stories, optionally with a status
+    async fn list_by_assignee(
+        &self,
+        assignee: String,
+        status: Option<Status>,
+        after: Option<Keyset>,
+        limit: Limit,
+    ) -> Result<KeysetPage<Task>> {
+        self.repo
+            .list_assigned_tasks(&assignee, status, after, limit)
+            .await
+    }
+
+    /// Set or clear the assignee of a task
+    async fn assign(&self, task_id: TaskId, assignee: Option<String>) -> Result<Task> {
+        self.repo.assign_task(&task_id, assignee.as_deref()).await
+    }
+
     /// Create a new task
-    async fn create(&self, story_id: StoryId, name: String, status: Status) -> Result<Task> {
-        self.repo.create_task(&story_id, name, status).await
+    async fn create(&self, story_id: StoryId, task: NewTask) -> Result<Task> {
+        self.repo.create_task(&story_id, task).await
+    }
+
+    /// Create several tasks in order, all or nothing
+    async fn batch_create(&self, story_id: StoryId, tasks: Vec<NewTask>) -> Result<Vec<Task>> {
+        self.repo.create_tasks(&story_id, tasks).await
+    }
+
+    /// Update an existing task, returning the next occurrence too when completing a recurring task
+    async fn update(&self, task_id: TaskId, changes: TaskChanges) -> Result<(Task, Option<Task>)> {
+        self.repo
+            .fetch_task(&task_id)
+            .and_then(async |t| {
+                let force = changes.force;
+                self.repo.update_task(&changes.apply(t), force).await
+            })
+            .await
     }
 
-    /// Update an existing task
-    async fn update(
+    /// Set the status of many tasks at once, all or nothing
+    async fn batch_update_status(
         &self,
-        task_id: TaskId,
-        maybe_name: Option<String>,
+        selection: TaskSelection,
         status: Status,
-    ) -> Result<Task> {
+        force: bool,
+    ) -> Result<Vec<TaskResult>> {
+        self.repo
+            .update_task_statuses(&selection, status, force)
+            .await
+    }
+
+    /// Move a task before or after a sibling task
+    async fn reorder(&self, task_id: TaskId, placement: Placement) -> Result<Task> {
+        self.repo.reorder_task(&task_id, &placement).await
+    }
+
+    /// Make a task wait on a blocker task
+    async fn add_dependency(&self, task_id: TaskId, blocker_id: TaskId) -> Result<Task> {
+        self.repo
+            .add_dependency(&task_id, &blocker_id)
+            .and_then(|_| self.repo.fetch_task(&task_id))
+            .await
+    }
+
+    /// Stop a task waiting on a blocker task
+    async fn remove_dependency(&self, task_id: TaskId, blocker_id: TaskId) -> Result<Task> {
         self.repo
             .fetch_task(&task_id)
-            .and_then(|t| {
-                let name = maybe_name.unwrap_or(t.name);
-                self.repo.update_task(&task_id, name, status)
-            })
+            .and_then(|_| self.repo.remove_dependency(&task_id, &blocker_id))
+            .and_then(|_| self.repo.fetch_task(&task_id))
             .await
     }
 
@@ -0,0 +1,40 @@
+use crate::{
+    Result,
+    domain::{NewTemplate, Page, PageParams, Story, Task, Template, TemplateId},
+    effect::TemplateEffects,
+    repo::Repo,
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::sync::Arc;
+
+/// Template service
+pub struct TemplateService {
+    repo: Arc<Repo>,
+}
+
+impl TemplateService {
+    /// Constructor
+    pub fn new(repo: Arc<Repo>) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl TemplateEffects for TemplateService {
+    /// Create a new template
+    async fn create(&self, template: NewTemplate) -> Result<Template> {
+        self.repo.create_template(template).await
+    }
+
+    /// Fetch a page of templates
+    async fn list(&self, page_params: PageParams) -> Result<Page<Template>> {
+        self.repo.list_templates(page_params).await
+    }
+
+    /// Create a story and its tasks from a template, filling in placeholders for today (UTC)
+    async fn instantiate(&self, template_id: TemplateId) -> Result<(Story, Vec<Task>)> {
+        let today = Utc::now().date_naive();
+        self.repo.instantiate_template(&template_id, today).await
+    }
+}
@@ -0,0 +1,445 @@
+use crate::{
+    Error, Result,
+    config::Limits,
+    domain::{Status, StoryExport, StoryId, StoryImport, TaskNode},
+    error::Violation,
+    grpc::validate::validate_import_story,
+    repo::Repo,
+};
+
+use clap::ValueEnum;
+use futures_util::TryStreamExt;
+use std::{
+    collections::HashSet,
+    fmt,
+    io::{BufRead, Read, Write},
+};
+
+// File records and their conversions.
+mod record;
+use record::{CsvRow, StoryRecord, TaskRecord};
+
+/// File formats stories can be exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum ExportFormat {
+    /// JSON Lines, one story with its tasks per line
+    Json,
+    /// One row per task, with the fields of its story
+    Csv,
+    /// Task checklists, for reading rather than importing
+    Markdown,
+}
+
+/// File formats stories can be imported from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum ImportFormat {
+    /// JSON Lines, one story with its tasks per line
+    Json,
+    /// One row per task, with the fields of its story
+    Csv,
+}
+
+/// A problem with an import file, on the line it was found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineError {
+    pub line: u64,
+    pub message: String,
+}
+
+// Display as "line N: message".
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// What an import created, or would create on a dry run. Nothing is imported when there are
+/// errors.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub stories: usize,
+    pub tasks: usize,
+    pub errors: Vec<LineError>,
+}
+
+/// Write all stories, or a single story, to `out`, returning how many were written. Ids and
+/// timestamps are written as stored, so json and csv exports can be imported as they were.
+pub async fn export_stories(
+    repo: &Repo,
+    format: ExportFormat,
+    story_id: Option<StoryId>,
+    out: impl Write,
+) -> Result<u64> {
+    if let Some(story_id) = &story_id {
+        repo.fetch_story(story_id).await?;
+    }
+    let mut writer = ExportWriter::new(format, out);
+    let mut stories = repo.export_stories(story_id);
+    let mut count = 0;
+    while let Some(export) = stories.try_next().await? {
+        writer.write(export)?;
+        count += 1;
+    }
+    writer.finish()?;
+    Ok(count)
+}
+
+/// Validate every story in `input` by the same rules as the ImportStories RPC, then import them
+/// all in one transaction unless there are errors or this is a dry run. A dry run only checks
+/// the file, so it cannot catch ids that already exist.
+pub async fn import_stories(
+    repo: &Repo,
+    format: ImportFormat,
+    input: impl BufRead,
+    limits: &Limits,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let (located, mut errors) = match format {
+        ImportFormat::Json => read_json(input),
+        ImportFormat::Csv => read_csv(input),
+    };
+    let stories = validate_stories(located, limits, &mut errors);
+    errors.sort_by_key(|err| err.line);
+    let report = ImportReport {
+        stories: stories.len(),
+        tasks: stories.iter().map(|story| story.tasks.len()).sum(),
+        errors,
+    };
+    if report.errors.is_empty() && !dry_run {
+        repo.import_stories(&stories).await?;
+    }
+    Ok(report)
+}
+
+/// A story read from a file, with the lines its fields came from.
+#[derive(Debug)]
+struct Located {
+    line: u64,
+    /// The line of each task, when tasks have lines of their own.
+    task_lines: Option<Vec<u64>>,
+    record: StoryRecord,
+}
+
+impl Located {
+    fn push_task(&mut self, line: u64, task: TaskRecord) {
+        self.task_lines.get_or_insert_with(Vec::new).push(line);
+        self.record.tasks.push(task);
+    }
+
+    /// Report a violation on the line of the field it is about. Task fields on lines of their
+    /// own drop the `tasks[i].` prefix, since the line already says which task.
+    fn locate(&self, violation: Violation) -> LineError {
+        let task = violation.field.strip_prefix("tasks[").and_then(|rest| {
+            let (index, field) = rest.split_once(']')?;
+            Some((index.parse::<usize>().ok()?, field.trim_start_matches('.')))
+        });
+        match (task, &self.task_lines) {
+            (Some((index, field)), Some(lines)) if index < lines.len() => LineError {
+                line: lines[index],
+                message: format!("{field}: {}", violation.description),
+            },
+            _ => LineError {
+                line: self.line,
+                message: violation.to_string(),
+            },
+        }
+    }
+}
+
+// Validate located stories, collecting violations and ids used more than once in the file.
+fn validate_stories(
+    located: Vec<Located>,
+    limits: &Limits,
+    errors: &mut Vec<LineError>,
+) -> Vec<StoryImport> {
+    let mut story_ids = HashSet::new();
+    let mut task_ids = HashSet::new();
+    let mut stories = Vec::with_capacity(located.len());
+    for mut story in located {
+        let record = std::mem::take(&mut story.record);
+        match validate_import_story(record.into(), limits) {
+            Ok(import) => {
+                if let Some(id) = &import.id
+                    && !story_ids.insert(id.clone())
+                {
+                    errors.push(story.locate(duplicate("story_id", "story")));
+                }
+                for (i, task) in import.tasks.iter().enumerate() {
+                    if let Some(id) = &task.id
+                        && !task_ids.insert(id.clone())
+                    {
+                        errors
+                            .push(story.locate(duplicate(&format!("tasks[{i}].task_id"), "task")));
+                    }
+                }
+                stories.push(import);
+            }
+            Err(err) => errors.extend(err.into_violations().into_iter().map(|v| story.locate(v))),
+        }
+    }
+    stories
+}
+
+fn duplicate(field: &str, resource: &str) -> Violation {
+    Violation {
+        field: field.to_string(),
+        description: format!("duplicate {resource} id in file"),
+    }
+}
+
+// Read JSON Lines, skipping blank lines.
+fn read_json(input: impl BufRead) -> (Vec<Located>, Vec<LineError>) {
+    let mut located = Vec::new();
+    let mut errors = Vec::new();
+    for (n, result) in input.lines().enumerate() {
+        let line = n as u64 + 1;
+        let message = match result {
+            Ok(text) if text.trim().is_empty() => continue,
+            Ok(text) => match serde_json::from_str::<StoryRecord>(&text) {
+                Ok(record) => {
+                    located.push(Located {
+                        line,
+                        task_lines: None,
+                        record,
+                    });
+                    continue;
+                }
+                Err(err) => format!("invalid story: {err}"),
+            },
+            Err(err) => format!("unreadable line: {err}"),
+        };
+        errors.push(LineError { line, message });
+    }
+    (located, errors)
+}
+
+// Read CSV with a header row, grouping consecutive rows of the same story.
+fn read_csv(input: impl Read) -> (Vec<Located>, Vec<LineError>) {
+    let mut reader = csv::Reader::from_reader(input);
+    let mut located: Vec<Located> = Vec::new();
+    let mut errors = Vec::new();
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(err) => {
+            let message = format!("invalid header: {err}");
+            return (located, vec![LineError { line: 1, message }]);
+        }
+    };
+    for result in reader.records() {
+        let row = result.and_then(|record| {
+            let line = record.position().map_or(0, |pos| pos.line());
+            record
+                .deserialize::<CsvRow>(Some(&headers))
+                .map(|row| (line, row))
+        });
+        let (line, row) = match row {
+            Ok(row) => row,
+            Err(err) => {
+                let line = err.position().map_or(0, |pos| pos.line());
+                errors.push(LineError {
+                    line,
+                    message: csv_message(&err, &headers),
+                });
+                continue;
+            }
+        };
+        let continues = located
+            .last()
+            .is_some_and(|story| row.continues(&story.record));
+        let (record, task) = match row.split() {
+            Ok(split) => split,
+            Err(message) => {
+                errors.push(LineError { line, message });
+                continue;
+            }
+        };
+        if !continues {
+            located.push(Located {
+                line,
+                task_lines: Some(Vec::new()),
+                record,
+            });
+        }
+        if let (Some(task), Some(story)) = (task, located.last_mut()) {
+            story.push_task(line, task);
+        }
+    }
+    (located, errors)
+}
+
+// Describe a CSV error by column rather than position, which is reported as the line.
+fn csv_message(err: &csv::Error, headers: &csv::StringRecord) -> String {
+    match err.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => {
+            match err.field().and_then(|i| headers.get(i as usize)) {
+                Some(column) => format!("{column}: {}", err.kind()),
+                None => format!("invalid row: {}", err.kind()),
+            }
+        }
+        csv::ErrorKind::UnequalLengths {
+            expected_len, len, ..
+        } => format!("invalid row: found {len} fields, expected {expected_len}"),
+        _ => format!("invalid row: {err}"),
+    }
+}
+
+/// Writes exported stories in one format.
+enum ExportWriter<W: Write> {
+    Json(W),
+    Csv(Box<csv::Writer<W>>),
+    Markdown { out: W, first: bool },
+}
+
+impl<W: Write> ExportWriter<W> {
+    fn new(format: ExportFormat, out: W) -> Self {
+        match format {
+            ExportFormat::Json => Self::Json(out),
+            ExportFormat::Csv => Self::Csv(Box::new(csv::Writer::from_writer(out))),
+            ExportFormat::Markdown => Self::Markdown { out, first: true },
+        }
+    }
+
+    fn write(&mut self, export: StoryExport) -> Result<()> {
+        match self {
+            Self::Json(out) => {
+                serde_json::to_writer(&mut *out, &StoryRecord::from(export))
+                    .map_err(write_error)?;
+                writeln!(out).map_err(write_error)
+            }
+            Self::Csv(writer) => CsvRow::from_export(export)
+                .iter()
+                .try_for_each(|row| writer.serialize(row))
+                .map_err(write_error),
+            Self::Markdown { out, first } => {
+                if !*first {
+                    writeln!(out).map_err(write_error)?;
+                }
+                *first = false;
+                write_markdown(out, export).map_err(write_error)
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Json(mut out) | Self::Markdown { mut out, .. } => out.flush(),
+            Self::Csv(mut writer) => writer.flush(),
+        }
+        .map_err(write_error)
+    }
+}
+
+// Write a story as a heading, its description, and a nested checklist of its tasks.
+fn write_markdown(out: &mut impl Write, export: StoryExport) -> std::io::Result<()> {
+    let story = export.story;
+    writeln!(out, "# {}", story.name)?;
+    if let Some(description) = story.description.filter(|d| !d.trim().is_empty()) {
+        writeln!(out, "\n{}", description.trim_end())?;
+    }
+    let nodes = TaskNode::build(export.tasks);
+    if !nodes.is_empty() {
+        writeln!(out)?;
+    }
+    nodes
+        .iter()
+        .try_for_each(|node| write_markdown_task(out, node, 0))
+}
+
+fn write_markdown_task(out: &mut impl Write, node: &TaskNode, depth: usize) -> std::io::Result<()> {
+    let task = &node.task;
+    let check = if task.status == Status::Complete {
+        'x'
+    } else {
+        ' '
+    };
+    write!(
+        out,
+        "{:indent$}- [{check}] {}",
+        "",
+        task.name,
+        indent = depth * 2
+    )?;
+    let mut notes = Vec::new();
+    if let Some(due_at) = task.due_at {
+        notes.push(format!("due {}", due_at.format("%Y-%m-%d")));
+    }
+    if let Some(assignee) = &task.assignee {
+        notes.push(format!("@{assignee}"));
+    }
+    if !notes.is_empty() {
+        write!(out, " ({})", notes.join(", "))?;
+    }
+    writeln!(out)?;
+    node.subtasks
+        .iter()
+        .try_for_each(|subtask| write_markdown_task(out, subtask, depth + 1))
+}
+
+fn write_error(err: impl fmt::Display) -> Error {
+    Error::internal(format!("export write failed: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_json_line_errors() {
+        let input = "{\"name\": \"one\"}\n\nnot json\n{\"name\": \"two\", \"tasks\": [{\"name\": \"t\"}]}\n";
+        let (located, errors) = read_json(input.as_bytes());
+        assert_eq!(located.len(), 2);
+        assert_eq!(located[1].line, 4);
+        assert_eq!(located[1].record.tasks.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+    }
+
+    #[test]
+    fn read_csv_groups_rows() {
+        let input = "story_id,story_name,task_name,status\n\
+                     ,one,a,complete\n\
+                     ,one,b,\n\
+                     ,two,,\n\
+                     ,one,c,\n";
+        let (located, errors) = read_csv(input.as_bytes());
+        assert!(errors.is_empty());
+        let names: Vec<_> = located.iter().map(|s| s.record.name.as_str()).collect();
+        assert_eq!(names, ["one", "two", "one"]);
+        assert_eq!(located[0].task_lines, Some(vec![2, 3]));
+        assert!(located[1].record.tasks.is_empty());
+        assert_eq!(located[2].line, 5);
+    }
+
+    #[test]
+    fn validate_reports_task_lines() {
+        let input = "story_name,task_name,status\n\
+                     one,a,complete\n\
+                     one,,\n\
+                     one,c,maybe\n\
+                     ,d,\n";
+        let (located, errors) = read_csv(input.as_bytes());
+        assert!(errors.is_empty());
+        let mut errors = Vec::new();
+        let stories = validate_stories(located, &Limits::default(), &mut errors);
+        assert_eq!(stories.len(), 0);
+        let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        assert!(errors.contains(&"line 4: status: unknown status".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("line 5: name:")));
+    }
+
+    #[test]
+    fn validate_reports_duplicate_ids() {
+        let id = uuid::Uuid::new_v4();
+        let input = format!(
+            "{{\"id\": \"{id}\", \"name\": \"one\"}}\n{{\"id\": \"{id}\", \"name\": \"two\"}}\n"
+        );
+        let (located, _) = read_json(input.as_bytes());
+        let mut errors = Vec::new();
+        let stories = validate_stories(located, &Limits::default(), &mut errors);
+        assert_eq!(stories.len(), 2);
+        assert_eq!(
+            errors[0].to_string(),
+            "line 2: story_id: duplicate story id in file"
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    domain::{Priority, Status, StoryExport, Task},
+    proto::{ImportStoriesRequest, ImportTask, TaskPriority, TaskStatus},
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use prost_types::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A story with its tasks, written as one line of a JSON Lines file.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct StoryRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Tasks in position order, with parents before their subtasks.
+    pub tasks: Vec<TaskRecord>,
+}
+
+/// A task of a story record. Status and priority are their snake case names.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct TaskRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    pub name: String,
+    pub status: String,
+    pub priority: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// One CSV row: a task together with the fields of its story. A story without tasks is a row
+/// with empty task columns. Timestamps are kept as text so bad ones can be reported by column.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct CsvRow {
+    pub story_id: Option<String>,
+    pub story_name: String,
+    pub story_description: String,
+    pub story_created_at: String,
+    pub story_updated_at: String,
+    pub task_id: Option<String>,
+    pub parent_task_id: Option<String>,
+    pub task_name: String,
+    pub status: String,
+    pub priority: String,
+    pub task_description: String,
+    pub due_at: String,
+    pub assignee: Option<String>,
+    pub recurrence: Option<String>,
+    pub task_created_at: String,
+    pub task_updated_at: String,
+}
+
+impl CsvRow {
+    /// Flatten an exported story into rows, one per task.
+    pub fn from_export(export: StoryExport) -> Vec<CsvRow> {
+        let mut record = StoryRecord::from(export);
+        let tasks = std::mem::take(&mut record.tasks);
+        if tasks.is_empty() {
+            return vec![CsvRow::new(&record, TaskRecord::default())];
+        }
+        tasks
+            .into_iter()
+            .map(|task| CsvRow::new(&record, task))
+            .collect()
+    }
+
+    fn new(story: &StoryRecord, task: TaskRecord) -> Self {
+        Self {
+            story_id: story.id.clone(),
+            story_name: story.name.clone(),
+            story_description: story.description.clone(),
+            story_created_at: format_time(story.created_at),
+            story_updated_at: format_time(story.updated_at),
+            task_id: task.id,
+            parent_task_id: task.parent_id,
+            task_name: task.name,
+            status: task.status,
+            priority: task.priority,
+            task_description: task.description,
+            due_at: format_time(task.due_at),
+            assignee: task.assignee,
+            recurrence: task.recurrence,
+            task_created_at: format_time(task.created_at),
+            task_updated_at: format_time(task.updated_at),
+        }
+    }
+
+    /// Whether this row continues the given story: consecutive rows with the same story id and
+    /// name belong to one story.
+    pub fn continues(&self, story: &StoryRecord) -> bool {
+        self.story_id == story.id && self.story_name == story.name
+    }
+
+    /// Split the row into its story fields and its task, if the task columns are not empty.
+    pub fn split(self) -> Result<(StoryRecord, Option<TaskRecord>), String> {
+        let has_task =
+            self.task_id.is_some() || self.parent_task_id.is_some() || !self.task_name.is_empty();
+        let task = has_task.then_some(TaskRecord {
+            id: self.task_id,
+            parent_id: self.parent_task_id,
+            name: self.task_name,
+            status: self.status,
+            priority: self.priority,
+            description: self.task_description,
+            due_at: parse_time("due_at", &self.due_at)?,
+            assignee: self.assignee,
+            recurrence: self.recurrence,
+            created_at: parse_time("task_created_at", &self.task_created_at)?,
+            updated_at: parse_time("task_updated_at", &self.task_updated_at)?,
+        });
+        let story = StoryRecord {
+            id: self.story_id,
+            name: self.story_name,
+            description: self.story_description,
+            created_at: parse_time("story_created_at", &self.story_created_at)?,
+            updated_at: parse_time("story_updated_at", &self.story_updated_at)?,
+            tasks: Vec::new(),
+        };
+        Ok((story, task))
+    }
+}
+
+impl From<StoryExport> for StoryRecord {
+    fn from(export: StoryExport) -> Self {
+        let story = export.story;
+        Self {
+            id: Some(story.id.to_string()),
+            name: story.name,
+            description: story.description.unwrap_or_default(),
+            created_at: Some(story.created_at),
+            updated_at: Some(story.updated_at),
+            tasks: export.tasks.into_iter().map(TaskRecord::from).collect(),
+        }
+    }
+}
+
+impl From<Task> for TaskRecord {
+    fn from(task: Task) -> Self {
+        Self {
+            id: Some(task.id.to_string()),
+            parent_id: task.parent_id.map(|id| id.to_string()),
+            name: task.name,
+            status: task.status.to_string(),
+            priority: task.priority.to_string(),
+            description: task.description.unwrap_or_default(),
+            due_at: task.due_at,
+            assignee: task.assignee,
+            recurrence: task.recurrence.map(|rule| rule.to_string()),
+            created_at: Some(task.created_at),
+            updated_at: Some(task.updated_at),
+        }
+    }
+}
+
+// Records become import requests, so files are held to the same rules as the ImportStories RPC.
+impl From<StoryRecord> for ImportStoriesRequest {
+    fn from(record: StoryRecord) -> Self {
+        Self {
+            story_id: record.id,
+            name: record.name,
+            description: record.description,
+            created_at: record.created_at.map(to_timestamp),
+            updated_at: record.updated_at.map(to_timestamp),
+            tasks: record.tasks.into_iter().map(ImportTask::from).collect(),
+        }
+    }
+}
+
+impl From<TaskRecord> for ImportTask {
+    fn from(record: TaskRecord) -> Self {
+        Self {
+            task_id: record.id,
+            parent_task_id: record.parent_id,
+            name: record.name,
+            status: status_value(&record.status),
+            priority: priority_value(&record.priority),
+            description: record.description,
+            due_at: record.due_at.map(to_timestamp),
+            assignee: record.assignee,
+            recurrence: record.recurrence,
+            created_at: record.created_at.map(to_timestamp),
+            updated_at: record.updated_at.map(to_timestamp),
+        }
+    }
+}
+
+// Blank is unspecified; unknown names map to a value validation rejects.
+fn status_value(status: &str) -> i32 {
+    match status.trim() {
+        "" => TaskStatus::Unspecified as i32,
+        name => Status::from_str(name).map_or(-1, |status| TaskStatus::from(status) as i32),
+    }
+}
+
+// Blank is unspecified; unknown names map to a value validation rejects.
+fn priority_value(priority: &str) -> i32 {
+    match priority.trim() {
+        "" => TaskPriority::Unspecified as i32,
+        name => Priority::from_str(name).map_or(-1, |priority| TaskPriority::from(priority) as i32),
+    }
+}
+
+// Write a CSV timestamp the way serde writes JSON ones.
+fn format_time(time: Option<DateTime<Utc>>) -> String {
+    time.map(|t| t.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        .unwrap_or_default()
+}
+
+// Read an RFC 3339 CSV timestamp, where blank is missing.
+fn parse_time(column: &str, value: &str) -> Result<Option<DateTime<Utc>>, String> {
+    match value.trim() {
+        "" => Ok(None),
+        value => DateTime::parse_from_rfc3339(value)
+            .map(|time| Some(time.with_timezone(&Utc)))
+            .map_err(|err| format!("{column}: invalid timestamp: {err}")),
+    }
+}
+
+fn to_timestamp(dt: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: dt.timestamp(),
+        nanos: dt.timestamp_subsec_nanos() as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Story, StoryId, TaskId};
+    use uuid::Uuid;
+
+    fn story_export(tasks: Vec<Task>) -> StoryExport {
+        let story = Story {
+            id: StoryId(Uuid::new_v4()),
+            name: "Story".to_string(),
+            description: Some("notes".to_string()),
+            labels: Vec::new(),
+            stats: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        StoryExport { story, tasks }
+    }
+
+    fn task(story_id: &StoryId, name: &str, parent_id: Option<TaskId>) -> Task {
+        Task {
+            id: TaskId(Uuid::new_v4()),
+            story_id: story_id.clone(),
+            parent_id,
+            name: name.to_string(),
+            description: None,
+            status: Status::Complete,
+            priority: Priority::Urgent,
+            position: "a0".to_string(),
+            due_at: None,
+            assignee: Some("alice".to_string()),
+            recurrence: None,
+            labels: Vec::new(),
+            blocker_ids: Vec::new(),
+            blocked: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn csv_rows_round_trip() {
+        let mut export = story_export(Vec::new());
+        let mut parent = task(&export.story.id, "parent", None);
+        parent.recurrence = Some("FREQ=WEEKLY;INTERVAL=2".parse().unwrap());
+        let child = task(&export.story.id, "child", Some(parent.id.clone()));
+        export.tasks = vec![parent, child];
+        let rows = CsvRow::from_export(export);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].parent_task_id, rows[0].task_id);
+        assert_eq!(rows[1].recurrence, None);
+
+        let (story, task) = rows.into_iter().next().unwrap().split().unwrap();
+        assert_eq!(story.name, "Story");
+        assert_eq!(story.description, "notes");
+        let task = task.unwrap();
+        assert_eq!(task.status, "complete");
+        assert_eq!(task.priority, "urgent");
+        let task = ImportTask::from(task);
+        assert_eq!(task.recurrence.as_deref(), Some("FREQ=WEEKLY;INTERVAL=2"));
+    }
+
+    #[test]
+    fn csv_story_without_tasks() {
+        let rows = CsvRow::from_export(story_export(Vec::new()));
+        assert_eq!(rows.len(), 1);
+        let (story, task) = rows.into_iter().next().unwrap().split().unwrap();
+        assert!(story.id.is_some());
+        assert!(task.is_none());
+    }
+
+    #[test]
+    fn csv_invalid_timestamp() {
+        let row = CsvRow {
+            story_name: "Story".to_string(),
+            task_name: "Task".to_string(),
+            due_at: "tomorrow".to_string(),
+            ..CsvRow::default()
+        };
+        let err = row.split().unwrap_err();
+        assert!(err.starts_with("due_at: invalid timestamp"));
+    }
+
+    #[test]
+    fn task_record_enum_values() {
+        let record = TaskRecord {
+            status: "complete".to_string(),
+            priority: "whenever".to_string(),
+            ..TaskRecord::default()
+        };
+        let task = ImportTask::from(record);
+        assert_eq!(task.status, TaskStatus::Complete as i32);
+        assert_eq!(task.priority, -1);
+        let task = ImportTask::from(TaskRecord::default());
+        assert_eq!(task.status, TaskStatus::Unspecified as i32);
+        assert_eq!(task.priority, TaskPriority::Unspecified as i32);
+    }
+}